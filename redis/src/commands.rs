@@ -1,4 +1,9 @@
+pub(crate) mod active_expire;
+pub(crate) mod blocking;
 mod errors;
+mod geohash;
 mod handlers;
 pub(crate) mod hash_map;
 pub(crate) mod processor;
+pub(crate) mod session;
+pub(crate) mod utils;
@@ -0,0 +1,154 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use tokio::time::{MissedTickBehavior, interval};
+
+use crate::commands::hash_map::{Db, HASH_MAP, Key};
+
+/// How often the cycle runs, like the default `hz 10` of redis.
+const CYCLE_PERIOD: Duration = Duration::from_millis(100);
+/// Share of the period a single run may hold the keyspace lock for.
+const CYCLE_BUDGET: Duration = Duration::from_millis(25);
+/// Keys with a deadline sampled between two checks of the expired ratio.
+const KEYS_PER_LOOP: usize = 20;
+/// Percentage of expired keys among the sampled ones above which sampling goes on, as it
+/// is likely there are many more to reclaim.
+const ACCEPTABLE_STALE: usize = 10;
+/// Hashes visited between two checks of the budget.
+const HASHES_PER_BATCH: usize = 20;
+
+/// Counters of the expiration of keys, reported by `INFO stats`.
+pub(crate) struct ExpireStats {
+    /// Keys removed because their deadline passed, whether lazily or by the cycle.
+    pub expired_keys: AtomicU64,
+    /// Estimated percentage of the keys with a deadline that already expired, as `f64` bits.
+    pub expired_stale_perc: AtomicU64,
+    /// Runs that stopped because they used their whole budget.
+    pub time_cap_reached_count: AtomicU64,
+    /// Total time spent in the cycle, in microseconds.
+    pub cycle_time_used: AtomicU64,
+}
+
+pub(crate) static STATS: ExpireStats = ExpireStats {
+    expired_keys: AtomicU64::new(0),
+    expired_stale_perc: AtomicU64::new(0),
+    time_cap_reached_count: AtomicU64::new(0),
+    cycle_time_used: AtomicU64::new(0),
+};
+
+/// Where the cycle resumes within a database.
+#[derive(Default)]
+struct Cursors {
+    keys: Option<Key>,
+    hashes: Option<Key>,
+}
+
+/// Periodically reclaims what expired without being accessed since, so memory does not
+/// depend on clients touching stale data again.
+pub(crate) async fn run() {
+    let mut ticker = interval(CYCLE_PERIOD);
+    let mut cursors: Vec<Cursors> = vec![];
+    let mut next_db = 0;
+
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        // commands are not held up by a run with nothing to do, which is the common case
+        if !HASH_MAP.read_all().await.iter().any(Db::has_volatile) {
+            record_run(0, 0, false, Duration::ZERO);
+
+            continue;
+        }
+
+        let mut dbs = HASH_MAP.write_all().await;
+        let started_at = Instant::now();
+        let (mut sampled, mut expired) = (0, 0);
+        let mut is_time_capped = false;
+
+        cursors.resize_with(dbs.len(), Cursors::default);
+
+        // a run out of time resumes from the database it stopped at, so each gets its turn
+        for i in 0..dbs.len() {
+            let index = (next_db + i) % dbs.len();
+            let (db, cursors) = (&mut dbs[index], &mut cursors[index]);
+
+            is_time_capped = expire_db(db, cursors, started_at, &mut sampled, &mut expired);
+
+            if is_time_capped {
+                next_db = index;
+                break;
+            }
+        }
+
+        drop(dbs);
+
+        record_run(sampled, expired, is_time_capped, started_at.elapsed());
+    }
+}
+
+/// Runs the cycle over a single database, returning whether it ran out of time.
+fn expire_db(
+    db: &mut Db,
+    cursors: &mut Cursors,
+    started_at: Instant,
+    sampled: &mut usize,
+    expired: &mut usize,
+) -> bool {
+    // keeps sampling while a good share of the keys turns out expired, resuming where the
+    // previous run stopped so every key gets its turn
+    loop {
+        let (batch_sampled, batch_expired, cursor) =
+            db.expire_keys(cursors.keys.as_ref(), KEYS_PER_LOOP);
+
+        cursors.keys = cursor;
+        *sampled += batch_sampled;
+        *expired += batch_expired;
+
+        if started_at.elapsed() >= CYCLE_BUDGET {
+            return true;
+        }
+
+        if batch_sampled == 0 || batch_expired * 100 / batch_sampled <= ACCEPTABLE_STALE {
+            break;
+        }
+    }
+
+    loop {
+        cursors.hashes = db.expire_hash_fields(cursors.hashes.as_ref(), HASHES_PER_BATCH);
+
+        if started_at.elapsed() >= CYCLE_BUDGET {
+            return true;
+        }
+
+        if cursors.hashes.is_none() {
+            return false;
+        }
+    }
+}
+
+fn record_run(sampled: usize, expired: usize, is_time_capped: bool, elapsed: Duration) {
+    // like redis, a moving average favouring the history over a single run
+    let current = if sampled > 0 {
+        expired as f64 / sampled as f64 * 100.0
+    } else {
+        0.0
+    };
+    let previous = f64::from_bits(STATS.expired_stale_perc.load(Ordering::Relaxed));
+    let average = current * 0.05 + previous * 0.95;
+
+    STATS
+        .expired_stale_perc
+        .store(average.to_bits(), Ordering::Relaxed);
+
+    if is_time_capped {
+        STATS.time_cap_reached_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    STATS
+        .cycle_time_used
+        .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+}
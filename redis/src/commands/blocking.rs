@@ -0,0 +1,394 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{LazyLock, Mutex},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use tokio::sync::oneshot;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        handlers::{
+            lists::{ListEnd, move_element, pop_element, push_elements},
+            stream_groups::read_group,
+            streams::read_stream,
+        },
+        hash_map::{Db, object::Object, stream::StreamId},
+    },
+    resp::types::RespType,
+};
+
+#[derive(Debug)]
+pub(crate) enum BlockedOp {
+    /// `BLPOP`/`BRPOP`, replies with the key and the popped element.
+    Pop(ListEnd),
+    /// `BLMOVE`, replies with the moved element.
+    Move {
+        from: ListEnd,
+        destination: Bytes,
+        to: ListEnd,
+    },
+    /// `XREAD`, replies with the entries added after the id the client waits on for the key.
+    ReadStreams {
+        ids: Vec<(Bytes, StreamId)>,
+        count: Option<usize>,
+    },
+    /// `XREADGROUP` with `>`, replies with the entries the group delivers to the consumer.
+    ReadGroup {
+        group: Bytes,
+        consumer: Bytes,
+        count: Option<usize>,
+        no_ack: bool,
+    },
+}
+
+impl BlockedOp {
+    fn is_list_op(&self) -> bool {
+        matches!(self, BlockedOp::Pop(_) | BlockedOp::Move { .. })
+    }
+
+    fn is_stream_op(&self) -> bool {
+        matches!(
+            self,
+            BlockedOp::ReadStreams { .. } | BlockedOp::ReadGroup { .. }
+        )
+    }
+}
+
+#[derive(Debug)]
+struct BlockedClient {
+    db: usize,
+    keys: Vec<Bytes>,
+    op: BlockedOp,
+    sender: oneshot::Sender<RespType>,
+}
+
+/// Clients parked on list keys. Each key keeps its clients in the order they blocked,
+/// so whoever pushes to a key hands the new elements out first come, first served.
+#[derive(Debug, Default)]
+pub(crate) struct BlockedClients {
+    next_id: u64,
+    clients: HashMap<u64, BlockedClient>,
+    /// Queues of the keys, along with the index of their database.
+    queues: HashMap<(usize, Bytes), VecDeque<u64>>,
+}
+
+impl BlockedClients {
+    pub fn block(
+        &mut self,
+        db: usize,
+        keys: &[Bytes],
+        op: BlockedOp,
+    ) -> (u64, oneshot::Receiver<RespType>) {
+        let (sender, receiver) = oneshot::channel();
+        let id = self.next_id;
+
+        self.next_id += 1;
+
+        for key in keys {
+            self.queues
+                .entry((db, key.clone()))
+                .or_default()
+                .push_back(id);
+        }
+
+        self.clients.insert(
+            id,
+            BlockedClient {
+                db,
+                keys: keys.to_vec(),
+                op,
+                sender,
+            },
+        );
+
+        (id, receiver)
+    }
+
+    fn unblock(&mut self, id: u64) -> Option<BlockedClient> {
+        let client = self.clients.remove(&id)?;
+
+        for key in &client.keys {
+            let queue_key = (client.db, key.clone());
+
+            if let Some(queue) = self.queues.get_mut(&queue_key) {
+                queue.retain(|queued| *queued != id);
+
+                if queue.is_empty() {
+                    self.queues.remove(&queue_key);
+                }
+            }
+        }
+
+        Some(client)
+    }
+
+    /// Ids of the clients blocked on the key of the database by the matching operation, longest
+    /// waiting first.
+    fn blocked_on(
+        &self,
+        db: usize,
+        key: &Bytes,
+        is_matching: impl Fn(&BlockedOp) -> bool,
+    ) -> Vec<u64> {
+        self.queues
+            .get(&(db, key.clone()))
+            .map(|queue| {
+                queue
+                    .iter()
+                    .filter(|id| self.clients.get(id).is_some_and(|c| is_matching(&c.op)))
+                    .copied()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Keys of the database that clients are blocked on.
+    pub fn keys_of(&self, db: usize) -> Vec<Bytes> {
+        self.queues
+            .keys()
+            .filter(|(index, _)| *index == db)
+            .map(|(_, key)| key.clone())
+            .collect()
+    }
+}
+
+pub(crate) static BLOCKED_CLIENTS: LazyLock<Mutex<BlockedClients>> =
+    LazyLock::new(|| Mutex::new(BlockedClients::default()));
+
+tokio::task_local! {
+    /// Cancelled once the connection whose command is running is closed by its peer, which
+    /// ends the wait of a blocked client instead of handing data to a client that is gone.
+    pub(crate) static DISCONNECTION: CancellationToken;
+}
+
+/// Unblocks the client however its wait ends, including when it is cancelled, so that it is
+/// never left queued on its keys.
+struct UnblockOnDrop(u64);
+
+impl Drop for UnblockOnDrop {
+    fn drop(&mut self) {
+        BLOCKED_CLIENTS
+            .lock()
+            .expect("blocked clients lock is poisoned")
+            .unblock(self.0);
+    }
+}
+
+/// Parses a blocking timeout given in (possibly fractional) seconds, zero blocks forever.
+pub(crate) fn parse_timeout(timeout: &Bytes) -> Result<Option<Duration>, CommandExecutionError> {
+    let timeout = str::from_utf8(timeout)
+        .ok()
+        .and_then(|timeout| timeout.parse::<f64>().ok())
+        .filter(|timeout| timeout.is_finite())
+        .ok_or(CommandExecutionError::InvalidTimeout)?;
+
+    if timeout < 0.0 {
+        return Err(CommandExecutionError::NegativeTimeout);
+    }
+
+    Ok((timeout > 0.0).then(|| Duration::from_secs_f64(timeout)))
+}
+
+/// Waits until the client is served by a push, or the timeout elapses or the client
+/// disconnects, in which case `on_timeout` is returned. Must be called without holding the
+/// `HASH_MAP` lock.
+pub(crate) async fn wait_until_served(
+    id: u64,
+    mut receiver: oneshot::Receiver<RespType>,
+    timeout: Option<Duration>,
+    on_timeout: RespType,
+) -> RespType {
+    let _unblock = UnblockOnDrop(id);
+    let disconnection = DISCONNECTION
+        .try_with(CancellationToken::clone)
+        .unwrap_or_default();
+
+    let served = async {
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, &mut receiver).await.ok(),
+            None => Some((&mut receiver).await),
+        }
+    };
+
+    let reply = tokio::select! {
+        reply = served => reply,
+        _ = disconnection.cancelled() => None,
+    };
+
+    if let Some(Ok(reply)) = reply {
+        return reply;
+    }
+
+    // the client might have been served right when the timeout fired, closing the
+    // receiver makes any later attempt to serve it fail and give the element back
+    receiver.close();
+
+    if let Ok(reply) = receiver.try_recv() {
+        return reply;
+    }
+
+    on_timeout
+}
+
+/// Hands elements of a list or entries of a stream that just received new ones to the clients
+/// blocked on it. Must be called while holding the `HASH_MAP` write lock the write happened under.
+pub(crate) fn serve_blocked_clients(db: &mut Db, key: &Bytes) {
+    let mut blocked_clients = BLOCKED_CLIENTS
+        .lock()
+        .expect("blocked clients lock is poisoned");
+    let mut ready_keys = VecDeque::from([key.clone()]);
+
+    while let Some(key) = ready_keys.pop_front() {
+        match db.get(&key).map(|record| record.data()) {
+            Some(Object::List(_)) => {
+                serve_list_clients(db, &key, &mut blocked_clients, &mut ready_keys)
+            }
+            Some(Object::Stream(_)) => serve_stream_clients(db, &key, &mut blocked_clients),
+            _ => {}
+        }
+    }
+}
+
+fn serve_list_clients(
+    db: &mut Db,
+    key: &Bytes,
+    blocked_clients: &mut BlockedClients,
+    ready_keys: &mut VecDeque<Bytes>,
+) {
+    for id in blocked_clients.blocked_on(db.index(), key, BlockedOp::is_list_op) {
+        let has_elements = db
+            .get(key)
+            .and_then(|record| record.data().as_list().ok())
+            .is_some_and(|list| !list.is_empty());
+
+        if !has_elements {
+            break;
+        }
+
+        let Some(client) = blocked_clients.unblock(id) else {
+            continue;
+        };
+
+        if client.sender.is_closed() {
+            continue;
+        }
+
+        match client.op {
+            BlockedOp::Pop(end) => {
+                let Ok(Some(value)) = pop_element(db, key, end) else {
+                    continue;
+                };
+
+                let reply = RespType::Array(Some(vec![
+                    RespType::bulk_string(key),
+                    RespType::bulk_string(&value),
+                ]));
+
+                if client.sender.send(reply).is_err() {
+                    let _ = push_elements(db, key, [value], end);
+                }
+            }
+            BlockedOp::Move {
+                from,
+                destination,
+                to,
+            } => match move_element(db, key, &destination, from, to) {
+                Ok(Some(value)) => {
+                    if client.sender.send(RespType::bulk_string(&value)).is_err() {
+                        let _ = move_element(db, &destination, key, to, from);
+                    } else if destination != key {
+                        ready_keys.push_back(destination);
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let _ = client.sender.send(e.into());
+                }
+            },
+            BlockedOp::ReadStreams { .. } | BlockedOp::ReadGroup { .. } => {}
+        }
+    }
+}
+
+fn serve_stream_clients(db: &mut Db, key: &Bytes, blocked_clients: &mut BlockedClients) {
+    let ids = blocked_clients.blocked_on(db.index(), key, BlockedOp::is_stream_op);
+
+    for id in ids {
+        let reply = match blocked_clients.clients.get(&id).map(|client| &client.op) {
+            Some(BlockedOp::ReadStreams { ids, count }) => ids
+                .iter()
+                .find(|(blocked_key, _)| blocked_key == key)
+                .and_then(|(_, last_id)| read_stream(db, key, last_id, *count).transpose()),
+            Some(BlockedOp::ReadGroup {
+                group,
+                consumer,
+                count,
+                no_ack,
+            }) => read_group(db, key, group, consumer, *count, *no_ack).transpose(),
+            _ => None,
+        };
+
+        let Some(reply) = reply else {
+            continue;
+        };
+
+        if let Some(client) = blocked_clients.unblock(id) {
+            let _ = client.sender.send(match reply {
+                Ok(reply) => RespType::Array(Some(vec![reply])),
+                Err(e) => e.into(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(key: &Bytes) -> (u64, oneshot::Receiver<RespType>) {
+        BLOCKED_CLIENTS.lock().unwrap().block(
+            0,
+            std::slice::from_ref(key),
+            BlockedOp::Pop(ListEnd::Left),
+        )
+    }
+
+    fn is_blocked(key: &Bytes) -> bool {
+        !BLOCKED_CLIENTS
+            .lock()
+            .unwrap()
+            .blocked_on(0, key, BlockedOp::is_list_op)
+            .is_empty()
+    }
+
+    #[tokio::test]
+    async fn disconnected_client_is_unblocked() {
+        let key = Bytes::from_static(b"disconnected_client_is_unblocked");
+        let disconnection = CancellationToken::new();
+
+        let (id, receiver) = block(&key);
+        let wait = tokio::spawn(DISCONNECTION.scope(
+            disconnection.clone(),
+            wait_until_served(id, receiver, None, RespType::Array(None)),
+        ));
+
+        disconnection.cancel();
+
+        assert_eq!(wait.await.unwrap(), RespType::Array(None));
+        assert!(!is_blocked(&key));
+
+        // neither is one whose wait is dropped halfway
+        let (id, receiver) = block(&key);
+        let wait = tokio::spawn(wait_until_served(id, receiver, None, RespType::Array(None)));
+
+        tokio::task::yield_now().await;
+        wait.abort();
+
+        assert!(wait.await.is_err());
+        assert!(!is_blocked(&key));
+    }
+}
@@ -12,6 +12,176 @@ pub enum CommandExecutionError {
     UnsupportedCommandError,
     #[error("provided array has incorrect formatting and cannot be parsed into valid command")]
     IncorrectCommandFormatError,
+    #[error("WRONGTYPE Operation against a key holding the wrong kind of value")]
+    WrongType,
+    #[error("ERR value is not an integer or out of range")]
+    NotAnInteger,
+    #[error("ERR value is not a valid float")]
+    NotAFloat,
+    #[error("ERR hash value is not an integer")]
+    HashValueNotAnInteger,
+    #[error("ERR hash value is not a float")]
+    HashValueNotAFloat,
+    #[error("ERR increment or decrement would overflow")]
+    IncrementOverflow,
+    #[error("ERR decrement would overflow")]
+    DecrementOverflow,
+    #[error("ERR increment would produce NaN or Infinity")]
+    NanOrInfinity,
+    #[error("ERR value is out of range, must be positive")]
+    NotPositive,
+    #[error("ERR no such key")]
+    NoSuchKey,
+    #[error("ERR index out of range")]
+    IndexOutOfRange,
+    #[error("ERR timeout is not a float or out of range")]
+    InvalidTimeout,
+    #[error("ERR timeout is negative")]
+    NegativeTimeout,
+    #[error("ERR timeout is not an integer or out of range")]
+    InvalidIntegerTimeout,
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongNumberOfArguments(String),
+    #[error("ERR Invalid stream ID specified as stream command argument")]
+    InvalidStreamId,
+    #[error("ERR The ID specified in XADD must be greater than 0-0")]
+    StreamIdZero,
+    #[error("ERR The ID specified in XADD is equal or smaller than the target stream top item")]
+    StreamIdTooSmall,
+    #[error("ERR The stream has exhausted the last possible ID, unable to add more items")]
+    StreamExhausted,
+    #[error("ERR The MAXLEN argument must be >= 0.")]
+    NegativeMaxLen,
+    #[error("ERR syntax error, LIMIT cannot be used without the special ~ option")]
+    LimitWithoutApproximation,
+    #[error(
+        "ERR Unbalanced '{0}' list of streams: for each stream key an ID or '$' must be specified."
+    )]
+    UnbalancedStreams(String),
+    #[error("ERR syntax error")]
+    SyntaxError,
+    #[error("NOPROTO unsupported protocol version")]
+    UnsupportedProtocolVersion,
+    #[error("WRONGPASS invalid username-password pair or user is disabled.")]
+    WrongPassword,
+    #[error("ERR unknown subcommand '{0}'. Try {1} HELP.")]
+    UnknownSubcommand(String, String),
+    #[error("NOGROUP {0}")]
+    NoGroup(String),
+    #[error("BUSYGROUP Consumer Group name already exists")]
+    BusyGroup,
+    #[error(
+        "ERR The XGROUP subcommand requires the key to exist. Note that for CREATE you may want to use the MKSTREAM option to create an empty stream automatically."
+    )]
+    GroupKeyMissing,
+    #[error("ERR value for ENTRIESREAD must be positive or -1")]
+    InvalidEntriesRead,
+    #[error(
+        "ERR The $ ID is meaningless in the context of XREADGROUP: you want to read the history of this consumer by specifying a proper ID, or use the > ID to get new messages. The $ ID would just return an empty result set."
+    )]
+    LastIdInReadGroup,
+    #[error(
+        "ERR The > ID can be specified only when calling XREADGROUP using the GROUP <group> <consumer> option."
+    )]
+    NewIdOutsideGroup,
+    #[error("ERR Missing GROUP option for XREADGROUP")]
+    MissingGroupOption,
+    #[error("ERR Invalid min-idle-time argument for {0}")]
+    InvalidMinIdleTime(String),
+    #[error("ERR COUNT must be > 0")]
+    CountNotPositive,
+    #[error("ERR invalid expire time in '{0}' command")]
+    InvalidExpireTime(String),
+    #[error("ERR Mandatory argument FIELDS is missing or not at the right position")]
+    FieldsMissing,
+    #[error("ERR Parameter `numFields` should be greater than 0")]
+    NumFieldsNotPositive,
+    #[error("ERR The `numfields` parameter must match the number of arguments")]
+    NumFieldsMismatch,
+    #[error("ERR min or max is not a float")]
+    InvalidScoreRange,
+    #[error("ERR min or max not valid string range item")]
+    InvalidLexRange,
+    #[error("ERR XX and NX options at the same time are not compatible")]
+    XxAndNx,
+    #[error("ERR GT, LT, and/or NX options at the same time are not compatible")]
+    GtLtAndNx,
+    #[error("ERR INCR option supports a single increment-element pair")]
+    IncrSinglePair,
+    #[error("ERR resulting score is not a number (NaN)")]
+    ScoreIsNan,
+    #[error(
+        "ERR syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+    )]
+    LimitWithoutBy,
+    #[error("ERR syntax error, WITHSCORES not supported in combination with BYLEX")]
+    WithScoresByLex,
+    #[error("ERR invalid longitude,latitude pair {0:.6},{1:.6}")]
+    InvalidLonLat(f64, f64),
+    #[error("ERR unsupported unit provided. please use M, KM, FT, MI")]
+    UnsupportedUnit,
+    #[error("ERR radius cannot be negative")]
+    NegativeRadius,
+    #[error("ERR height or width cannot be negative")]
+    NegativeBox,
+    #[error("ERR could not decode requested zset member")]
+    UndecodableMember,
+    #[error("ERR exactly one of FROMMEMBER or FROMLONLAT can be specified for {0}")]
+    GeoCenterMissing(String),
+    #[error("ERR exactly one of BYRADIUS and BYBOX can be specified for {0}")]
+    GeoShapeMissing(String),
+    #[error(
+        "ERR STORE option in {0} is not compatible with WITHDIST, WITHHASH and WITHCOORD options"
+    )]
+    GeoStoreWithReplyOptions(String),
+    #[error("WRONGTYPE Key is not a valid HyperLogLog string value.")]
+    NotAHyperLogLog,
+    #[error("INVALIDOBJ Corrupted HLL object detected")]
+    CorruptedHyperLogLog,
+    #[error("ERR bit offset is not an integer or out of range")]
+    InvalidBitOffset,
+    #[error("ERR bit is not an integer or out of range")]
+    InvalidBit,
+    #[error("ERR The bit argument must be 1 or 0.")]
+    InvalidBitPosBit,
+    #[error("ERR BITOP NOT must be called with a single source key.")]
+    BitOpNotSingleSource,
+    #[error(
+        "ERR Invalid bitfield type. Use something like i16 u8. Note that u64 is not supported but i64 is."
+    )]
+    InvalidBitfieldType,
+    #[error("ERR Invalid OVERFLOW type specified")]
+    InvalidOverflowType,
+    #[error("ERR BITFIELD_RO only supports the GET subcommand")]
+    BitfieldReadOnly,
+    #[error("ERR offset is out of range")]
+    OffsetOutOfRange,
+    #[error("ERR string exceeds maximum allowed size (proto-max-bulk-len)")]
+    StringTooLong,
+    #[error("ERR NX and XX, GT or LT options at the same time are not compatible")]
+    NxAndXxGtLt,
+    #[error("ERR GT and LT options at the same time are not compatible")]
+    GtAndLt,
+    #[error("ERR Unsupported option {0}")]
+    UnsupportedOption(String),
+    #[error("ERR invalid cursor")]
+    InvalidCursor,
+    #[error("ERR unknown type name '{0}'")]
+    UnknownTypeName(String),
+    #[error("ERR DB index is out of range")]
+    DbIndexOutOfRange,
+    #[error("ERR invalid first DB index")]
+    InvalidFirstDbIndex,
+    #[error("ERR invalid second DB index")]
+    InvalidSecondDbIndex,
+    #[error("ERR source and destination objects are the same")]
+    SameObject,
+    #[error("ERR Background save already in progress")]
+    SaveInProgress,
+    #[error("ERR")]
+    SaveFailed,
+    #[error("ERR CONFIG SET failed (possibly related to argument '{0}') - {1}")]
+    ConfigSetFailed(String, String),
 }
 
 impl From<CommandExecutionError> for RespType {
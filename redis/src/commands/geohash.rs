@@ -0,0 +1,397 @@
+//! Geohash encoding of coordinates into sorted set scores, following redis so that scores,
+//! distances and search areas are the same.
+
+use std::f64::consts::PI;
+
+pub(crate) const LON_MIN: f64 = -180.0;
+pub(crate) const LON_MAX: f64 = 180.0;
+/// Web mercator limits, beyond which coordinates cannot be indexed.
+pub(crate) const LAT_MIN: f64 = -85.05112878;
+pub(crate) const LAT_MAX: f64 = 85.05112878;
+
+/// Scores are 52 bits, 26 for each coordinate.
+const STEP_MAX: u8 = 26;
+const EARTH_RADIUS_IN_METERS: f64 = 6372797.560856;
+const MERCATOR_MAX: f64 = 20037726.37;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct GeoHash {
+    bits: u64,
+    step: u8,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Area {
+    lon_min: f64,
+    lon_max: f64,
+    lat_min: f64,
+    lat_max: f64,
+}
+
+/// Spreads the bits of `lat` over the even positions and those of `lon` over the odd ones.
+fn interleave(lat: u32, lon: u32) -> u64 {
+    (0..32).fold(0, |bits, i| {
+        bits | ((lat as u64 >> i) & 1) << (2 * i) | ((lon as u64 >> i) & 1) << (2 * i + 1)
+    })
+}
+
+fn deinterleave(bits: u64) -> (u32, u32) {
+    (0..32).fold((0, 0), |(lat, lon), i| {
+        (
+            lat | (((bits >> (2 * i)) & 1) as u32) << i,
+            lon | (((bits >> (2 * i + 1)) & 1) as u32) << i,
+        )
+    })
+}
+
+pub(crate) fn is_valid(lon: f64, lat: f64) -> bool {
+    (LON_MIN..=LON_MAX).contains(&lon) && (LAT_MIN..=LAT_MAX).contains(&lat)
+}
+
+fn encode(lon: f64, lat: f64, step: u8) -> GeoHash {
+    let cells = (1u64 << step) as f64;
+    let lat_offset = (lat - LAT_MIN) / (LAT_MAX - LAT_MIN) * cells;
+    let lon_offset = (lon - LON_MIN) / (LON_MAX - LON_MIN) * cells;
+
+    GeoHash {
+        bits: interleave(lat_offset as u32, lon_offset as u32),
+        step,
+    }
+}
+
+fn area(hash: GeoHash) -> Area {
+    let (lat, lon) = deinterleave(hash.bits);
+    let cells = (1u64 << hash.step) as f64;
+
+    Area {
+        lon_min: LON_MIN + (lon as f64 / cells) * (LON_MAX - LON_MIN),
+        lon_max: LON_MIN + ((lon as f64 + 1.0) / cells) * (LON_MAX - LON_MIN),
+        lat_min: LAT_MIN + (lat as f64 / cells) * (LAT_MAX - LAT_MIN),
+        lat_max: LAT_MIN + ((lat as f64 + 1.0) / cells) * (LAT_MAX - LAT_MIN),
+    }
+}
+
+/// Score of the coordinates, which must be valid.
+pub(crate) fn score(lon: f64, lat: f64) -> u64 {
+    encode(lon, lat, STEP_MAX).bits
+}
+
+/// Center of the cell the score stands for, as `(longitude, latitude)`.
+pub(crate) fn coordinates(score: f64) -> (f64, f64) {
+    let area = area(GeoHash {
+        bits: score as u64,
+        step: STEP_MAX,
+    });
+
+    (
+        ((area.lon_min + area.lon_max) / 2.0).clamp(LON_MIN, LON_MAX),
+        ((area.lat_min + area.lat_max) / 2.0).clamp(LAT_MIN, LAT_MAX),
+    )
+}
+
+fn to_radians(degrees: f64) -> f64 {
+    degrees * (PI / 180.0)
+}
+
+fn lat_distance(lat1: f64, lat2: f64) -> f64 {
+    EARTH_RADIUS_IN_METERS * (to_radians(lat2) - to_radians(lat1)).abs()
+}
+
+/// Haversine distance in meters.
+pub(crate) fn distance(lon1: f64, lat1: f64, lon2: f64, lat2: f64) -> f64 {
+    let v = ((to_radians(lon2) - to_radians(lon1)) / 2.0).sin();
+
+    if v == 0.0 {
+        return lat_distance(lat1, lat2);
+    }
+
+    let (lat1, lat2) = (to_radians(lat1), to_radians(lat2));
+    let u = ((lat2 - lat1) / 2.0).sin();
+    let a = u * u + lat1.cos() * lat2.cos() * v * v;
+
+    2.0 * EARTH_RADIUS_IN_METERS * a.sqrt().asin()
+}
+
+/// Area searched around a point, sizes being in meters.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Shape {
+    Radius(f64),
+    Box { width: f64, height: f64 },
+}
+
+impl Shape {
+    /// Distance in meters from the center to the point if the point lies within the shape.
+    pub(crate) fn distance_within(&self, center: (f64, f64), point: (f64, f64)) -> Option<f64> {
+        let ((lon1, lat1), (lon2, lat2)) = (center, point);
+
+        match *self {
+            Shape::Radius(radius) => {
+                Some(distance(lon1, lat1, lon2, lat2)).filter(|distance| *distance <= radius)
+            }
+            Shape::Box { width, height } => {
+                if lat_distance(lat1, lat2) > height / 2.0
+                    || distance(lon1, lat2, lon2, lat2) > width / 2.0
+                {
+                    return None;
+                }
+
+                Some(distance(lon1, lat1, lon2, lat2))
+            }
+        }
+    }
+
+    fn half_sizes(&self) -> (f64, f64) {
+        match *self {
+            Shape::Radius(radius) => (radius, radius),
+            Shape::Box { width, height } => (width / 2.0, height / 2.0),
+        }
+    }
+
+    /// `(lon_min, lat_min, lon_max, lat_max)` of the smallest box holding the shape.
+    fn bounding_box(&self, lon: f64, lat: f64) -> (f64, f64, f64, f64) {
+        let (half_width, half_height) = self.half_sizes();
+        let lat_delta = (half_height / EARTH_RADIUS_IN_METERS).to_degrees();
+        let lon_delta_top =
+            (half_width / EARTH_RADIUS_IN_METERS / to_radians(lat + lat_delta).cos()).to_degrees();
+        let lon_delta_bottom =
+            (half_width / EARTH_RADIUS_IN_METERS / to_radians(lat - lat_delta).cos()).to_degrees();
+        // the widest side is the one closest to the equator
+        let lon_delta = if lat < 0.0 {
+            lon_delta_bottom
+        } else {
+            lon_delta_top
+        };
+
+        (
+            lon - lon_delta,
+            lat - lat_delta,
+            lon + lon_delta,
+            lat + lat_delta,
+        )
+    }
+}
+
+/// Largest step whose cells, along with their neighbors, can cover the radius.
+fn estimate_steps(mut radius: f64, lat: f64) -> u8 {
+    if radius == 0.0 {
+        return STEP_MAX;
+    }
+
+    let mut step: i32 = 1;
+
+    while radius < MERCATOR_MAX {
+        radius *= 2.0;
+        step += 1;
+    }
+
+    step -= 2;
+
+    // cells get narrower towards the poles
+    if !(-66.0..=66.0).contains(&lat) {
+        step -= 1;
+
+        if !(-80.0..=80.0).contains(&lat) {
+            step -= 1;
+        }
+    }
+
+    step.clamp(1, STEP_MAX as i32) as u8
+}
+
+fn move_x(hash: GeoHash, east: bool) -> GeoHash {
+    let shift = 64 - hash.step as u32 * 2;
+    let mut x = hash.bits & 0xaaaa_aaaa_aaaa_aaaa;
+    let y = hash.bits & 0x5555_5555_5555_5555;
+    let zz = 0x5555_5555_5555_5555u64 >> shift;
+
+    x = if east {
+        x.wrapping_add(zz + 1)
+    } else {
+        (x | zz).wrapping_sub(zz + 1)
+    };
+
+    GeoHash {
+        bits: (x & (0xaaaa_aaaa_aaaa_aaaa >> shift)) | y,
+        step: hash.step,
+    }
+}
+
+fn move_y(hash: GeoHash, north: bool) -> GeoHash {
+    let shift = 64 - hash.step as u32 * 2;
+    let x = hash.bits & 0xaaaa_aaaa_aaaa_aaaa;
+    let mut y = hash.bits & 0x5555_5555_5555_5555;
+    let zz = 0xaaaa_aaaa_aaaa_aaaau64 >> shift;
+
+    y = if north {
+        y.wrapping_add(zz + 1)
+    } else {
+        (y | zz).wrapping_sub(zz + 1)
+    };
+
+    GeoHash {
+        bits: x | (y & (0x5555_5555_5555_5555 >> shift)),
+        step: hash.step,
+    }
+}
+
+/// The cell of the point and its 8 neighbors, in redis order: center, north, south, east, west,
+/// north east, north west, south east, south west.
+fn cells(lon: f64, lat: f64, step: u8) -> [GeoHash; 9] {
+    let hash = encode(lon, lat, step);
+    let (north, south) = (move_y(hash, true), move_y(hash, false));
+
+    [
+        hash,
+        north,
+        south,
+        move_x(hash, true),
+        move_x(hash, false),
+        move_x(north, true),
+        move_x(north, false),
+        move_x(south, true),
+        move_x(south, false),
+    ]
+}
+
+/// Score ranges, each `[min, max)`, that hold every point within the shape around the center.
+/// Points found in them still need to be checked against the shape itself.
+pub(crate) fn search_ranges(lon: f64, lat: f64, shape: &Shape) -> Vec<(u64, u64)> {
+    const CENTER: usize = 0;
+    const NORTH: usize = 1;
+    const SOUTH: usize = 2;
+    const EAST: usize = 3;
+    const WEST: usize = 4;
+    const NORTH_EAST: usize = 5;
+    const NORTH_WEST: usize = 6;
+    const SOUTH_EAST: usize = 7;
+    const SOUTH_WEST: usize = 8;
+
+    let (lon_min, lat_min, lon_max, lat_max) = shape.bounding_box(lon, lat);
+    let (half_width, half_height) = shape.half_sizes();
+    let radius = match shape {
+        Shape::Radius(radius) => *radius,
+        Shape::Box { .. } => half_width.hypot(half_height),
+    };
+
+    let mut step = estimate_steps(radius, lat);
+    let mut neighbors = cells(lon, lat, step);
+
+    // near the edges of its cell the estimated step might not be enough to cover the shape
+    let too_small = area(neighbors[NORTH]).lat_max < lat_max
+        || area(neighbors[SOUTH]).lat_min > lat_min
+        || area(neighbors[EAST]).lon_max < lon_max
+        || area(neighbors[WEST]).lon_min > lon_min;
+
+    if step > 1 && too_small {
+        step -= 1;
+        neighbors = cells(lon, lat, step);
+    }
+
+    let mut useful = [true; 9];
+
+    if step >= 2 {
+        let center = area(neighbors[CENTER]);
+
+        let mut exclude = |cells: [usize; 3]| cells.into_iter().for_each(|i| useful[i] = false);
+
+        if center.lat_min < lat_min {
+            exclude([SOUTH, SOUTH_WEST, SOUTH_EAST]);
+        }
+
+        if center.lat_max > lat_max {
+            exclude([NORTH, NORTH_EAST, NORTH_WEST]);
+        }
+
+        if center.lon_min < lon_min {
+            exclude([WEST, SOUTH_WEST, NORTH_WEST]);
+        }
+
+        if center.lon_max > lon_max {
+            exclude([EAST, SOUTH_EAST, NORTH_EAST]);
+        }
+    }
+
+    let mut ranges = vec![];
+    let mut last: Option<GeoHash> = None;
+
+    for (hash, _) in neighbors
+        .into_iter()
+        .zip(useful)
+        .filter(|(_, useful)| *useful)
+    {
+        // with huge shapes adjacent neighbors can be the same cell
+        if last == Some(hash) {
+            continue;
+        }
+
+        let shift = (STEP_MAX - hash.step) as u32 * 2;
+
+        ranges.push((hash.bits << shift, (hash.bits + 1) << shift));
+        last = Some(hash);
+    }
+
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PALERMO: (f64, f64) = (13.361389, 38.115556);
+    const CATANIA: (f64, f64) = (15.087269, 37.502669);
+
+    #[test]
+    fn scores_and_coordinates_match_redis() {
+        let score = score(PALERMO.0, PALERMO.1);
+
+        assert_eq!(score, 3479099956230698);
+
+        let (lon, lat) = coordinates(score as f64);
+
+        assert_eq!(format!("{lon:.17}"), "13.36138933897018433");
+        assert_eq!(format!("{lat:.17}"), "38.11555639549629859");
+    }
+
+    #[test]
+    fn distances_match_redis() {
+        // members are measured from the center of their cell
+        let (lon1, lat1) = coordinates(score(PALERMO.0, PALERMO.1) as f64);
+        let (lon2, lat2) = coordinates(score(CATANIA.0, CATANIA.1) as f64);
+
+        assert_eq!(
+            format!("{:.4}", distance(lon1, lat1, lon2, lat2)),
+            "166274.1516"
+        );
+        assert_eq!(distance(1.0, 2.0, 1.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn search_ranges_cover_the_shape() {
+        let center = (15.0, 37.0);
+
+        for shape in [
+            Shape::Radius(200_000.0),
+            Shape::Radius(10.0),
+            Shape::Box {
+                width: 400_000.0,
+                height: 400_000.0,
+            },
+        ] {
+            let ranges = search_ranges(center.0, center.1, &shape);
+
+            for point in [PALERMO, CATANIA, (15.0001, 37.0), (14.0, 38.0)] {
+                if shape.distance_within(center, point).is_none() {
+                    continue;
+                }
+
+                let score = score(point.0, point.1);
+
+                assert!(
+                    ranges
+                        .iter()
+                        .any(|(min, max)| (*min..*max).contains(&score))
+                );
+            }
+        }
+    }
+}
@@ -1,6 +1,24 @@
+pub(crate) mod bitmaps;
 pub(crate) mod config;
+pub(crate) mod databases;
 pub(crate) mod echo;
+pub(crate) mod expire;
+pub(crate) mod geo;
 pub(crate) mod get;
+pub(crate) mod hashes;
+pub(crate) mod hello;
+pub(crate) mod hyperloglog;
+pub(crate) mod info;
+pub(crate) mod key_type;
 pub(crate) mod keys;
+pub(crate) mod lists;
+pub(crate) mod object;
+pub(crate) mod persistence;
 pub(crate) mod ping;
+pub(crate) mod scan;
 pub(crate) mod set;
+pub(crate) mod sets;
+pub(crate) mod sorted_sets;
+pub(crate) mod stream_groups;
+pub(crate) mod streams;
+pub(crate) mod strings;
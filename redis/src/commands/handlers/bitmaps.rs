@@ -0,0 +1,518 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        handlers::strings::MAX_STRING_LEN,
+        hash_map::{Db, HASH_MAP, Value, object::Object},
+        utils::parse_int,
+    },
+    resp::types::RespType,
+};
+
+/// Strings are capped to 512MB, so are the bits addressed within them.
+const MAX_BITS: u64 = MAX_STRING_LEN as u64 * 8;
+
+fn parse_bit_offset(arg: &Bytes) -> Result<u64, CommandExecutionError> {
+    str::from_utf8(arg)
+        .ok()
+        .and_then(|arg| arg.parse::<u64>().ok())
+        .filter(|offset| *offset < MAX_BITS)
+        .ok_or(CommandExecutionError::InvalidBitOffset)
+}
+
+fn get_bit(bytes: &[u8], offset: u64) -> u8 {
+    let byte = bytes
+        .get((offset / 8) as usize)
+        .copied()
+        .unwrap_or_default();
+
+    (byte >> (7 - offset % 8)) & 1
+}
+
+fn set_bit(bytes: &mut [u8], offset: u64, bit: u8) {
+    let (byte, mask) = ((offset / 8) as usize, 1 << (7 - offset % 8));
+
+    if bit == 1 {
+        bytes[byte] |= mask;
+    } else {
+        bytes[byte] &= !mask;
+    }
+}
+
+/// Bytes of the string stored at the key, empty if there is none.
+fn read_string(db: &Db, key: &Bytes) -> Result<Bytes, CommandExecutionError> {
+    match db.get(key) {
+        Some(record) => Ok(record.data().as_string()?.clone()),
+        None => Ok(Bytes::new()),
+    }
+}
+
+/// Rewrites the string stored at the key, zero padded to at least `len` bytes, with `f`.
+fn update_string<T>(
+    db: &mut Db,
+    key: &Bytes,
+    len: usize,
+    f: impl FnOnce(&mut Vec<u8>) -> T,
+) -> Result<T, CommandExecutionError> {
+    let mut bytes = read_string(db, key)?.to_vec();
+
+    if bytes.len() < len {
+        bytes.resize(len, 0);
+    }
+
+    let result = f(&mut bytes);
+
+    db.set_data(key, Object::String(Bytes::from(bytes)));
+
+    Ok(result)
+}
+
+pub(crate) async fn setbit(
+    key: &Bytes,
+    offset: &Bytes,
+    value: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let offset = parse_bit_offset(offset)?;
+    let bit = match value.as_ref() {
+        b"0" => 0,
+        b"1" => 1,
+        _ => return Err(CommandExecutionError::InvalidBit),
+    };
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let previous = update_string(&mut map_write, key, (offset / 8) as usize + 1, |bytes| {
+        let previous = get_bit(bytes, offset);
+
+        set_bit(bytes, offset, bit);
+
+        previous
+    })?;
+
+    Ok(RespType::Integer(Some(previous as i64)))
+}
+
+pub(crate) async fn getbit(key: &Bytes, offset: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let offset = parse_bit_offset(offset)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let bytes = read_string(&map_read, key)?;
+
+    Ok(RespType::Integer(Some(get_bit(&bytes, offset) as i64)))
+}
+
+/// Parses the optional `start end [BYTE | BIT]` of `BITCOUNT` and `BITPOS` into an inclusive
+/// range of bits, `None` when it is empty.
+fn bit_range(
+    start: Option<&Bytes>,
+    end: Option<&Bytes>,
+    unit: Option<&Bytes>,
+    len: usize,
+) -> Result<Option<(u64, u64)>, CommandExecutionError> {
+    let is_bit = match unit {
+        None => false,
+        Some(unit) if unit.eq_ignore_ascii_case(b"byte") => false,
+        Some(unit) if unit.eq_ignore_ascii_case(b"bit") => true,
+        Some(_) => return Err(CommandExecutionError::SyntaxError),
+    };
+
+    let total = if is_bit { len as i64 * 8 } else { len as i64 };
+    let start = start.map(parse_int).transpose()?.unwrap_or(0);
+    let end = end.map(parse_int).transpose()?.unwrap_or(-1);
+
+    let normalize = |index: i64| {
+        if index < 0 {
+            (total + index).max(0)
+        } else {
+            index
+        }
+    };
+    let (start, end) = (normalize(start), normalize(end).min(total - 1));
+
+    if start > end {
+        return Ok(None);
+    }
+
+    Ok(Some(if is_bit {
+        (start as u64, end as u64)
+    } else {
+        (start as u64 * 8, end as u64 * 8 + 7)
+    }))
+}
+
+/// `BITCOUNT key [start end [BYTE | BIT]]`.
+pub(crate) async fn bitcount(
+    key: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let (start, end, unit) = match args {
+        [] => (None, None, None),
+        [start, end] => (Some(start), Some(end), None),
+        [start, end, unit] => (Some(start), Some(end), Some(unit)),
+        _ => return Err(CommandExecutionError::SyntaxError),
+    };
+
+    let map_read = HASH_MAP.read().await;
+
+    let bytes = read_string(&map_read, key)?;
+
+    let Some((first, last)) = bit_range(start, end, unit, bytes.len())? else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let (first_byte, last_byte) = ((first / 8) as usize, (last / 8) as usize);
+    let mut count: u64 = bytes[first_byte..=last_byte]
+        .iter()
+        .map(|byte| byte.count_ones() as u64)
+        .sum();
+
+    // bits of the first and last bytes outside of the range
+    let (head, tail) = (first % 8, 7 - last % 8);
+
+    count -= (bytes[first_byte] as u16 >> (8 - head)).count_ones() as u64;
+    count -= (bytes[last_byte] as u16 & ((1 << tail) - 1)).count_ones() as u64;
+
+    Ok(RespType::Integer(Some(count as i64)))
+}
+
+/// `BITPOS key bit [start [end [BYTE | BIT]]]`.
+pub(crate) async fn bitpos(
+    key: &Bytes,
+    bit: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let bit = match bit.as_ref() {
+        b"0" => 0,
+        b"1" => 1,
+        _ => return Err(CommandExecutionError::InvalidBitPosBit),
+    };
+
+    let (start, end, unit) = match args {
+        [] => (None, None, None),
+        [start] => (Some(start), None, None),
+        [start, end] => (Some(start), Some(end), None),
+        [start, end, unit] => (Some(start), Some(end), Some(unit)),
+        _ => return Err(CommandExecutionError::SyntaxError),
+    };
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        // a missing key is an empty string, padded with zeros on the right
+        return Ok(RespType::Integer(Some(if bit == 1 { -1 } else { 0 })));
+    };
+
+    let bytes = record.data().as_string()?;
+
+    let Some((first, last)) = bit_range(start, end, unit, bytes.len())? else {
+        return Ok(RespType::Integer(Some(-1)));
+    };
+
+    let mut offset = first;
+
+    while offset <= last {
+        let byte = bytes[(offset / 8) as usize];
+
+        // whole bytes without the bit are skipped at once
+        if offset % 8 == 0 && offset + 7 <= last && byte == if bit == 1 { 0x00 } else { 0xff } {
+            offset += 8;
+            continue;
+        }
+
+        if (byte >> (7 - offset % 8)) & 1 == bit {
+            return Ok(RespType::Integer(Some(offset as i64)));
+        }
+
+        offset += 1;
+    }
+
+    // without an explicit end the string is considered padded with zeros on the right
+    let position = if bit == 0 && end.is_none() {
+        last as i64 + 1
+    } else {
+        -1
+    };
+
+    Ok(RespType::Integer(Some(position)))
+}
+
+/// `BITOP AND | OR | XOR | NOT destkey key [key ...]`, missing keys being zero filled strings.
+pub(crate) async fn bitop(
+    operation: &Bytes,
+    destination: &Bytes,
+    keys: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let operation = operation.to_ascii_lowercase();
+
+    if !matches!(operation.as_slice(), b"and" | b"or" | b"xor" | b"not") {
+        return Err(CommandExecutionError::SyntaxError);
+    }
+
+    if operation == b"not" && keys.len() != 1 {
+        return Err(CommandExecutionError::BitOpNotSingleSource);
+    }
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let sources = keys
+        .iter()
+        .map(|key| read_string(&map_write, key))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let len = sources.iter().map(Bytes::len).max().unwrap_or_default();
+    let byte_at = |source: &Bytes, i: usize| source.get(i).copied().unwrap_or_default();
+
+    let result: Vec<u8> = (0..len)
+        .map(|i| {
+            let bytes = sources.iter().map(|source| byte_at(source, i));
+
+            match operation.as_slice() {
+                b"and" => bytes.fold(0xff, |acc, byte| acc & byte),
+                b"or" => bytes.fold(0, |acc, byte| acc | byte),
+                b"xor" => bytes.fold(0, |acc, byte| acc ^ byte),
+                _ => !byte_at(&sources[0], i),
+            }
+        })
+        .collect();
+
+    if result.is_empty() {
+        map_write.remove(destination);
+    } else {
+        map_write.insert(
+            destination.clone(),
+            Value::new(Object::String(Bytes::from(result)), None),
+        );
+    }
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Overflow {
+    Wrap,
+    Sat,
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FieldType {
+    is_signed: bool,
+    bits: u32,
+}
+
+impl FieldType {
+    /// `i1` to `i64` or `u1` to `u63`.
+    fn parse(arg: &Bytes) -> Result<Self, CommandExecutionError> {
+        let (is_signed, bits) = match arg.split_first() {
+            Some((b'i' | b'I', bits)) => (true, bits),
+            Some((b'u' | b'U', bits)) => (false, bits),
+            _ => return Err(CommandExecutionError::InvalidBitfieldType),
+        };
+
+        let bits = str::from_utf8(bits)
+            .ok()
+            .and_then(|bits| bits.parse::<u32>().ok())
+            .filter(|bits| (1..=if is_signed { 64 } else { 63 }).contains(bits))
+            .ok_or(CommandExecutionError::InvalidBitfieldType)?;
+
+        Ok(Self { is_signed, bits })
+    }
+
+    /// Offsets prefixed by `#` are multiplied by the width of the type.
+    fn parse_offset(&self, arg: &Bytes) -> Result<u64, CommandExecutionError> {
+        let offset = match arg.strip_prefix(b"#") {
+            Some(index) => parse_bit_offset(&Bytes::copy_from_slice(index))?
+                .checked_mul(self.bits as u64)
+                .ok_or(CommandExecutionError::InvalidBitOffset)?,
+            None => parse_bit_offset(arg)?,
+        };
+
+        if offset + self.bits as u64 > MAX_BITS {
+            return Err(CommandExecutionError::InvalidBitOffset);
+        }
+
+        Ok(offset)
+    }
+
+    fn get(&self, bytes: &[u8], offset: u64) -> i64 {
+        let value = (0..self.bits as u64).fold(0u64, |value, i| {
+            (value << 1) | get_bit(bytes, offset + i) as u64
+        });
+
+        // the sign bit is propagated to the higher bits
+        if self.is_signed && self.bits < 64 && value & (1 << (self.bits - 1)) != 0 {
+            return (value | (u64::MAX << self.bits)) as i64;
+        }
+
+        value as i64
+    }
+
+    fn set(&self, bytes: &mut [u8], offset: u64, value: i64) {
+        for i in 0..self.bits as u64 {
+            let bit = ((value as u64) >> (self.bits as u64 - 1 - i)) & 1;
+
+            set_bit(bytes, offset + i, bit as u8);
+        }
+    }
+
+    /// Result of adding `increment` to `value` unless it does not fit in the type, in which
+    /// case the overflow policy decides, `None` meaning the operation fails.
+    fn add(&self, value: i64, increment: i64, overflow: Overflow) -> Option<i64> {
+        let wrap = || {
+            let sum = (value as u64).wrapping_add(increment as u64);
+
+            if self.bits == 64 {
+                return sum as i64;
+            }
+
+            let mask = u64::MAX << self.bits;
+
+            if self.is_signed && sum & (1 << (self.bits - 1)) != 0 {
+                (sum | mask) as i64
+            } else {
+                (sum & !mask) as i64
+            }
+        };
+
+        let (min, max) = if self.is_signed {
+            let max = if self.bits == 64 {
+                i64::MAX
+            } else {
+                (1 << (self.bits - 1)) - 1
+            };
+
+            (-max - 1, max)
+        } else {
+            (0, (1 << self.bits) - 1)
+        };
+
+        let sum = value as i128 + increment as i128;
+
+        let saturated = if sum > max as i128 {
+            max
+        } else if sum < min as i128 {
+            min
+        } else {
+            return Some(sum as i64);
+        };
+
+        match overflow {
+            Overflow::Wrap => Some(wrap()),
+            Overflow::Sat => Some(saturated),
+            Overflow::Fail => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum FieldOp {
+    Get,
+    Set(i64),
+    IncrBy(i64),
+}
+
+/// `BITFIELD key [GET type offset] [SET type offset value] [INCRBY type offset increment]
+/// [OVERFLOW WRAP | SAT | FAIL] ...`, `BITFIELD_RO` when `read_only` is set.
+pub(crate) async fn bitfield(
+    key: &Bytes,
+    args: &[Bytes],
+    read_only: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let mut ops = vec![];
+    let mut overflow = Overflow::Wrap;
+    let mut args = args.iter();
+
+    // every operation is validated before running any
+    while let Some(arg) = args.next() {
+        let mut next = || args.next().ok_or(CommandExecutionError::SyntaxError);
+        let subcommand = arg.to_ascii_lowercase();
+
+        if read_only && subcommand != b"get" {
+            return Err(CommandExecutionError::BitfieldReadOnly);
+        }
+
+        if subcommand == b"overflow" {
+            overflow = match next()?.to_ascii_lowercase().as_slice() {
+                b"wrap" => Overflow::Wrap,
+                b"sat" => Overflow::Sat,
+                b"fail" => Overflow::Fail,
+                _ => return Err(CommandExecutionError::InvalidOverflowType),
+            };
+
+            continue;
+        }
+
+        if !matches!(subcommand.as_slice(), b"get" | b"set" | b"incrby") {
+            return Err(CommandExecutionError::SyntaxError);
+        }
+
+        let field_type = FieldType::parse(next()?)?;
+        let offset = field_type.parse_offset(next()?)?;
+
+        let op = match subcommand.as_slice() {
+            b"get" => FieldOp::Get,
+            b"set" => FieldOp::Set(parse_int(next()?)?),
+            _ => FieldOp::IncrBy(parse_int(next()?)?),
+        };
+
+        ops.push((op, field_type, offset, overflow));
+    }
+
+    let writes_up_to = ops
+        .iter()
+        .filter(|(op, ..)| !matches!(op, FieldOp::Get))
+        .map(|(_, field_type, offset, _)| (offset + field_type.bits as u64).div_ceil(8) as usize)
+        .max();
+
+    let run = |bytes: &mut [u8]| {
+        let replies = ops
+            .iter()
+            .map(|(op, field_type, offset, overflow)| {
+                let current = field_type.get(bytes, *offset);
+
+                let (value, reply) = match op {
+                    FieldOp::Get => return RespType::Integer(Some(current)),
+                    FieldOp::Set(value) => {
+                        // the value to set is checked as if added to zero
+                        let value = if field_type.is_signed {
+                            field_type.add(*value, 0, *overflow)
+                        } else {
+                            field_type.add(0, *value, *overflow)
+                        };
+
+                        (value, Some(current))
+                    }
+                    FieldOp::IncrBy(increment) => {
+                        (field_type.add(current, *increment, *overflow), None)
+                    }
+                };
+
+                let Some(value) = value else {
+                    return RespType::BulkString(None);
+                };
+
+                field_type.set(bytes, *offset, value);
+
+                RespType::Integer(Some(reply.unwrap_or(value)))
+            })
+            .collect();
+
+        RespType::Array(Some(replies))
+    };
+
+    match writes_up_to {
+        // the string is grown up front even if the writes end up failing, like redis does
+        Some(len) => {
+            let mut map_write = HASH_MAP.write().await;
+
+            update_string(&mut map_write, key, len, |bytes| run(bytes))
+        }
+        None => {
+            let map_read = HASH_MAP.read().await;
+
+            Ok(run(&mut read_string(&map_read, key)?.to_vec()))
+        }
+    }
+}
@@ -1,31 +1,70 @@
-use bytes::BytesMut;
+use bytes::Bytes;
 
-use crate::{commands::errors::CommandExecutionError, config::CONFIG, resp::types::RespType};
+use crate::{
+    commands::errors::CommandExecutionError,
+    config::{CONFIG, format_save_rules, parse_save_rules},
+    resp::types::RespType,
+};
 
-pub(crate) async fn config(
-    action: &BytesMut,
-    key: &BytesMut,
-) -> Result<RespType, CommandExecutionError> {
+async fn get(key: &Bytes) -> Result<RespType, CommandExecutionError> {
     let config = CONFIG.read().await;
 
-    match action.to_ascii_lowercase().as_slice() {
-        b"get" => match key.to_ascii_lowercase().as_slice() {
-            b"dir" if config.dir.is_some() => Ok(RespType::Array(Some(vec![
-                RespType::BulkString(Some(action.clone())),
-                RespType::BulkString(Some(BytesMut::from_iter::<Vec<u8>>(
-                    config.dir.as_ref().unwrap().clone().into(),
-                ))),
-            ]))),
-            b"dbfilename" => Ok(RespType::Array(Some(vec![
-                RespType::BulkString(Some(action.clone())),
-                RespType::BulkString(Some(BytesMut::from_iter::<Vec<u8>>(
-                    config.dbfilename.as_ref().unwrap().clone().into(),
-                ))),
-            ]))),
-            _ => Err(CommandExecutionError::IncorrectOptionsError(
+    let value = match key.to_ascii_lowercase().as_slice() {
+        b"dir" => config.dir.clone(),
+        b"dbfilename" => config.dbfilename.clone(),
+        b"databases" => Some(config.databases.to_string()),
+        b"save" => Some(format_save_rules(&config.save)),
+        _ => {
+            return Err(CommandExecutionError::IncorrectOptionsError(
+                "unknown config key".to_string(),
+            ));
+        }
+    };
+
+    Ok(RespType::Map(Some(vec![(
+        RespType::bulk_string(key.to_ascii_lowercase()),
+        RespType::bulk_string(value.unwrap_or_default()),
+    )])))
+}
+
+/// Only `save` can be changed at runtime, as the others are used once at startup.
+async fn set(key: &Bytes, value: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let mut config = CONFIG.write().await;
+
+    match key.to_ascii_lowercase().as_slice() {
+        b"save" => {
+            config.save = str::from_utf8(value)
+                .ok()
+                .and_then(parse_save_rules)
+                .ok_or_else(|| {
+                    CommandExecutionError::ConfigSetFailed(
+                        "save".to_string(),
+                        "Invalid save parameters".to_string(),
+                    )
+                })?;
+        }
+        _ => {
+            return Err(CommandExecutionError::IncorrectOptionsError(
                 "unknown config key".to_string(),
-            )),
-        },
+            ));
+        }
+    }
+
+    Ok(RespType::simple_string("OK"))
+}
+
+/// `CONFIG GET parameter` and `CONFIG SET parameter value`.
+pub(crate) async fn config(
+    action: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    match (action.to_ascii_lowercase().as_slice(), args) {
+        (b"get", [key]) => get(key).await,
+        (b"set", [key, value]) => set(key, value).await,
+        (b"get" | b"set", _) => Err(CommandExecutionError::WrongNumberOfArguments(format!(
+            "config|{}",
+            String::from_utf8_lossy(action).to_ascii_lowercase()
+        ))),
         _ => Err(CommandExecutionError::IncorrectOptionsError(
             "unknown config action".to_string(),
         )),
@@ -0,0 +1,150 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        blocking::{BLOCKED_CLIENTS, serve_blocked_clients},
+        errors::CommandExecutionError,
+        hash_map::{Databases, Db, HASH_MAP},
+        session::Session,
+        utils::parse_int,
+    },
+    resp::types::RespType,
+};
+
+/// Parses the index of an existing database, `invalid` being the error if it is not a number.
+pub(crate) fn parse_db_index(
+    arg: &Bytes,
+    len: usize,
+    invalid: CommandExecutionError,
+) -> Result<usize, CommandExecutionError> {
+    match parse_int(arg).map_err(|_| invalid)? {
+        index if index < 0 || index as usize >= len => {
+            Err(CommandExecutionError::DbIndexOutOfRange)
+        }
+        index => Ok(index as usize),
+    }
+}
+
+/// Hands what the database now holds to the clients blocked on its keys, e.g. once `SWAPDB`
+/// brought in a list they wait on.
+fn serve_all_blocked_clients(db: &mut Db) {
+    let keys = BLOCKED_CLIENTS
+        .lock()
+        .expect("blocked clients lock is poisoned")
+        .keys_of(db.index());
+
+    for key in keys {
+        serve_blocked_clients(db, &key);
+    }
+}
+
+/// Drops the databases' content, in the background for `ASYNC` as freeing a large keyspace
+/// takes a while.
+fn free(dbs: Vec<Db>, is_async: bool) {
+    if is_async {
+        tokio::task::spawn_blocking(move || drop(dbs));
+    }
+}
+
+/// Parses the optional `ASYNC | SYNC` of `FLUSHDB` and `FLUSHALL`.
+fn parse_flush_mode(args: &[Bytes]) -> Result<bool, CommandExecutionError> {
+    match args {
+        [] => Ok(false),
+        [mode] if mode.eq_ignore_ascii_case(b"async") => Ok(true),
+        [mode] if mode.eq_ignore_ascii_case(b"sync") => Ok(false),
+        _ => Err(CommandExecutionError::SyntaxError),
+    }
+}
+
+pub(crate) async fn select(
+    session: &mut Session,
+    index: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let len = HASH_MAP.read_all().await.len();
+
+    session.db = parse_db_index(index, len, CommandExecutionError::NotAnInteger)?;
+
+    Ok(RespType::simple_string("OK"))
+}
+
+/// Swaps the content of two databases, which every connection sees at once.
+pub(crate) async fn swapdb(
+    first: &Bytes,
+    second: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let mut dbs = HASH_MAP.write_all().await;
+
+    let first = parse_db_index(first, dbs.len(), CommandExecutionError::InvalidFirstDbIndex)?;
+    let second = parse_db_index(
+        second,
+        dbs.len(),
+        CommandExecutionError::InvalidSecondDbIndex,
+    )?;
+
+    if first != second {
+        let (low, high) = dbs.split_at_mut(first.max(second));
+
+        low[first.min(second)].swap(&mut high[0]);
+
+        for index in [first, second] {
+            serve_all_blocked_clients(&mut dbs[index]);
+        }
+    }
+
+    Ok(RespType::simple_string("OK"))
+}
+
+/// Moves the key, along with its deadline, to another database unless it already holds it.
+pub(crate) async fn move_key(key: &Bytes, db: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let mut dbs = HASH_MAP.write_all().await;
+
+    let destination = parse_db_index(db, dbs.len(), CommandExecutionError::NotAnInteger)?;
+    let source = Databases::selected();
+
+    if source == destination {
+        return Err(CommandExecutionError::SameObject);
+    }
+
+    if dbs[source].get(key).is_none() || dbs[destination].get(key).is_some() {
+        return Ok(RespType::Integer(Some(0)));
+    }
+
+    if let Some(value) = dbs[source].remove(key) {
+        dbs[destination].insert(key.clone(), value);
+
+        serve_blocked_clients(&mut dbs[destination], key);
+    }
+
+    Ok(RespType::Integer(Some(1)))
+}
+
+pub(crate) async fn dbsize() -> RespType {
+    RespType::Integer(Some(HASH_MAP.read().await.len() as i64))
+}
+
+/// `FLUSHDB [ASYNC | SYNC]`.
+pub(crate) async fn flushdb(args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let is_async = parse_flush_mode(args)?;
+
+    let flushed = HASH_MAP.write().await.take();
+
+    free(vec![flushed], is_async);
+
+    Ok(RespType::simple_string("OK"))
+}
+
+/// `FLUSHALL [ASYNC | SYNC]`.
+pub(crate) async fn flushall(args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let is_async = parse_flush_mode(args)?;
+
+    let flushed = HASH_MAP
+        .write_all()
+        .await
+        .iter_mut()
+        .map(Db::take)
+        .collect();
+
+    free(flushed, is_async);
+
+    Ok(RespType::simple_string("OK"))
+}
@@ -1,7 +1,7 @@
-use bytes::BytesMut;
+use bytes::Bytes;
 
 use crate::resp::types::RespType;
 
-pub(crate) async fn echo(arg: &BytesMut) -> RespType {
-    RespType::BulkString(Some(arg.clone()))
+pub(crate) async fn echo(arg: &Bytes) -> RespType {
+    RespType::bulk_string(arg)
 }
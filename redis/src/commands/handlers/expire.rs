@@ -0,0 +1,134 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        hash_map::HASH_MAP,
+        utils::{ExpireCondition, parse_int, unix_time_ms},
+    },
+    resp::types::RespType,
+};
+
+/// Parses the `NX`, `XX`, `GT` and `LT` options of `EXPIRE`, all of which must hold for the
+/// deadline to be set.
+fn parse_conditions(args: &[Bytes]) -> Result<Vec<ExpireCondition>, CommandExecutionError> {
+    let conditions = args
+        .iter()
+        .map(|arg| {
+            ExpireCondition::parse(arg).ok_or_else(|| {
+                CommandExecutionError::UnsupportedOption(String::from_utf8_lossy(arg).to_string())
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let has = |condition| conditions.contains(&condition);
+
+    if has(ExpireCondition::Nx)
+        && (has(ExpireCondition::Xx) || has(ExpireCondition::Gt) || has(ExpireCondition::Lt))
+    {
+        return Err(CommandExecutionError::NxAndXxGtLt);
+    }
+
+    if has(ExpireCondition::Gt) && has(ExpireCondition::Lt) {
+        return Err(CommandExecutionError::GtAndLt);
+    }
+
+    Ok(conditions)
+}
+
+/// `EXPIRE` family: `in_millis` for the `P*` variants, `absolute` for the `*AT` ones.
+/// A deadline that already passed deletes the key.
+pub(crate) async fn expire(
+    command: &str,
+    key: &Bytes,
+    time: &Bytes,
+    args: &[Bytes],
+    in_millis: bool,
+    absolute: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let invalid_time = || CommandExecutionError::InvalidExpireTime(command.to_string());
+
+    let conditions = parse_conditions(args)?;
+    let time = parse_int(time)?;
+
+    let now = unix_time_ms();
+    let time = if in_millis {
+        Some(time)
+    } else {
+        time.checked_mul(1000)
+    };
+    let at = if absolute {
+        time
+    } else {
+        time.and_then(|time| time.checked_add(now as i64))
+    };
+    let at = at.ok_or_else(invalid_time)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get(key) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    // negative deadlines are in the past as well
+    let at = at.max(0) as u64;
+
+    if !conditions
+        .iter()
+        .all(|condition| condition.allows(record.expires_at(), at))
+    {
+        return Ok(RespType::Integer(Some(0)));
+    }
+
+    if at <= now {
+        map_write.remove(key);
+    } else {
+        map_write.set_expires_at(key, Some(at));
+    }
+
+    Ok(RespType::Integer(Some(1)))
+}
+
+/// `TTL` family: `in_millis` for the `P*` variants, `absolute` for `EXPIRETIME` and
+/// `PEXPIRETIME`. Replies -2 if the key does not exist and -1 if it has no deadline.
+pub(crate) async fn ttl(
+    key: &Bytes,
+    in_millis: bool,
+    absolute: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::Integer(Some(-2)));
+    };
+
+    let Some(at) = record.expires_at() else {
+        return Ok(RespType::Integer(Some(-1)));
+    };
+
+    let time = match (absolute, in_millis) {
+        (true, true) => at,
+        (true, false) => at / 1000,
+        (false, true) => at.saturating_sub(unix_time_ms()),
+        // rounded to the closest second
+        (false, false) => (at.saturating_sub(unix_time_ms()) + 500) / 1000,
+    };
+
+    Ok(RespType::Integer(Some(time as i64)))
+}
+
+/// Removes the deadline of the key, replying whether it had one.
+pub(crate) async fn persist(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    if map_write
+        .get(key)
+        .is_none_or(|record| record.expires_at().is_none())
+    {
+        return Ok(RespType::Integer(Some(0)));
+    }
+
+    map_write.set_expires_at(key, None);
+
+    Ok(RespType::Integer(Some(1)))
+}
@@ -0,0 +1,437 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        geohash::{self, Shape},
+        handlers::sorted_sets::zadd,
+        hash_map::{
+            Db, HASH_MAP, Value,
+            object::Object,
+            sorted_set::{ScoreRange, SortedSet},
+        },
+        utils::{parse_float, parse_int},
+    },
+    resp::types::{ProtocolVersion, RespDouble, RespType},
+};
+
+/// Meters per unit.
+fn parse_unit(unit: &Bytes) -> Result<f64, CommandExecutionError> {
+    match unit.to_ascii_lowercase().as_slice() {
+        b"m" => Ok(1.0),
+        b"km" => Ok(1000.0),
+        b"ft" => Ok(0.3048),
+        b"mi" => Ok(1609.34),
+        _ => Err(CommandExecutionError::UnsupportedUnit),
+    }
+}
+
+fn parse_lon_lat(lon: &Bytes, lat: &Bytes) -> Result<(f64, f64), CommandExecutionError> {
+    let (lon, lat) = (parse_float(lon)?, parse_float(lat)?);
+
+    if !geohash::is_valid(lon, lat) {
+        return Err(CommandExecutionError::InvalidLonLat(lon, lat));
+    }
+
+    Ok((lon, lat))
+}
+
+/// Coordinates are printed with 17 decimals on RESP2, like redis does.
+fn coordinate_reply(value: f64, protocol: ProtocolVersion) -> RespType {
+    match protocol {
+        ProtocolVersion::Resp2 => {
+            let value = format!("{value:.17}");
+
+            RespType::bulk_string(value.trim_end_matches('0').trim_end_matches('.'))
+        }
+        ProtocolVersion::Resp3 => RespType::Double(Some(RespDouble(value))),
+    }
+}
+
+fn coordinates_reply((lon, lat): (f64, f64), protocol: ProtocolVersion) -> RespType {
+    RespType::Array(Some(vec![
+        coordinate_reply(lon, protocol),
+        coordinate_reply(lat, protocol),
+    ]))
+}
+
+fn distance_reply(meters: f64, conversion: f64) -> RespType {
+    RespType::bulk_string(format!("{:.4}", meters / conversion))
+}
+
+/// `GEOADD key [NX | XX] [CH] longitude latitude member [...]`, stored through `ZADD`.
+pub(crate) async fn geoadd(key: &Bytes, args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let flags = args
+        .iter()
+        .take_while(|arg| {
+            [&b"nx"[..], b"xx", b"ch"]
+                .iter()
+                .any(|flag| arg.eq_ignore_ascii_case(flag))
+        })
+        .count();
+    let (flags, triples) = args.split_at(flags);
+
+    let has_flag = |flag: &[u8]| flags.iter().any(|arg| arg.eq_ignore_ascii_case(flag));
+
+    if triples.is_empty()
+        || !triples.len().is_multiple_of(3)
+        || (has_flag(b"nx") && has_flag(b"xx"))
+    {
+        return Err(CommandExecutionError::SyntaxError);
+    }
+
+    let mut zadd_args = flags.to_vec();
+
+    for triple in triples.chunks_exact(3) {
+        let (lon, lat) = parse_lon_lat(&triple[0], &triple[1])?;
+
+        zadd_args.push(Bytes::from(geohash::score(lon, lat).to_string()));
+        zadd_args.push(triple[2].clone());
+    }
+
+    zadd(key, &zadd_args).await
+}
+
+pub(crate) async fn geopos(
+    key: &Bytes,
+    members: &[Bytes],
+    protocol: ProtocolVersion,
+) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let zset = match map_read.get(key) {
+        Some(record) => Some(record.data().as_zset()?),
+        None => None,
+    };
+
+    Ok(RespType::Array(Some(
+        members
+            .iter()
+            .map(|member| match zset.and_then(|zset| zset.score(member)) {
+                Some(score) => coordinates_reply(geohash::coordinates(score), protocol),
+                None => RespType::Array(None),
+            })
+            .collect(),
+    )))
+}
+
+pub(crate) async fn geodist(
+    key: &Bytes,
+    member1: &Bytes,
+    member2: &Bytes,
+    unit: Option<&Bytes>,
+) -> Result<RespType, CommandExecutionError> {
+    let conversion = unit.map(parse_unit).transpose()?.unwrap_or(1.0);
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::BulkString(None));
+    };
+
+    let zset = record.data().as_zset()?;
+
+    let (Some(score1), Some(score2)) = (zset.score(member1), zset.score(member2)) else {
+        return Ok(RespType::BulkString(None));
+    };
+
+    let (lon1, lat1) = geohash::coordinates(score1);
+    let (lon2, lat2) = geohash::coordinates(score2);
+
+    Ok(distance_reply(
+        geohash::distance(lon1, lat1, lon2, lat2),
+        conversion,
+    ))
+}
+
+#[derive(Debug)]
+enum Center {
+    Member(Bytes),
+    LonLat(f64, f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Sort {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug)]
+struct GeoSearch {
+    center: Center,
+    shape: Shape,
+    /// Meters per unit of the shape, distances are replied in the same unit.
+    conversion: f64,
+    sort: Option<Sort>,
+    count: Option<usize>,
+    any: bool,
+    with_coord: bool,
+    with_dist: bool,
+    with_hash: bool,
+    store_dist: bool,
+}
+
+impl GeoSearch {
+    fn parse(command: &str, args: &[Bytes], is_store: bool) -> Result<Self, CommandExecutionError> {
+        let mut center = None;
+        let mut center_count = 0;
+        let mut shape = None;
+        let mut shape_count = 0;
+        let mut search = GeoSearch {
+            center: Center::LonLat(0.0, 0.0),
+            shape: Shape::Radius(0.0),
+            conversion: 1.0,
+            sort: None,
+            count: None,
+            any: false,
+            with_coord: false,
+            with_dist: false,
+            with_hash: false,
+            store_dist: false,
+        };
+
+        let mut args = args.iter().peekable();
+
+        while let Some(arg) = args.next() {
+            let mut next = || args.next().ok_or(CommandExecutionError::SyntaxError);
+
+            match arg.to_ascii_lowercase().as_slice() {
+                b"frommember" => {
+                    center = Some(Center::Member(next()?.clone()));
+                    center_count += 1;
+                }
+                b"fromlonlat" => {
+                    let (lon, lat) = (next()?, next()?);
+                    let (lon, lat) = parse_lon_lat(lon, lat)?;
+
+                    center = Some(Center::LonLat(lon, lat));
+                    center_count += 1;
+                }
+                b"byradius" => {
+                    let radius = parse_float(next()?)?;
+
+                    if radius < 0.0 {
+                        return Err(CommandExecutionError::NegativeRadius);
+                    }
+
+                    search.conversion = parse_unit(next()?)?;
+                    shape = Some(Shape::Radius(radius * search.conversion));
+                    shape_count += 1;
+                }
+                b"bybox" => {
+                    let (width, height) = (parse_float(next()?)?, parse_float(next()?)?);
+
+                    if width < 0.0 || height < 0.0 {
+                        return Err(CommandExecutionError::NegativeBox);
+                    }
+
+                    search.conversion = parse_unit(next()?)?;
+                    shape = Some(Shape::Box {
+                        width: width * search.conversion,
+                        height: height * search.conversion,
+                    });
+                    shape_count += 1;
+                }
+                b"asc" => search.sort = Some(Sort::Asc),
+                b"desc" => search.sort = Some(Sort::Desc),
+                b"count" => {
+                    let count = parse_int(next()?)?;
+
+                    if count <= 0 {
+                        return Err(CommandExecutionError::CountNotPositive);
+                    }
+
+                    search.count = Some(count as usize);
+
+                    if args
+                        .next_if(|arg| arg.eq_ignore_ascii_case(b"any"))
+                        .is_some()
+                    {
+                        search.any = true;
+                    }
+                }
+                b"withcoord" => search.with_coord = true,
+                b"withdist" => search.with_dist = true,
+                b"withhash" => search.with_hash = true,
+                b"storedist" if is_store => search.store_dist = true,
+                _ => return Err(CommandExecutionError::SyntaxError),
+            }
+        }
+
+        let (Some(center), 1) = (center, center_count) else {
+            return Err(CommandExecutionError::GeoCenterMissing(command.to_string()));
+        };
+
+        let (Some(shape), 1) = (shape, shape_count) else {
+            return Err(CommandExecutionError::GeoShapeMissing(command.to_string()));
+        };
+
+        if is_store && (search.with_coord || search.with_dist || search.with_hash) {
+            return Err(CommandExecutionError::GeoStoreWithReplyOptions(
+                command.to_string(),
+            ));
+        }
+
+        // the closest members are the ones picked by a count unless any of them will do
+        if search.count.is_some() && !search.any && search.sort.is_none() {
+            search.sort = Some(Sort::Asc);
+        }
+
+        Ok(GeoSearch {
+            center,
+            shape,
+            ..search
+        })
+    }
+}
+
+#[derive(Debug)]
+struct GeoMatch {
+    member: Bytes,
+    score: f64,
+    /// In meters.
+    distance: f64,
+    coordinates: (f64, f64),
+}
+
+fn search(
+    db: &Db,
+    key: &Bytes,
+    search: &GeoSearch,
+) -> Result<Vec<GeoMatch>, CommandExecutionError> {
+    let Some(record) = db.get(key) else {
+        return Ok(vec![]);
+    };
+
+    let zset = record.data().as_zset()?;
+
+    let center = match &search.center {
+        Center::Member(member) => geohash::coordinates(
+            zset.score(member)
+                .ok_or(CommandExecutionError::UndecodableMember)?,
+        ),
+        Center::LonLat(lon, lat) => (*lon, *lat),
+    };
+
+    let limit = search.count.filter(|_| search.any).unwrap_or(usize::MAX);
+    let mut matches = vec![];
+
+    'ranges: for (min, max) in geohash::search_ranges(center.0, center.1, &search.shape) {
+        let range = ScoreRange {
+            min: min as f64,
+            max: max as f64,
+            min_exclusive: false,
+            max_exclusive: true,
+        };
+
+        for (member, score) in zset.iter_score_range(range, false) {
+            let coordinates = geohash::coordinates(score);
+
+            let Some(distance) = search.shape.distance_within(center, coordinates) else {
+                continue;
+            };
+
+            matches.push(GeoMatch {
+                member: member.clone(),
+                score,
+                distance,
+                coordinates,
+            });
+
+            if matches.len() >= limit {
+                break 'ranges;
+            }
+        }
+    }
+
+    match search.sort {
+        Some(Sort::Asc) => matches.sort_by(|a, b| a.distance.total_cmp(&b.distance)),
+        Some(Sort::Desc) => matches.sort_by(|a, b| b.distance.total_cmp(&a.distance)),
+        None => {}
+    }
+
+    if let Some(count) = search.count {
+        matches.truncate(count);
+    }
+
+    Ok(matches)
+}
+
+/// `GEOSEARCH key FROMMEMBER member | FROMLONLAT longitude latitude
+/// BYRADIUS radius unit | BYBOX width height unit [ASC | DESC] [COUNT count [ANY]]
+/// [WITHCOORD] [WITHDIST] [WITHHASH]`.
+pub(crate) async fn geosearch(
+    command: &str,
+    key: &Bytes,
+    args: &[Bytes],
+    protocol: ProtocolVersion,
+) -> Result<RespType, CommandExecutionError> {
+    let query = GeoSearch::parse(command, args, false)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let matches = search(&map_read, key, &query)?;
+
+    Ok(RespType::Array(Some(
+        matches
+            .into_iter()
+            .map(|found| {
+                if !(query.with_dist || query.with_hash || query.with_coord) {
+                    return RespType::bulk_string(&found.member);
+                }
+
+                let mut reply = vec![RespType::bulk_string(&found.member)];
+
+                if query.with_dist {
+                    reply.push(distance_reply(found.distance, query.conversion));
+                }
+
+                if query.with_hash {
+                    reply.push(RespType::Integer(Some(found.score as i64)));
+                }
+
+                if query.with_coord {
+                    reply.push(coordinates_reply(found.coordinates, protocol));
+                }
+
+                RespType::Array(Some(reply))
+            })
+            .collect(),
+    )))
+}
+
+/// `GEOSEARCHSTORE destination source ... [STOREDIST]`, the matches replace whatever the
+/// destination held, scored by their distance with `STOREDIST`.
+pub(crate) async fn geosearchstore(
+    command: &str,
+    destination: &Bytes,
+    key: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let query = GeoSearch::parse(command, args, true)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let matches = search(&map_write, key, &query)?;
+    let len = matches.len();
+
+    if matches.is_empty() {
+        map_write.remove(destination);
+    } else {
+        let mut zset = SortedSet::default();
+
+        for found in matches {
+            let score = if query.store_dist {
+                found.distance / query.conversion
+            } else {
+                found.score
+            };
+
+            zset.insert(found.member, score);
+        }
+
+        map_write.insert(destination.clone(), Value::new(Object::ZSet(zset), None));
+    }
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
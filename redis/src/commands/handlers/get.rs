@@ -1,23 +1,23 @@
-use crate::{commands::hash_map::HASH_MAP, resp::types::RespType};
+use bytes::Bytes;
 
-pub(crate) async fn get(key: &RespType) -> RespType {
-    let map_read = HASH_MAP.read().await;
+use crate::{
+    commands::{errors::CommandExecutionError, hash_map::HASH_MAP},
+    resp::types::RespType,
+};
 
-    let get_record = map_read.get(key);
+pub(crate) async fn get(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
 
-    match get_record {
-        Some(record) => match record.get_data() {
-            Some(value) => value,
-            None => {
+    match map_read.get(key) {
+        Some(record) => Ok(RespType::bulk_string(record.data().as_string()?)),
+        None => {
+            if map_read.is_expired(key) {
                 drop(map_read);
 
-                let mut map_write = HASH_MAP.write().await;
-
-                map_write.remove(key);
-
-                RespType::BulkString(None)
+                HASH_MAP.write().await.remove_expired(key);
             }
-        },
-        _ => RespType::BulkString(None),
+
+            Ok(RespType::BulkString(None))
+        }
     }
 }
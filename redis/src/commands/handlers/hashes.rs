@@ -0,0 +1,443 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        hash_map::{Db, HASH_MAP, hash::Hash, object::Object},
+        utils::{ExpireCondition, format_float, parse_float, parse_int, unix_time_ms},
+    },
+    resp::types::RespType,
+};
+
+pub(crate) async fn hset(key: &Bytes, pairs: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+        return Err(CommandExecutionError::WrongNumberOfArguments(
+            "hset".to_string(),
+        ));
+    }
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let hash = map_write
+        .get_or_insert_with(key, || Object::Hash(Hash::default()))
+        .data_mut()
+        .as_hash_mut()?;
+
+    let added = pairs
+        .chunks_exact(2)
+        .filter(|pair| hash.insert(pair[0].clone(), pair[1].clone()))
+        .count();
+
+    // updated fields are changes as well
+    Db::mark_dirty(pairs.len() / 2);
+
+    Ok(RespType::Integer(Some(added as i64)))
+}
+
+pub(crate) async fn hsetnx(
+    key: &Bytes,
+    field: &Bytes,
+    value: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let hash = map_write
+        .get_or_insert_with(key, || Object::Hash(Hash::default()))
+        .data_mut()
+        .as_hash_mut()?;
+
+    if hash.contains_key(field) {
+        return Ok(RespType::Integer(Some(0)));
+    }
+
+    hash.insert(field.clone(), value.clone());
+    Db::mark_dirty(1);
+
+    Ok(RespType::Integer(Some(1)))
+}
+
+pub(crate) async fn hget(key: &Bytes, field: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::BulkString(None));
+    };
+
+    Ok(record
+        .data()
+        .as_hash()?
+        .get(field)
+        .map(RespType::bulk_string)
+        .unwrap_or(RespType::BulkString(None)))
+}
+
+pub(crate) async fn hmget(
+    key: &Bytes,
+    fields: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let hash = match map_read.get(key) {
+        Some(record) => Some(record.data().as_hash()?),
+        None => None,
+    };
+
+    Ok(RespType::Array(Some(
+        fields
+            .iter()
+            .map(|field| {
+                hash.and_then(|hash| hash.get(field))
+                    .map(RespType::bulk_string)
+                    .unwrap_or(RespType::BulkString(None))
+            })
+            .collect(),
+    )))
+}
+
+pub(crate) async fn hgetall(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::Map(Some(vec![])));
+    };
+
+    Ok(RespType::Map(Some(
+        record
+            .data()
+            .as_hash()?
+            .iter()
+            .map(|(field, value)| (RespType::bulk_string(field), RespType::bulk_string(value)))
+            .collect(),
+    )))
+}
+
+pub(crate) async fn hdel(key: &Bytes, fields: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let hash = record.data_mut().as_hash_mut()?;
+    let removed = fields.iter().filter(|field| hash.remove(field)).count();
+
+    Db::mark_dirty(removed);
+
+    if hash.is_empty() {
+        map_write.remove(key);
+    }
+
+    Ok(RespType::Integer(Some(removed as i64)))
+}
+
+pub(crate) async fn hexists(key: &Bytes, field: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let exists = match map_read.get(key) {
+        Some(record) => record.data().as_hash()?.contains_key(field),
+        None => false,
+    };
+
+    Ok(RespType::Integer(Some(exists as i64)))
+}
+
+pub(crate) async fn hlen(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let len = match map_read.get(key) {
+        Some(record) => record.data().as_hash()?.len(),
+        None => 0,
+    };
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+/// `HKEYS` when `keys` is set, `HVALS` otherwise.
+pub(crate) async fn hkeys_or_values(
+    key: &Bytes,
+    keys: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::Array(Some(vec![])));
+    };
+
+    Ok(RespType::Array(Some(
+        record
+            .data()
+            .as_hash()?
+            .iter()
+            .map(|(field, value)| RespType::bulk_string(if keys { field } else { value }))
+            .collect(),
+    )))
+}
+
+pub(crate) async fn hincrby(
+    key: &Bytes,
+    field: &Bytes,
+    increment: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let increment = parse_int(increment)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let current = match map_write.get(key) {
+        Some(record) => record.data().as_hash()?.get(field),
+        None => None,
+    };
+
+    let current = match current {
+        Some(value) => {
+            parse_int(value).map_err(|_| CommandExecutionError::HashValueNotAnInteger)?
+        }
+        None => 0,
+    };
+
+    let value = current
+        .checked_add(increment)
+        .ok_or(CommandExecutionError::IncrementOverflow)?;
+
+    map_write
+        .get_or_insert_with(key, || Object::Hash(Hash::default()))
+        .data_mut()
+        .as_hash_mut()?
+        .update(field.clone(), Bytes::from(value.to_string()));
+
+    Db::mark_dirty(1);
+
+    Ok(RespType::Integer(Some(value)))
+}
+
+pub(crate) async fn hincrbyfloat(
+    key: &Bytes,
+    field: &Bytes,
+    increment: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let increment = parse_float(increment)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let current = match map_write.get(key) {
+        Some(record) => record.data().as_hash()?.get(field),
+        None => None,
+    };
+
+    let current = match current {
+        Some(value) => parse_float(value).map_err(|_| CommandExecutionError::HashValueNotAFloat)?,
+        None => 0.0,
+    };
+
+    let value = current + increment;
+
+    if !value.is_finite() {
+        return Err(CommandExecutionError::NanOrInfinity);
+    }
+
+    let value = Bytes::from(format_float(value));
+
+    map_write
+        .get_or_insert_with(key, || Object::Hash(Hash::default()))
+        .data_mut()
+        .as_hash_mut()?
+        .update(field.clone(), value.clone());
+
+    Db::mark_dirty(1);
+
+    Ok(RespType::bulk_string(&value))
+}
+
+/// Parses the trailing `FIELDS numfields field [field ...]` of the field expiration commands.
+fn parse_fields(args: &[Bytes]) -> Result<&[Bytes], CommandExecutionError> {
+    let [keyword, num_fields, fields @ ..] = args else {
+        return Err(CommandExecutionError::FieldsMissing);
+    };
+
+    if !keyword.eq_ignore_ascii_case(b"fields") {
+        return Err(CommandExecutionError::FieldsMissing);
+    }
+
+    match parse_int(num_fields)? {
+        num_fields if num_fields <= 0 => Err(CommandExecutionError::NumFieldsNotPositive),
+        num_fields if num_fields as usize != fields.len() => {
+            Err(CommandExecutionError::NumFieldsMismatch)
+        }
+        _ => Ok(fields),
+    }
+}
+
+/// Deadlines beyond this are rejected, like redis does to keep them within 48 bits.
+const MAX_FIELD_EXPIRE_AT: u64 = (1 << 48) - 1;
+
+/// `HEXPIRE` family: `in_millis` for the `HP*` variants, `absolute` for the `*AT` ones.
+pub(crate) async fn hexpire(
+    command: &str,
+    key: &Bytes,
+    time: &Bytes,
+    args: &[Bytes],
+    in_millis: bool,
+    absolute: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let invalid_time = || CommandExecutionError::InvalidExpireTime(command.to_string());
+
+    let (condition, args) = match args.split_first() {
+        Some((arg, rest)) => match ExpireCondition::parse(arg) {
+            Some(condition) => (Some(condition), rest),
+            None => (None, args),
+        },
+        None => (None, args),
+    };
+    let fields = parse_fields(args)?;
+
+    let time = parse_int(time)?;
+
+    if time < 0 {
+        return Err(invalid_time());
+    }
+
+    let now = unix_time_ms();
+    let time = time as u64;
+    let time = if in_millis {
+        time
+    } else {
+        time.checked_mul(1000).ok_or_else(invalid_time)?
+    };
+    let at = if absolute {
+        time
+    } else {
+        time.checked_add(now).ok_or_else(invalid_time)?
+    };
+
+    if at > MAX_FIELD_EXPIRE_AT {
+        return Err(invalid_time());
+    }
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Array(Some(vec![
+            RespType::Integer(Some(-2));
+            fields.len()
+        ])));
+    };
+
+    let hash = record.data_mut().as_hash_mut()?;
+    let mut changed = 0;
+
+    let replies = fields
+        .iter()
+        .map(|field| {
+            let code = if !hash.contains_key(field) {
+                -2
+            } else if condition.is_some_and(|c| !c.allows(hash.expire_at(field), at)) {
+                0
+            } else if at <= now {
+                hash.remove(field);
+                changed += 1;
+
+                2
+            } else {
+                hash.set_expire_at(field, at);
+                changed += 1;
+
+                1
+            };
+
+            RespType::Integer(Some(code))
+        })
+        .collect();
+
+    Db::mark_dirty(changed);
+
+    if hash.is_empty() {
+        map_write.remove(key);
+    } else if hash.has_volatile_fields() {
+        map_write.track_volatile_hash(key);
+    }
+
+    Ok(RespType::Array(Some(replies)))
+}
+
+/// `HTTL` family: `in_millis` for the `HP*` variants, `absolute` for the `*EXPIRETIME` ones.
+pub(crate) async fn httl(
+    key: &Bytes,
+    args: &[Bytes],
+    in_millis: bool,
+    absolute: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let fields = parse_fields(args)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let hash = match map_read.get(key) {
+        Some(record) => Some(record.data().as_hash()?),
+        None => None,
+    };
+
+    let now = unix_time_ms();
+
+    let replies = fields
+        .iter()
+        .map(|field| {
+            let code = match hash {
+                Some(hash) if hash.contains_key(field) => match hash.expire_at(field) {
+                    Some(at) => {
+                        let time = if absolute { at } else { at.saturating_sub(now) };
+
+                        if in_millis {
+                            time as i64
+                        } else {
+                            time.div_ceil(1000) as i64
+                        }
+                    }
+                    None => -1,
+                },
+                _ => -2,
+            };
+
+            RespType::Integer(Some(code))
+        })
+        .collect();
+
+    Ok(RespType::Array(Some(replies)))
+}
+
+pub(crate) async fn hpersist(
+    key: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let fields = parse_fields(args)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Array(Some(vec![
+            RespType::Integer(Some(-2));
+            fields.len()
+        ])));
+    };
+
+    let hash = record.data_mut().as_hash_mut()?;
+    let mut changed = 0;
+
+    let replies = fields
+        .iter()
+        .map(|field| {
+            let code = if !hash.contains_key(field) {
+                -2
+            } else if hash.persist(field) {
+                changed += 1;
+
+                1
+            } else {
+                -1
+            };
+
+            RespType::Integer(Some(code))
+        })
+        .collect();
+
+    Db::mark_dirty(changed);
+
+    Ok(RespType::Array(Some(replies)))
+}
@@ -0,0 +1,91 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{errors::CommandExecutionError, session::Session},
+    config::SERVER_VERSION,
+    resp::types::{ProtocolVersion, RespType},
+};
+
+const SERVER_NAME: &str = "redis";
+const DEFAULT_USER: &[u8] = b"default";
+
+fn parse_protocol_version(protover: &Bytes) -> Result<ProtocolVersion, CommandExecutionError> {
+    match protover.as_ref() {
+        b"2" => Ok(ProtocolVersion::Resp2),
+        b"3" => Ok(ProtocolVersion::Resp3),
+        _ if str::from_utf8(protover)
+            .ok()
+            .and_then(|v| v.parse::<i64>().ok())
+            .is_some() =>
+        {
+            Err(CommandExecutionError::UnsupportedProtocolVersion)
+        }
+        _ => Err(CommandExecutionError::IncorrectOptionsError(
+            "Protocol version is not an integer or out of range".to_string(),
+        )),
+    }
+}
+
+pub(crate) async fn hello(
+    session: &mut Session,
+    params: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let mut protocol = session.protocol;
+    let mut name = None;
+
+    if let [protover, options @ ..] = params {
+        protocol = parse_protocol_version(protover)?;
+
+        let mut options = options.iter();
+
+        while let Some(option) = options.next() {
+            match option.to_ascii_lowercase().as_slice() {
+                b"auth" => {
+                    let (Some(user), Some(_)) = (options.next(), options.next()) else {
+                        return Err(CommandExecutionError::SyntaxError);
+                    };
+
+                    // there is no ACL support, so only the passwordless default user exists
+                    if user.as_ref() != DEFAULT_USER {
+                        return Err(CommandExecutionError::WrongPassword);
+                    }
+                }
+                b"setname" => {
+                    let Some(client_name) = options.next() else {
+                        return Err(CommandExecutionError::SyntaxError);
+                    };
+
+                    if client_name.iter().any(|b| *b == b' ' || *b == b'\n') {
+                        return Err(CommandExecutionError::IncorrectOptionsError(
+                            "Client names cannot contain spaces, newlines or special characters."
+                                .to_string(),
+                        ));
+                    }
+
+                    name = Some(client_name.clone());
+                }
+                _ => return Err(CommandExecutionError::SyntaxError),
+            }
+        }
+    } else if !params.is_empty() {
+        return Err(CommandExecutionError::IncorrectCommandFormatError);
+    }
+
+    session.protocol = protocol;
+
+    if name.is_some() {
+        session.name = name;
+    }
+
+    let bulk = |value: &str| RespType::BulkString(Some(value.into()));
+
+    Ok(RespType::Map(Some(vec![
+        (bulk("server"), bulk(SERVER_NAME)),
+        (bulk("version"), bulk(SERVER_VERSION)),
+        (bulk("proto"), RespType::Integer(Some(protocol.into()))),
+        (bulk("id"), RespType::Integer(Some(session.id as i64))),
+        (bulk("mode"), bulk("standalone")),
+        (bulk("role"), bulk("master")),
+        (bulk("modules"), RespType::Array(Some(vec![]))),
+    ])))
+}
@@ -0,0 +1,105 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        hash_map::{
+            Db, HASH_MAP,
+            hyperloglog::{HyperLogLog, REGISTERS, count_registers},
+            object::Object,
+        },
+    },
+    resp::types::RespType,
+};
+
+pub(crate) async fn pfadd(
+    key: &Bytes,
+    elements: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let (mut hll, created) = match map_write.get(key) {
+        Some(record) => (HyperLogLog::parse(record.data().as_string()?)?, false),
+        None => (HyperLogLog::default(), true),
+    };
+
+    let mut updated = created;
+
+    for element in elements {
+        updated |= hll.add(element)?;
+    }
+
+    if !updated {
+        return Ok(RespType::Integer(Some(0)));
+    }
+
+    hll.invalidate_cache();
+
+    map_write.set_data(key, Object::String(Bytes::from(hll.into_bytes())));
+
+    Ok(RespType::Integer(Some(1)))
+}
+
+/// A single key's cardinality is cached within its value, the union of several keys is not.
+pub(crate) async fn pfcount(keys: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    if let [key] = keys {
+        let Some(record) = map_write.get_mut(key) else {
+            return Ok(RespType::Integer(Some(0)));
+        };
+
+        let bytes = record.data().as_string()?;
+        let mut hll = HyperLogLog::parse(bytes)?;
+        let cardinality = hll.count()?;
+        let hll = hll.into_bytes();
+
+        if hll != bytes.as_ref() {
+            *record.data_mut() = Object::String(Bytes::from(hll));
+            Db::mark_dirty(1);
+        }
+
+        return Ok(RespType::Integer(Some(cardinality as i64)));
+    }
+
+    let mut registers = vec![0; REGISTERS];
+
+    for key in keys {
+        if let Some(record) = map_write.get(key) {
+            HyperLogLog::parse(record.data().as_string()?)?.merge_into(&mut registers)?;
+        }
+    }
+
+    Ok(RespType::Integer(Some(count_registers(&registers) as i64)))
+}
+
+/// Merges the sources, and whatever the destination already held, into the destination.
+pub(crate) async fn pfmerge(
+    destination: &Bytes,
+    sources: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let mut registers = vec![0; REGISTERS];
+    let mut use_dense = false;
+
+    for key in std::iter::once(destination).chain(sources) {
+        if let Some(record) = map_write.get(key) {
+            let hll = HyperLogLog::parse(record.data().as_string()?)?;
+
+            use_dense |= hll.is_dense();
+            hll.merge_into(&mut registers)?;
+        }
+    }
+
+    let mut hll = match map_write.get(destination) {
+        Some(record) => HyperLogLog::parse(record.data().as_string()?)?,
+        None => HyperLogLog::default(),
+    };
+
+    hll.store_registers(&registers, use_dense)?;
+
+    map_write.set_data(destination, Object::String(Bytes::from(hll.into_bytes())));
+
+    Ok(RespType::simple_string("OK"))
+}
@@ -0,0 +1,81 @@
+use std::sync::atomic::Ordering;
+
+use bytes::Bytes;
+
+use crate::{
+    commands::{active_expire::STATS, errors::CommandExecutionError, utils::unix_time_ms},
+    rdb::snapshot::SAVE_STATE,
+    resp::types::RespType,
+};
+
+fn persistence() -> String {
+    let is_in_progress = SAVE_STATE.is_in_progress.load(Ordering::Acquire);
+    let current_save_time = if is_in_progress {
+        let started_at = SAVE_STATE.last_attempt.load(Ordering::Relaxed);
+
+        (unix_time_ms() / 1000).saturating_sub(started_at) as i64
+    } else {
+        -1
+    };
+    let last_save_status = if SAVE_STATE.is_last_save_ok.load(Ordering::Relaxed) {
+        "ok"
+    } else {
+        "err"
+    };
+
+    format!(
+        "# Persistence\r\n\
+         loading:0\r\n\
+         rdb_changes_since_last_save:{}\r\n\
+         rdb_bgsave_in_progress:{}\r\n\
+         rdb_last_save_time:{}\r\n\
+         rdb_last_bgsave_status:{last_save_status}\r\n\
+         rdb_last_bgsave_time_sec:{}\r\n\
+         rdb_current_bgsave_time_sec:{current_save_time}\r\n",
+        SAVE_STATE.changes_since_last_save(),
+        is_in_progress as u8,
+        SAVE_STATE.last_save.load(Ordering::Relaxed),
+        SAVE_STATE.last_duration.load(Ordering::Relaxed),
+    )
+}
+
+fn stats() -> String {
+    let expired_stale_perc = f64::from_bits(STATS.expired_stale_perc.load(Ordering::Relaxed));
+
+    format!(
+        "# Stats\r\n\
+         expired_keys:{}\r\n\
+         expired_stale_perc:{expired_stale_perc:.2}\r\n\
+         expired_time_cap_reached_count:{}\r\n\
+         expire_cycle_cpu_milliseconds:{}\r\n",
+        STATS.expired_keys.load(Ordering::Relaxed),
+        STATS.time_cap_reached_count.load(Ordering::Relaxed),
+        STATS.cycle_time_used.load(Ordering::Relaxed) / 1000,
+    )
+}
+
+/// `INFO [section ...]`, every section being listed when none is asked for.
+pub(crate) async fn info(sections: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let sections: Vec<Vec<u8>> = sections
+        .iter()
+        .map(|section| section.to_ascii_lowercase())
+        .collect();
+    let wants = |section: &[u8]| {
+        sections.is_empty()
+            || sections
+                .iter()
+                .any(|s| s == section || s == b"all" || s == b"default" || s == b"everything")
+    };
+
+    let mut info = vec![];
+
+    if wants(b"persistence") {
+        info.push(persistence());
+    }
+
+    if wants(b"stats") {
+        info.push(stats());
+    }
+
+    Ok(RespType::bulk_string(info.join("\r\n")))
+}
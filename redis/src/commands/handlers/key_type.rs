@@ -0,0 +1,13 @@
+use bytes::Bytes;
+
+use crate::{commands::hash_map::HASH_MAP, resp::types::RespType};
+
+pub(crate) async fn key_type(key: &Bytes) -> RespType {
+    let map_read = HASH_MAP.read().await;
+
+    // like OBJECT, telling the type of a key does not count as an access to it
+    match map_read.peek(key) {
+        Some(record) => RespType::simple_string(&record.data().object_type().to_string()),
+        None => RespType::simple_string("none"),
+    }
+}
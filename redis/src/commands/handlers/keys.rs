@@ -1,12 +1,158 @@
-use crate::{commands::hash_map::HASH_MAP, resp::types::RespType};
+use bytes::Bytes;
 
-pub(crate) async fn keys() -> RespType {
-    let mut keys = vec![];
+use crate::{
+    commands::{
+        blocking::serve_blocked_clients,
+        errors::CommandExecutionError,
+        handlers::databases::parse_db_index,
+        hash_map::{Databases, HASH_MAP, Value},
+        utils::glob_match,
+    },
+    resp::types::RespType,
+};
+
+pub(crate) async fn keys(pattern: &Bytes) -> RespType {
     let map_read = HASH_MAP.read().await;
 
-    for k in map_read.keys() {
-        keys.push(k.clone());
-    }
+    let keys = map_read
+        .keys()
+        .filter(|key| !map_read.is_expired(key))
+        .filter(|key| pattern.as_ref() == b"*" || glob_match(pattern, key))
+        .map(RespType::bulk_string)
+        .collect();
 
     RespType::Array(Some(keys))
 }
+
+pub(crate) async fn del(keys: &[Bytes]) -> RespType {
+    let mut map_write = HASH_MAP.write().await;
+
+    let removed = keys
+        .iter()
+        .filter(|key| map_write.remove(key).is_some())
+        .count();
+
+    RespType::Integer(Some(removed as i64))
+}
+
+/// Like `DEL`, but the values are freed on a blocking task once out of the keyspace so large
+/// ones do not hold the lock meanwhile.
+pub(crate) async fn unlink(keys: &[Bytes]) -> RespType {
+    let mut map_write = HASH_MAP.write().await;
+
+    let removed: Vec<Value> = keys
+        .iter()
+        .filter_map(|key| map_write.remove(key))
+        .collect();
+
+    drop(map_write);
+
+    let count = removed.len();
+
+    if count > 0 {
+        tokio::task::spawn_blocking(move || drop(removed));
+    }
+
+    RespType::Integer(Some(count as i64))
+}
+
+/// Counts the existing keys, a key given several times being counted as many times.
+pub(crate) async fn exists(keys: &[Bytes]) -> RespType {
+    let map_read = HASH_MAP.read().await;
+
+    let count = keys
+        .iter()
+        .filter(|key| map_read.get(key).is_some())
+        .count();
+
+    RespType::Integer(Some(count as i64))
+}
+
+/// Same as `EXISTS`, the point being the access recorded on the keys.
+pub(crate) async fn touch(keys: &[Bytes]) -> RespType {
+    exists(keys).await
+}
+
+/// `RENAME` and, when `only_new` is set, `RENAMENX` which leaves an existing destination
+/// alone. The key keeps its deadline.
+pub(crate) async fn rename(
+    source: &Bytes,
+    destination: &Bytes,
+    only_new: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    if map_write.get(source).is_none() {
+        return Err(CommandExecutionError::NoSuchKey);
+    }
+
+    // renaming a key to itself changes nothing, though it exists already for `RENAMENX`
+    let is_renamed = source != destination && !(only_new && map_write.get(destination).is_some());
+
+    if is_renamed && let Some(value) = map_write.remove(source) {
+        map_write.insert(destination.clone(), value);
+
+        serve_blocked_clients(&mut map_write, destination);
+    }
+
+    if only_new {
+        return Ok(RespType::Integer(Some(is_renamed as i64)));
+    }
+
+    Ok(RespType::simple_string("OK"))
+}
+
+/// `COPY source destination [DB destination-db] [REPLACE]`, the copy keeping the deadline.
+pub(crate) async fn copy(
+    source: &Bytes,
+    destination: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let mut dbs = HASH_MAP.write_all().await;
+
+    let source_db = Databases::selected();
+    let mut destination_db = source_db;
+    let mut replace = false;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.to_ascii_lowercase().as_slice() {
+            b"db" => {
+                let index = args.next().ok_or(CommandExecutionError::SyntaxError)?;
+
+                destination_db =
+                    parse_db_index(index, dbs.len(), CommandExecutionError::NotAnInteger)?;
+            }
+            b"replace" => replace = true,
+            _ => return Err(CommandExecutionError::SyntaxError),
+        }
+    }
+
+    if source_db == destination_db && source == destination {
+        return Err(CommandExecutionError::SameObject);
+    }
+
+    let Some(value) = dbs[source_db].get(source) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let copy = Value::new(value.data().clone(), value.expires_at());
+    let destination_db = &mut dbs[destination_db];
+
+    if !replace && destination_db.get(destination).is_some() {
+        return Ok(RespType::Integer(Some(0)));
+    }
+
+    destination_db.insert(destination.clone(), copy);
+    serve_blocked_clients(destination_db, destination);
+
+    Ok(RespType::Integer(Some(1)))
+}
+
+pub(crate) async fn random_key() -> RespType {
+    let mut map_write = HASH_MAP.write().await;
+
+    map_write
+        .random_key()
+        .map_or(RespType::BulkString(None), RespType::bulk_string)
+}
@@ -0,0 +1,414 @@
+use std::collections::VecDeque;
+
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        blocking::{
+            BLOCKED_CLIENTS, BlockedOp, parse_timeout, serve_blocked_clients, wait_until_served,
+        },
+        errors::CommandExecutionError,
+        hash_map::{Db, HASH_MAP, object::Object},
+        utils::{normalize_index, normalize_range, parse_count, parse_int},
+    },
+    resp::types::RespType,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ListEnd {
+    Left,
+    Right,
+}
+
+impl ListEnd {
+    pub(crate) fn parse(arg: &Bytes) -> Result<Self, CommandExecutionError> {
+        match arg.to_ascii_lowercase().as_slice() {
+            b"left" => Ok(ListEnd::Left),
+            b"right" => Ok(ListEnd::Right),
+            _ => Err(CommandExecutionError::SyntaxError),
+        }
+    }
+
+    pub(crate) fn push(&self, list: &mut VecDeque<Bytes>, value: Bytes) {
+        match self {
+            ListEnd::Left => list.push_front(value),
+            ListEnd::Right => list.push_back(value),
+        }
+    }
+
+    pub(crate) fn pop(&self, list: &mut VecDeque<Bytes>) -> Option<Bytes> {
+        match self {
+            ListEnd::Left => list.pop_front(),
+            ListEnd::Right => list.pop_back(),
+        }
+    }
+}
+
+/// Pushes to the list stored at the key, creating it if needed, and returns its new length.
+pub(crate) fn push_elements(
+    db: &mut Db,
+    key: &Bytes,
+    values: impl IntoIterator<Item = Bytes>,
+    end: ListEnd,
+) -> Result<usize, CommandExecutionError> {
+    let list = db
+        .get_or_insert_with(key, || Object::List(VecDeque::new()))
+        .data_mut()
+        .as_list_mut()?;
+
+    let len = list.len();
+
+    for value in values {
+        end.push(list, value);
+    }
+
+    Db::mark_dirty(list.len() - len);
+
+    Ok(list.len())
+}
+
+/// Pops from the list stored at the key, removing the key once the list gets empty.
+pub(crate) fn pop_element(
+    db: &mut Db,
+    key: &Bytes,
+    end: ListEnd,
+) -> Result<Option<Bytes>, CommandExecutionError> {
+    let Some(record) = db.get_mut(key) else {
+        return Ok(None);
+    };
+
+    let list = record.data_mut().as_list_mut()?;
+    let value = end.pop(list);
+
+    if value.is_some() {
+        Db::mark_dirty(1);
+    }
+
+    if list.is_empty() {
+        db.remove(key);
+    }
+
+    Ok(value)
+}
+
+/// Atomically pops an element from the source list and pushes it to the destination one.
+pub(crate) fn move_element(
+    db: &mut Db,
+    source: &Bytes,
+    destination: &Bytes,
+    from: ListEnd,
+    to: ListEnd,
+) -> Result<Option<Bytes>, CommandExecutionError> {
+    if let Some(record) = db.get(source) {
+        record.data().as_list()?;
+    }
+
+    if let Some(record) = db.get(destination) {
+        record.data().as_list()?;
+    }
+
+    let Some(value) = pop_element(db, source, from)? else {
+        return Ok(None);
+    };
+
+    push_elements(db, destination, [value.clone()], to)?;
+
+    Ok(Some(value))
+}
+
+pub(crate) async fn push(
+    key: &Bytes,
+    values: &[Bytes],
+    end: ListEnd,
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let len = push_elements(&mut map_write, key, values.iter().cloned(), end)?;
+
+    serve_blocked_clients(&mut map_write, key);
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+pub(crate) async fn pop(
+    key: &Bytes,
+    count: Option<&Bytes>,
+    end: ListEnd,
+) -> Result<RespType, CommandExecutionError> {
+    let count = count.map(parse_count).transpose()?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(match count {
+            Some(_) => RespType::Array(None),
+            None => RespType::BulkString(None),
+        });
+    };
+
+    let list = record.data_mut().as_list_mut()?;
+    let popped = std::iter::from_fn(|| end.pop(list))
+        .take(count.unwrap_or(1))
+        .collect::<Vec<_>>();
+
+    Db::mark_dirty(popped.len());
+
+    let reply = match count {
+        Some(_) => RespType::Array(Some(popped.iter().map(RespType::bulk_string).collect())),
+        None => popped
+            .first()
+            .map(RespType::bulk_string)
+            .unwrap_or(RespType::BulkString(None)),
+    };
+
+    if list.is_empty() {
+        map_write.remove(key);
+    }
+
+    Ok(reply)
+}
+
+pub(crate) async fn blocking_pop(
+    keys: &[Bytes],
+    timeout: &Bytes,
+    end: ListEnd,
+) -> Result<RespType, CommandExecutionError> {
+    let timeout = parse_timeout(timeout)?;
+
+    let (id, receiver) = {
+        let mut map_write = HASH_MAP.write().await;
+
+        for key in keys {
+            if let Some(value) = pop_element(&mut map_write, key, end)? {
+                return Ok(RespType::Array(Some(vec![
+                    RespType::bulk_string(key),
+                    RespType::bulk_string(&value),
+                ])));
+            }
+        }
+
+        BLOCKED_CLIENTS
+            .lock()
+            .expect("blocked clients lock is poisoned")
+            .block(map_write.index(), keys, BlockedOp::Pop(end))
+    };
+
+    Ok(wait_until_served(id, receiver, timeout, RespType::Array(None)).await)
+}
+
+pub(crate) async fn lmove(
+    source: &Bytes,
+    destination: &Bytes,
+    from: &Bytes,
+    to: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let (from, to) = (ListEnd::parse(from)?, ListEnd::parse(to)?);
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(value) = move_element(&mut map_write, source, destination, from, to)? else {
+        return Ok(RespType::BulkString(None));
+    };
+
+    serve_blocked_clients(&mut map_write, destination);
+
+    Ok(RespType::bulk_string(&value))
+}
+
+pub(crate) async fn blmove(
+    source: &Bytes,
+    destination: &Bytes,
+    from: &Bytes,
+    to: &Bytes,
+    timeout: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let (from, to) = (ListEnd::parse(from)?, ListEnd::parse(to)?);
+    let timeout = parse_timeout(timeout)?;
+
+    let (id, receiver) = {
+        let mut map_write = HASH_MAP.write().await;
+
+        if let Some(value) = move_element(&mut map_write, source, destination, from, to)? {
+            serve_blocked_clients(&mut map_write, destination);
+
+            return Ok(RespType::bulk_string(&value));
+        }
+
+        BLOCKED_CLIENTS
+            .lock()
+            .expect("blocked clients lock is poisoned")
+            .block(
+                map_write.index(),
+                std::slice::from_ref(source),
+                BlockedOp::Move {
+                    from,
+                    destination: destination.clone(),
+                    to,
+                },
+            )
+    };
+
+    Ok(wait_until_served(id, receiver, timeout, RespType::BulkString(None)).await)
+}
+
+pub(crate) async fn llen(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let len = match map_read.get(key) {
+        Some(record) => record.data().as_list()?.len(),
+        None => 0,
+    };
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+pub(crate) async fn lrange(
+    key: &Bytes,
+    start: &Bytes,
+    stop: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let (start, stop) = (parse_int(start)?, parse_int(stop)?);
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::Array(Some(vec![])));
+    };
+
+    let list = record.data().as_list()?;
+
+    let values = match normalize_range(start, stop, list.len()) {
+        Some((start, stop)) => list
+            .range(start..=stop)
+            .map(RespType::bulk_string)
+            .collect(),
+        None => vec![],
+    };
+
+    Ok(RespType::Array(Some(values)))
+}
+
+pub(crate) async fn lindex(key: &Bytes, index: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let index = parse_int(index)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::BulkString(None));
+    };
+
+    let list = record.data().as_list()?;
+
+    Ok(normalize_index(index, list.len())
+        .and_then(|index| list.get(index))
+        .map(RespType::bulk_string)
+        .unwrap_or(RespType::BulkString(None)))
+}
+
+pub(crate) async fn lset(
+    key: &Bytes,
+    index: &Bytes,
+    value: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let index = parse_int(index)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let list = map_write
+        .get_mut(key)
+        .ok_or(CommandExecutionError::NoSuchKey)?
+        .data_mut()
+        .as_list_mut()?;
+
+    let item = normalize_index(index, list.len())
+        .and_then(|index| list.get_mut(index))
+        .ok_or(CommandExecutionError::IndexOutOfRange)?;
+
+    *item = value.clone();
+    Db::mark_dirty(1);
+
+    Ok(RespType::simple_string("OK"))
+}
+
+pub(crate) async fn ltrim(
+    key: &Bytes,
+    start: &Bytes,
+    stop: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let (start, stop) = (parse_int(start)?, parse_int(stop)?);
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::simple_string("OK"));
+    };
+
+    let list = record.data_mut().as_list_mut()?;
+    let len = list.len();
+
+    match normalize_range(start, stop, list.len()) {
+        Some((start, stop)) => {
+            list.truncate(stop + 1);
+            list.drain(..start);
+        }
+        None => list.clear(),
+    }
+
+    Db::mark_dirty(len - list.len());
+
+    if list.is_empty() {
+        map_write.remove(key);
+    }
+
+    Ok(RespType::simple_string("OK"))
+}
+
+pub(crate) async fn lrem(
+    key: &Bytes,
+    count: &Bytes,
+    value: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let count = parse_int(count)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let list = record.data_mut().as_list_mut()?;
+
+    // a positive count removes from head to tail, a negative one from tail to head
+    let limit = if count == 0 {
+        usize::MAX
+    } else {
+        count.unsigned_abs() as usize
+    };
+    let mut removed = 0;
+
+    if count < 0 {
+        list.make_contiguous().reverse();
+    }
+
+    list.retain(|item| {
+        if removed < limit && item == value {
+            removed += 1;
+
+            return false;
+        }
+
+        true
+    });
+
+    if count < 0 {
+        list.make_contiguous().reverse();
+    }
+
+    Db::mark_dirty(removed);
+
+    if list.is_empty() {
+        map_write.remove(key);
+    }
+
+    Ok(RespType::Integer(Some(removed as i64)))
+}
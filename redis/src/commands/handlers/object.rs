@@ -0,0 +1,37 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{errors::CommandExecutionError, hash_map::HASH_MAP},
+    resp::types::RespType,
+};
+
+pub(crate) async fn object(
+    subcommand: &Bytes,
+    key: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let subcommand_lowercase = subcommand.to_ascii_lowercase();
+
+    // unknown subcommands are an error whether the key exists or not
+    if !matches!(
+        subcommand_lowercase.as_slice(),
+        b"encoding" | b"idletime" | b"freq"
+    ) {
+        return Err(CommandExecutionError::UnknownSubcommand(
+            String::from_utf8_lossy(subcommand).into_owned(),
+            "OBJECT".to_string(),
+        ));
+    }
+
+    let map_read = HASH_MAP.read().await;
+
+    // looking into a key does not count as an access to it
+    let Some(record) = map_read.peek(key) else {
+        return Ok(RespType::BulkString(None));
+    };
+
+    Ok(match subcommand_lowercase.as_slice() {
+        b"encoding" => RespType::bulk_string(record.data().encoding()),
+        b"idletime" => RespType::Integer(Some((record.idle_time() / 1000) as i64)),
+        _ => RespType::Integer(Some(record.frequency() as i64)),
+    })
+}
@@ -0,0 +1,33 @@
+use std::sync::atomic::Ordering;
+
+use crate::{
+    commands::errors::CommandExecutionError,
+    rdb::snapshot::{self, SAVE_STATE, SaveError},
+    resp::types::RespType,
+};
+
+impl From<SaveError> for CommandExecutionError {
+    fn from(error: SaveError) -> Self {
+        match error {
+            SaveError::InProgress => CommandExecutionError::SaveInProgress,
+            SaveError::Failed => CommandExecutionError::SaveFailed,
+        }
+    }
+}
+
+pub(crate) async fn save() -> Result<RespType, CommandExecutionError> {
+    snapshot::save().await?;
+
+    Ok(RespType::simple_string("OK"))
+}
+
+pub(crate) async fn bgsave() -> Result<RespType, CommandExecutionError> {
+    snapshot::bgsave().await?;
+
+    Ok(RespType::simple_string("Background saving started"))
+}
+
+/// Unix time of the last successful save.
+pub(crate) fn lastsave() -> RespType {
+    RespType::Integer(Some(SAVE_STATE.last_save.load(Ordering::Relaxed) as i64))
+}
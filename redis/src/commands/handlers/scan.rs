@@ -0,0 +1,256 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        hash_map::{HASH_MAP, object::ObjectType, scan_hash},
+        utils::{format_float, glob_match, parse_int},
+    },
+    resp::types::RespType,
+};
+
+const DEFAULT_COUNT: usize = 10;
+
+#[derive(Debug)]
+struct ScanOptions<'a> {
+    pattern: Option<&'a Bytes>,
+    count: usize,
+    object_type: Option<ObjectType>,
+    no_values: bool,
+}
+
+impl<'a> ScanOptions<'a> {
+    /// `[MATCH pattern] [COUNT count]`, plus `[TYPE type]` for `SCAN` and `[NOVALUES]` for
+    /// `HSCAN`.
+    fn parse(
+        args: &'a [Bytes],
+        allows_type: bool,
+        allows_no_values: bool,
+    ) -> Result<Self, CommandExecutionError> {
+        let mut options = Self {
+            pattern: None,
+            count: DEFAULT_COUNT,
+            object_type: None,
+            no_values: false,
+        };
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut next = || args.next().ok_or(CommandExecutionError::SyntaxError);
+
+            match arg.to_ascii_lowercase().as_slice() {
+                b"match" => {
+                    let pattern = next()?;
+
+                    // matching everything is the same as not matching at all
+                    options.pattern = (pattern.as_ref() != b"*").then_some(pattern);
+                }
+                b"count" => {
+                    options.count = match parse_int(next()?)? {
+                        count if count < 1 => return Err(CommandExecutionError::SyntaxError),
+                        count => count as usize,
+                    };
+                }
+                b"type" if allows_type => {
+                    let name = next()?;
+
+                    options.object_type = Some(ObjectType::parse(name).ok_or_else(|| {
+                        CommandExecutionError::UnknownTypeName(
+                            String::from_utf8_lossy(name).to_string(),
+                        )
+                    })?);
+                }
+                b"novalues" if allows_no_values => options.no_values = true,
+                _ => return Err(CommandExecutionError::SyntaxError),
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn matches(&self, item: &[u8]) -> bool {
+        self.pattern.is_none_or(|pattern| glob_match(pattern, item))
+    }
+}
+
+fn parse_cursor(cursor: &Bytes) -> Result<u64, CommandExecutionError> {
+    str::from_utf8(cursor)
+        .ok()
+        .and_then(|cursor| cursor.parse::<u64>().ok())
+        .ok_or(CommandExecutionError::InvalidCursor)
+}
+
+fn scan_reply(cursor: u64, items: Vec<RespType>) -> RespType {
+    RespType::Array(Some(vec![
+        RespType::bulk_string(cursor.to_string()),
+        RespType::Array(Some(items)),
+    ]))
+}
+
+/// Picks the elements of a collection visited by the next `*SCAN` call, in the same order
+/// `SCAN` visits keys: about `count` of them from `cursor` on. Returns them along with the
+/// cursor to resume from, 0 once every element was visited.
+fn scan_elements<T>(
+    elements: impl Iterator<Item = T>,
+    hash: impl Fn(&T) -> u64,
+    cursor: u64,
+    count: usize,
+) -> (Vec<T>, u64) {
+    let mut candidates: Vec<(u64, T)> = elements
+        .map(|element| (hash(&element), element))
+        .filter(|(hash, _)| *hash >= cursor)
+        .collect();
+
+    if candidates.len() <= count {
+        return (
+            candidates.into_iter().map(|(_, element)| element).collect(),
+            0,
+        );
+    }
+
+    candidates.select_nth_unstable_by_key(count - 1, |(hash, _)| *hash);
+
+    let last = candidates[count - 1].0;
+    let len = candidates.len();
+
+    // elements sharing a hash are never split across calls
+    candidates.retain(|(hash, _)| *hash <= last);
+
+    let cursor = if candidates.len() < len { last + 1 } else { 0 };
+
+    (
+        candidates.into_iter().map(|(_, element)| element).collect(),
+        cursor,
+    )
+}
+
+/// `SCAN cursor [MATCH pattern] [COUNT count] [TYPE type]`. Every key that exists during the
+/// whole iteration is returned, exactly once.
+pub(crate) async fn scan(
+    cursor: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let cursor = parse_cursor(cursor)?;
+    let options = ScanOptions::parse(args, true, false)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let (keys, cursor) = map_read.scan(cursor, options.count);
+
+    let keys = keys
+        .into_iter()
+        .filter(|key| {
+            map_read.peek(key).is_some_and(|record| {
+                options
+                    .object_type
+                    .is_none_or(|object_type| record.data().object_type() == object_type)
+            })
+        })
+        .filter(|key| options.matches(key))
+        .map(RespType::bulk_string)
+        .collect();
+
+    Ok(scan_reply(cursor, keys))
+}
+
+/// `HSCAN key cursor [MATCH pattern] [COUNT count] [NOVALUES]`.
+pub(crate) async fn hscan(
+    key: &Bytes,
+    cursor: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let cursor = parse_cursor(cursor)?;
+    let options = ScanOptions::parse(args, false, true)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(scan_reply(0, vec![]));
+    };
+
+    let (fields, cursor) = scan_elements(
+        record.data().as_hash()?.iter(),
+        |(field, _)| scan_hash(field),
+        cursor,
+        options.count,
+    );
+
+    let items = fields
+        .into_iter()
+        .filter(|(field, _)| options.matches(field))
+        .flat_map(|(field, value)| {
+            let value = (!options.no_values).then(|| RespType::bulk_string(value));
+
+            std::iter::once(RespType::bulk_string(field)).chain(value)
+        })
+        .collect();
+
+    Ok(scan_reply(cursor, items))
+}
+
+/// `SSCAN key cursor [MATCH pattern] [COUNT count]`.
+pub(crate) async fn sscan(
+    key: &Bytes,
+    cursor: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let cursor = parse_cursor(cursor)?;
+    let options = ScanOptions::parse(args, false, false)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(scan_reply(0, vec![]));
+    };
+
+    let (members, cursor) = scan_elements(
+        record.data().as_set()?.iter(),
+        |member| scan_hash(member),
+        cursor,
+        options.count,
+    );
+
+    let items = members
+        .into_iter()
+        .filter(|member| options.matches(member))
+        .map(RespType::bulk_string)
+        .collect();
+
+    Ok(scan_reply(cursor, items))
+}
+
+/// `ZSCAN key cursor [MATCH pattern] [COUNT count]`, scores being replied as strings.
+pub(crate) async fn zscan(
+    key: &Bytes,
+    cursor: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let cursor = parse_cursor(cursor)?;
+    let options = ScanOptions::parse(args, false, false)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(scan_reply(0, vec![]));
+    };
+
+    let (members, cursor) = scan_elements(
+        record.data().as_zset()?.iter_from_rank(0, false),
+        |(member, _)| scan_hash(member),
+        cursor,
+        options.count,
+    );
+
+    let items = members
+        .into_iter()
+        .filter(|(member, _)| options.matches(member))
+        .flat_map(|(member, score)| {
+            [
+                RespType::bulk_string(member),
+                RespType::bulk_string(format_float(score)),
+            ]
+        })
+        .collect();
+
+    Ok(scan_reply(cursor, items))
+}
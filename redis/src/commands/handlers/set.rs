@@ -1,59 +1,130 @@
-use std::time::Duration;
-
-use bytes::BytesMut;
+use bytes::Bytes;
 
 use crate::{
     commands::{
         errors::CommandExecutionError,
-        hash_map::{HASH_MAP, Value},
+        hash_map::{HASH_MAP, Value, object::Object},
+        utils::{parse_expire_at, unix_time_ms},
     },
     resp::types::RespType,
 };
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Condition {
+    /// Only set the key if it does not exist yet.
+    Nx,
+    /// Only set the key if it already exists.
+    Xx,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Expiry<'a> {
+    /// One of `EX`, `PX`, `EXAT` and `PXAT` with its time, validated once all options are known.
+    At(&'a [u8], &'a Bytes),
+    KeepTtl,
+}
+
+#[derive(Debug, Default)]
+struct SetOptions<'a> {
+    condition: Option<Condition>,
+    get: bool,
+    expiry: Option<Expiry<'a>>,
+}
+
+impl<'a> SetOptions<'a> {
+    /// Options can be given in any order and repeated, conflicting ones being a syntax error.
+    fn parse(args: &'a [Bytes]) -> Result<Self, CommandExecutionError> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.to_ascii_lowercase().as_slice() {
+                b"nx" if options.condition != Some(Condition::Xx) => {
+                    options.condition = Some(Condition::Nx)
+                }
+                b"xx" if options.condition != Some(Condition::Nx) => {
+                    options.condition = Some(Condition::Xx)
+                }
+                b"get" => options.get = true,
+                b"keepttl" if matches!(options.expiry, None | Some(Expiry::KeepTtl)) => {
+                    options.expiry = Some(Expiry::KeepTtl)
+                }
+                // repeating an option is fine, the last one wins
+                b"ex" | b"px" | b"exat" | b"pxat"
+                    if options.expiry.is_none_or(
+                        |expiry| matches!(expiry, Expiry::At(option, _) if option.eq_ignore_ascii_case(arg)),
+                    ) =>
+                {
+                    let time = args.next().ok_or(CommandExecutionError::SyntaxError)?;
+
+                    options.expiry = Some(Expiry::At(arg, time));
+                }
+                _ => return Err(CommandExecutionError::SyntaxError),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// `SET key value [NX | XX] [GET] [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+/// PXAT unix-time-milliseconds | KEEPTTL]`.
 pub(crate) async fn set(
-    key: &RespType,
-    value: &RespType,
-    time_op: Option<&BytesMut>,
-    time: Option<&BytesMut>,
+    key: &Bytes,
+    value: &Bytes,
+    args: &[Bytes],
 ) -> Result<RespType, CommandExecutionError> {
-    let parse_ttl = |ttl: &BytesMut| {
-        str::from_utf8(ttl.as_ref())
-            .map_err(|e| {
-                CommandExecutionError::IncorrectOptionsError(
-                    format!("cannot parse ttl value from bytes: {e}").to_string(),
-                )
-            })
-            .and_then(|str| {
-                str::parse::<u64>(str).map_err(|e| {
-                    CommandExecutionError::IncorrectOptionsError(
-                        format!("cannot parse ttl string into valid u64: {e}").to_string(),
-                    )
-                })
-            })
+    let options = SetOptions::parse(args)?;
+
+    let now = unix_time_ms();
+    let expire_at = match options.expiry {
+        Some(Expiry::At(option, time)) => Some(parse_expire_at("set", option, time, now)?),
+        _ => None,
     };
 
     let mut map_write = HASH_MAP.write().await;
 
-    match (time_op, time) {
-        (Some(op), Some(ttl)) => match op.to_ascii_lowercase().as_slice() {
-            b"px" => {
-                map_write.insert(
-                    key.clone(),
-                    Value::new(value.clone(), Some(Duration::from_millis(parse_ttl(ttl)?))),
-                );
-            }
-            b"ex" => {
-                map_write.insert(
-                    key.clone(),
-                    Value::new(value.clone(), Some(Duration::from_secs(parse_ttl(ttl)?))),
-                );
-            }
-            _ => {}
-        },
-        _ => {
-            map_write.insert(key.clone(), Value::new(value.clone(), None));
+    let current = map_write.get(key);
+    let exists = current.is_some();
+
+    // the previous value must be a string even if the condition prevents the update
+    let previous = match current {
+        Some(record) if options.get => Some(record.data().as_string()?.clone()),
+        _ => None,
+    };
+
+    let reply = if options.get {
+        previous.map_or(RespType::BulkString(None), RespType::bulk_string)
+    } else {
+        RespType::simple_string("OK")
+    };
+
+    let is_prevented = match options.condition {
+        Some(Condition::Nx) => exists,
+        Some(Condition::Xx) => !exists,
+        None => false,
+    };
+
+    if is_prevented {
+        return Ok(if options.get {
+            reply
+        } else {
+            RespType::BulkString(None)
+        });
+    }
+
+    let data = Object::String(value.clone());
+
+    match (options.expiry, expire_at) {
+        (Some(Expiry::KeepTtl), _) => map_write.set_data(key, data),
+        // a deadline in the past deletes the key right away
+        (_, Some(at)) if at <= now => {
+            map_write.remove(key);
+        }
+        (_, at) => {
+            map_write.insert(key.clone(), Value::new(data, at));
         }
     }
 
-    Ok(RespType::SimpleString(Some("OK".into())))
+    Ok(reply)
 }
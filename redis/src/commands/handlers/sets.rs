@@ -0,0 +1,275 @@
+use bytes::Bytes;
+use indexmap::IndexSet;
+use rand::{Rng, seq::index::sample};
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        hash_map::{Db, HASH_MAP, Value, object::Object},
+        utils::{parse_count, parse_int},
+    },
+    resp::types::RespType,
+};
+
+fn set_reply<'a>(members: impl IntoIterator<Item = &'a Bytes>) -> RespType {
+    RespType::Set(Some(
+        members.into_iter().map(RespType::bulk_string).collect(),
+    ))
+}
+
+pub(crate) async fn sadd(
+    key: &Bytes,
+    members: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let set = map_write
+        .get_or_insert_with(key, || Object::Set(IndexSet::new()))
+        .data_mut()
+        .as_set_mut()?;
+
+    let added = members
+        .iter()
+        .filter(|member| set.insert((*member).clone()))
+        .count();
+
+    Db::mark_dirty(added);
+
+    Ok(RespType::Integer(Some(added as i64)))
+}
+
+pub(crate) async fn srem(
+    key: &Bytes,
+    members: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let set = record.data_mut().as_set_mut()?;
+    let removed = members
+        .iter()
+        .filter(|member| set.swap_remove(*member))
+        .count();
+
+    Db::mark_dirty(removed);
+
+    if set.is_empty() {
+        map_write.remove(key);
+    }
+
+    Ok(RespType::Integer(Some(removed as i64)))
+}
+
+pub(crate) async fn smembers(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::Set(Some(vec![])));
+    };
+
+    Ok(set_reply(record.data().as_set()?))
+}
+
+pub(crate) async fn sismember(
+    key: &Bytes,
+    members: &[Bytes],
+    is_multiple: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let set = match map_read.get(key) {
+        Some(record) => Some(record.data().as_set()?),
+        None => None,
+    };
+
+    let mut replies = members
+        .iter()
+        .map(|member| RespType::Integer(Some(set.is_some_and(|set| set.contains(member)) as i64)));
+
+    if is_multiple {
+        return Ok(RespType::Array(Some(replies.collect())));
+    }
+
+    Ok(replies.next().unwrap_or(RespType::Integer(Some(0))))
+}
+
+pub(crate) async fn scard(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let len = match map_read.get(key) {
+        Some(record) => record.data().as_set()?.len(),
+        None => 0,
+    };
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+pub(crate) async fn spop(
+    key: &Bytes,
+    count: Option<&Bytes>,
+) -> Result<RespType, CommandExecutionError> {
+    let count = count.map(parse_count).transpose()?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(match count {
+            Some(_) => RespType::Set(Some(vec![])),
+            None => RespType::BulkString(None),
+        });
+    };
+
+    let set = record.data_mut().as_set_mut()?;
+    let len = set.len();
+    let mut rng = rand::rng();
+    let mut pop = || match set.len() {
+        0 => None,
+        len => set.swap_remove_index(rng.random_range(0..len)),
+    };
+
+    let reply = match count {
+        Some(count) => RespType::Set(Some(
+            std::iter::from_fn(&mut pop)
+                .take(count)
+                .map(RespType::bulk_string)
+                .collect(),
+        )),
+        None => pop()
+            .map(RespType::bulk_string)
+            .unwrap_or(RespType::BulkString(None)),
+    };
+
+    Db::mark_dirty(len - set.len());
+
+    if set.is_empty() {
+        map_write.remove(key);
+    }
+
+    Ok(reply)
+}
+
+pub(crate) async fn srandmember(
+    key: &Bytes,
+    count: Option<&Bytes>,
+) -> Result<RespType, CommandExecutionError> {
+    let count = count.map(parse_int).transpose()?;
+
+    // redis refuses counts that would make the reply absurdly large
+    if count.is_some_and(|count| count < -(i64::MAX / 2)) {
+        return Err(CommandExecutionError::NotAnInteger);
+    }
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(match count {
+            Some(_) => RespType::Array(Some(vec![])),
+            None => RespType::BulkString(None),
+        });
+    };
+
+    let set = record.data().as_set()?;
+    let mut rng = rand::rng();
+
+    let Some(count) = count else {
+        return Ok(set
+            .get_index(rng.random_range(0..set.len()))
+            .map(RespType::bulk_string)
+            .unwrap_or(RespType::BulkString(None)));
+    };
+
+    // a negative count allows the same member to be returned several times
+    let members = if count < 0 {
+        (0..count.unsigned_abs())
+            .filter_map(|_| set.get_index(rng.random_range(0..set.len())))
+            .map(RespType::bulk_string)
+            .collect()
+    } else {
+        let count = (count as usize).min(set.len());
+
+        sample(&mut rng, set.len(), count)
+            .into_iter()
+            .filter_map(|idx| set.get_index(idx))
+            .map(RespType::bulk_string)
+            .collect()
+    };
+
+    Ok(RespType::Array(Some(members)))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SetOp {
+    Inter,
+    Union,
+    Diff,
+}
+
+/// Result of the operation over the sets stored at the keys, missing keys being empty sets.
+fn compute(db: &Db, op: SetOp, keys: &[Bytes]) -> Result<IndexSet<Bytes>, CommandExecutionError> {
+    let empty = IndexSet::new();
+    let mut sets = Vec::with_capacity(keys.len());
+
+    // every key is type checked before computing anything
+    for key in keys {
+        sets.push(match db.get(key) {
+            Some(record) => record.data().as_set()?,
+            None => &empty,
+        });
+    }
+
+    let Some((first, others)) = sets.split_first() else {
+        return Ok(IndexSet::new());
+    };
+
+    Ok(match op {
+        SetOp::Inter => {
+            // probing the smallest set against the others keeps it linear in its size
+            let smallest = sets
+                .iter()
+                .min_by_key(|set| set.len())
+                .copied()
+                .unwrap_or(&empty);
+
+            smallest
+                .iter()
+                .filter(|member| sets.iter().all(|set| set.contains(*member)))
+                .cloned()
+                .collect()
+        }
+        SetOp::Union => sets.iter().flat_map(|set| set.iter()).cloned().collect(),
+        SetOp::Diff => first
+            .iter()
+            .filter(|member| others.iter().all(|set| !set.contains(*member)))
+            .cloned()
+            .collect(),
+    })
+}
+
+pub(crate) async fn set_op(op: SetOp, keys: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    Ok(set_reply(&compute(&map_read, op, keys)?))
+}
+
+/// `*STORE` variants, the result replaces whatever the destination held.
+pub(crate) async fn set_op_store(
+    op: SetOp,
+    destination: &Bytes,
+    keys: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let result = compute(&map_write, op, keys)?;
+    let len = result.len();
+
+    if result.is_empty() {
+        map_write.remove(destination);
+    } else {
+        map_write.insert(destination.clone(), Value::new(Object::Set(result), None));
+    }
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
@@ -0,0 +1,505 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        hash_map::{
+            Db, HASH_MAP,
+            object::Object,
+            sorted_set::{LexRange, ScoreRange, SortedSet},
+        },
+        utils::{normalize_range, parse_count, parse_float, parse_int},
+    },
+    resp::types::{ProtocolVersion, RespDouble, RespType},
+};
+
+fn score_reply(score: f64) -> RespType {
+    RespType::Double(Some(RespDouble(score)))
+}
+
+/// Members with their scores: a flat array on RESP2, an array of pairs on RESP3.
+fn members_reply<'a>(
+    members: impl IntoIterator<Item = (&'a Bytes, f64)>,
+    with_scores: bool,
+    protocol: ProtocolVersion,
+) -> RespType {
+    let members = members.into_iter();
+
+    let replies = match (with_scores, protocol) {
+        (false, _) => members
+            .map(|(member, _)| RespType::bulk_string(member))
+            .collect(),
+        (true, ProtocolVersion::Resp2) => members
+            .flat_map(|(member, score)| [RespType::bulk_string(member), score_reply(score)])
+            .collect(),
+        (true, ProtocolVersion::Resp3) => members
+            .map(|(member, score)| {
+                RespType::Array(Some(vec![
+                    RespType::bulk_string(member),
+                    score_reply(score),
+                ]))
+            })
+            .collect(),
+    };
+
+    RespType::Array(Some(replies))
+}
+
+#[derive(Debug, Default)]
+struct ZaddFlags {
+    nx: bool,
+    xx: bool,
+    gt: bool,
+    lt: bool,
+    ch: bool,
+    incr: bool,
+}
+
+impl ZaddFlags {
+    /// Parses the leading flags and returns them along with the remaining arguments.
+    fn parse(mut args: &[Bytes]) -> Result<(Self, &[Bytes]), CommandExecutionError> {
+        let mut flags = Self::default();
+
+        while let Some((arg, rest)) = args.split_first() {
+            match arg.to_ascii_lowercase().as_slice() {
+                b"nx" => flags.nx = true,
+                b"xx" => flags.xx = true,
+                b"gt" => flags.gt = true,
+                b"lt" => flags.lt = true,
+                b"ch" => flags.ch = true,
+                b"incr" => flags.incr = true,
+                _ => break,
+            }
+
+            args = rest;
+        }
+
+        if flags.nx && flags.xx {
+            return Err(CommandExecutionError::XxAndNx);
+        }
+
+        if (flags.gt && flags.lt) || ((flags.gt || flags.lt) && flags.nx) {
+            return Err(CommandExecutionError::GtLtAndNx);
+        }
+
+        Ok((flags, args))
+    }
+}
+
+pub(crate) async fn zadd(key: &Bytes, args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let (flags, pairs) = ZaddFlags::parse(args)?;
+
+    if pairs.is_empty() || !pairs.len().is_multiple_of(2) {
+        return Err(CommandExecutionError::SyntaxError);
+    }
+
+    if flags.incr && pairs.len() > 2 {
+        return Err(CommandExecutionError::IncrSinglePair);
+    }
+
+    // every score is validated before touching the set
+    let pairs = pairs
+        .chunks_exact(2)
+        .map(|pair| Ok((parse_float(&pair[0])?, &pair[1])))
+        .collect::<Result<Vec<_>, CommandExecutionError>>()?;
+
+    let aborted = || {
+        Ok(if flags.incr {
+            RespType::BulkString(None)
+        } else {
+            RespType::Integer(Some(0))
+        })
+    };
+
+    let mut map_write = HASH_MAP.write().await;
+
+    match map_write.get(key) {
+        Some(record) => {
+            record.data().as_zset()?;
+        }
+        None if flags.xx => return aborted(),
+        None => {}
+    }
+
+    let zset = map_write
+        .get_or_insert_with(key, || Object::ZSet(SortedSet::default()))
+        .data_mut()
+        .as_zset_mut()?;
+
+    let (mut added, mut updated) = (0, 0);
+    let mut incremented = None;
+
+    for (score, member) in pairs {
+        let score = match zset.score(member) {
+            Some(_) if flags.nx => continue,
+            Some(current) => {
+                let score = if flags.incr { current + score } else { score };
+
+                if score.is_nan() {
+                    return Err(CommandExecutionError::ScoreIsNan);
+                }
+
+                if (flags.gt && score <= current) || (flags.lt && score >= current) {
+                    continue;
+                }
+
+                if score != current {
+                    updated += 1;
+                }
+
+                score
+            }
+            None if flags.xx => continue,
+            None => {
+                added += 1;
+
+                score
+            }
+        };
+
+        zset.insert(member.clone(), score);
+        incremented = Some(score);
+    }
+
+    Db::mark_dirty((added + updated) as usize);
+
+    if flags.incr {
+        return Ok(incremented
+            .map(score_reply)
+            .unwrap_or(RespType::BulkString(None)));
+    }
+
+    Ok(RespType::Integer(Some(
+        added + if flags.ch { updated } else { 0 },
+    )))
+}
+
+pub(crate) async fn zincrby(
+    key: &Bytes,
+    increment: &Bytes,
+    member: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let increment = parse_float(increment)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let current = match map_write.get(key) {
+        Some(record) => record.data().as_zset()?.score(member),
+        None => None,
+    };
+
+    let score = current.unwrap_or_default() + increment;
+
+    if score.is_nan() {
+        return Err(CommandExecutionError::ScoreIsNan);
+    }
+
+    map_write
+        .get_or_insert_with(key, || Object::ZSet(SortedSet::default()))
+        .data_mut()
+        .as_zset_mut()?
+        .insert(member.clone(), score);
+
+    Db::mark_dirty(1);
+
+    Ok(score_reply(score))
+}
+
+pub(crate) async fn zrem(
+    key: &Bytes,
+    members: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let zset = record.data_mut().as_zset_mut()?;
+    let removed = members.iter().filter(|member| zset.remove(member)).count();
+
+    Db::mark_dirty(removed);
+
+    if zset.is_empty() {
+        map_write.remove(key);
+    }
+
+    Ok(RespType::Integer(Some(removed as i64)))
+}
+
+pub(crate) async fn zcard(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let len = match map_read.get(key) {
+        Some(record) => record.data().as_zset()?.len(),
+        None => 0,
+    };
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+pub(crate) async fn zscore(key: &Bytes, member: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let score = match map_read.get(key) {
+        Some(record) => record.data().as_zset()?.score(member),
+        None => None,
+    };
+
+    Ok(score.map(score_reply).unwrap_or(RespType::BulkString(None)))
+}
+
+/// `ZRANK` and, when reversed, `ZREVRANK`, with the optional `WITHSCORE` flag.
+pub(crate) async fn zrank(
+    key: &Bytes,
+    member: &Bytes,
+    args: &[Bytes],
+    is_reversed: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let with_score = match args {
+        [] => false,
+        [arg] if arg.eq_ignore_ascii_case(b"withscore") => true,
+        _ => return Err(CommandExecutionError::SyntaxError),
+    };
+
+    let missing = || {
+        if with_score {
+            RespType::Array(None)
+        } else {
+            RespType::BulkString(None)
+        }
+    };
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(missing());
+    };
+
+    let zset = record.data().as_zset()?;
+
+    let (Some(rank), Some(score)) = (zset.rank(member, is_reversed), zset.score(member)) else {
+        return Ok(missing());
+    };
+
+    let rank = RespType::Integer(Some(rank as i64));
+
+    Ok(if with_score {
+        RespType::Array(Some(vec![rank, score_reply(score)]))
+    } else {
+        rank
+    })
+}
+
+pub(crate) async fn zcount(
+    key: &Bytes,
+    min: &Bytes,
+    max: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let range = ScoreRange::parse(min, max)?;
+
+    let map_read = HASH_MAP.read().await;
+
+    let count = match map_read.get(key) {
+        Some(record) => record.data().as_zset()?.count_in_score_range(range),
+        None => 0,
+    };
+
+    Ok(RespType::Integer(Some(count as i64)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RangeBy {
+    Rank,
+    Score,
+    Lex,
+}
+
+#[derive(Debug)]
+struct RangeOptions {
+    by: RangeBy,
+    is_reversed: bool,
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+}
+
+impl RangeOptions {
+    /// Parses the trailing options, `BYSCORE`, `BYLEX` and `REV` being only known to the
+    /// unified `ZRANGE` syntax.
+    fn parse(mut self, args: &[Bytes], is_unified: bool) -> Result<Self, CommandExecutionError> {
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            match arg.to_ascii_lowercase().as_slice() {
+                b"withscores" => self.with_scores = true,
+                b"limit" => {
+                    let (Some(offset), Some(count)) = (args.next(), args.next()) else {
+                        return Err(CommandExecutionError::SyntaxError);
+                    };
+
+                    self.limit = Some((parse_int(offset)?, parse_int(count)?));
+                }
+                b"byscore" if is_unified => self.by = RangeBy::Score,
+                b"bylex" if is_unified => self.by = RangeBy::Lex,
+                b"rev" if is_unified => self.is_reversed = true,
+                _ => return Err(CommandExecutionError::SyntaxError),
+            }
+        }
+
+        if self.limit.is_some() && self.by == RangeBy::Rank {
+            return Err(if is_unified {
+                CommandExecutionError::LimitWithoutBy
+            } else {
+                CommandExecutionError::SyntaxError
+            });
+        }
+
+        if self.with_scores && self.by == RangeBy::Lex {
+            return Err(if is_unified {
+                CommandExecutionError::WithScoresByLex
+            } else {
+                CommandExecutionError::SyntaxError
+            });
+        }
+
+        Ok(self)
+    }
+
+    /// Applies `LIMIT offset count`, a negative offset selecting nothing and a negative count
+    /// everything after the offset.
+    fn limit<'a>(&self, members: impl Iterator<Item = (&'a Bytes, f64)>) -> Vec<(&'a Bytes, f64)> {
+        match self.limit {
+            Some((offset, _)) if offset < 0 => vec![],
+            Some((offset, count)) => members
+                .skip(offset as usize)
+                .take(usize::try_from(count).unwrap_or(usize::MAX))
+                .collect(),
+            None => members.collect(),
+        }
+    }
+}
+
+enum Range {
+    Rank(i64, i64),
+    Score(ScoreRange),
+    Lex(LexRange),
+}
+
+async fn range(
+    key: &Bytes,
+    start: &Bytes,
+    stop: &Bytes,
+    options: RangeOptions,
+    protocol: ProtocolVersion,
+) -> Result<RespType, CommandExecutionError> {
+    // reversed score and lex ranges are given from the max to the min
+    let (min, max) = if options.is_reversed && options.by != RangeBy::Rank {
+        (stop, start)
+    } else {
+        (start, stop)
+    };
+
+    let range = match options.by {
+        RangeBy::Rank => Range::Rank(parse_int(start)?, parse_int(stop)?),
+        RangeBy::Score => Range::Score(ScoreRange::parse(min, max)?),
+        RangeBy::Lex => Range::Lex(LexRange::parse(min, max)?),
+    };
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::Array(Some(vec![])));
+    };
+
+    let zset = record.data().as_zset()?;
+
+    let members = match &range {
+        Range::Rank(start, stop) => match normalize_range(*start, *stop, zset.len()) {
+            Some((start, stop)) => zset
+                .iter_from_rank(start, options.is_reversed)
+                .take(stop - start + 1)
+                .collect(),
+            None => vec![],
+        },
+        Range::Score(range) => options.limit(zset.iter_score_range(*range, options.is_reversed)),
+        Range::Lex(range) => options.limit(zset.iter_lex_range(range, options.is_reversed)),
+    };
+
+    Ok(members_reply(members, options.with_scores, protocol))
+}
+
+/// `ZRANGE key start stop [BYSCORE | BYLEX] [REV] [LIMIT offset count] [WITHSCORES]`.
+pub(crate) async fn zrange(
+    key: &Bytes,
+    start: &Bytes,
+    stop: &Bytes,
+    args: &[Bytes],
+    protocol: ProtocolVersion,
+) -> Result<RespType, CommandExecutionError> {
+    let options = RangeOptions {
+        by: RangeBy::Rank,
+        is_reversed: false,
+        limit: None,
+        with_scores: false,
+    }
+    .parse(args, true)?;
+
+    range(key, start, stop, options, protocol).await
+}
+
+/// The older range commands, e.g. `ZRANGEBYSCORE` or `ZREVRANGEBYLEX`, whose kind of range
+/// and direction come from their name.
+pub(crate) async fn zrange_legacy(
+    key: &Bytes,
+    start: &Bytes,
+    stop: &Bytes,
+    args: &[Bytes],
+    by: RangeBy,
+    is_reversed: bool,
+    protocol: ProtocolVersion,
+) -> Result<RespType, CommandExecutionError> {
+    let options = RangeOptions {
+        by,
+        is_reversed,
+        limit: None,
+        with_scores: false,
+    }
+    .parse(args, false)?;
+
+    range(key, start, stop, options, protocol).await
+}
+
+/// `ZPOPMIN` and, when reversed, `ZPOPMAX`.
+pub(crate) async fn zpop(
+    key: &Bytes,
+    count: Option<&Bytes>,
+    is_reversed: bool,
+    protocol: ProtocolVersion,
+) -> Result<RespType, CommandExecutionError> {
+    let count = count.map(parse_count).transpose()?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Array(Some(vec![])));
+    };
+
+    let zset = record.data_mut().as_zset_mut()?;
+    let popped: Vec<(Bytes, f64)> = std::iter::from_fn(|| zset.pop(is_reversed))
+        .take(count.unwrap_or(1))
+        .collect();
+
+    Db::mark_dirty(popped.len());
+
+    if zset.is_empty() {
+        map_write.remove(key);
+    }
+
+    let members = popped.iter().map(|(member, score)| (member, *score));
+
+    // without a count the reply stays a flat pair whatever the protocol
+    Ok(match count {
+        Some(_) => members_reply(members, true, protocol),
+        None => members_reply(members, true, ProtocolVersion::Resp2),
+    })
+}
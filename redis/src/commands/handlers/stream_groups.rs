@@ -0,0 +1,1067 @@
+use std::{ops::Bound, time::Duration};
+
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        blocking::{BLOCKED_CLIENTS, BlockedOp, serve_blocked_clients, wait_until_served},
+        errors::CommandExecutionError,
+        handlers::streams::{entry_reply, parse_range_bound},
+        hash_map::{
+            Db, HASH_MAP,
+            object::Object,
+            stream::{ConsumerGroup, Stream, StreamId, range_bounds},
+        },
+        utils::{parse_int, unix_time_ms},
+    },
+    resp::types::RespType,
+};
+
+fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn no_group_error(key: &[u8], group: &[u8]) -> CommandExecutionError {
+    CommandExecutionError::NoGroup(format!(
+        "No such consumer group '{}' for key name '{}'",
+        lossy(group),
+        lossy(key)
+    ))
+}
+
+fn no_key_or_group_error(key: &[u8], group: &[u8]) -> CommandExecutionError {
+    CommandExecutionError::NoGroup(format!(
+        "No such key '{}' or consumer group '{}'",
+        lossy(key),
+        lossy(group)
+    ))
+}
+
+fn read_group_error(key: &[u8], group: &[u8]) -> CommandExecutionError {
+    CommandExecutionError::NoGroup(format!(
+        "No such key '{}' or consumer group '{}' in XREADGROUP with GROUP option",
+        lossy(key),
+        lossy(group)
+    ))
+}
+
+fn id_reply(id: &StreamId) -> RespType {
+    RespType::bulk_string(id.to_string())
+}
+
+fn optional_integer(value: Option<u64>) -> RespType {
+    match value {
+        Some(value) => RespType::Integer(Some(value as i64)),
+        None => RespType::Null,
+    }
+}
+
+/// Stream stored at the key for the `XGROUP` subcommands, which all require it to exist.
+fn existing_stream<'a>(
+    db: &'a mut Db,
+    key: &[u8],
+) -> Result<&'a mut Stream, CommandExecutionError> {
+    db.get_mut(key)
+        .ok_or(CommandExecutionError::GroupKeyMissing)?
+        .data_mut()
+        .as_stream_mut()
+}
+
+/// Resolves the last delivered id of a group being created or moved, `$` standing for the
+/// last entry of the stream.
+fn parse_group_id(stream: &Stream, id: &Bytes) -> Result<StreamId, CommandExecutionError> {
+    match id.as_ref() {
+        b"$" => Ok(stream.last_id),
+        id => StreamId::parse(id, 0),
+    }
+}
+
+#[derive(Default)]
+struct GroupOptions {
+    make_stream: bool,
+    /// `Some(None)` when explicitly set to -1, which stands for unknown.
+    entries_read: Option<Option<u64>>,
+}
+
+fn parse_group_options(
+    options: &[Bytes],
+    allow_make_stream: bool,
+) -> Result<GroupOptions, CommandExecutionError> {
+    let mut parsed = GroupOptions::default();
+    let mut idx = 0;
+
+    while let Some(option) = options.get(idx) {
+        match (option.to_ascii_lowercase().as_slice(), options.get(idx + 1)) {
+            (b"mkstream", _) if allow_make_stream => {
+                parsed.make_stream = true;
+                idx += 1;
+            }
+            (b"entriesread", Some(value)) => {
+                parsed.entries_read = match parse_int(value)? {
+                    -1 => Some(None),
+                    entries_read if entries_read < 0 => {
+                        return Err(CommandExecutionError::InvalidEntriesRead);
+                    }
+                    entries_read => Some(Some(entries_read as u64)),
+                };
+                idx += 2;
+            }
+            _ => return Err(CommandExecutionError::SyntaxError),
+        }
+    }
+
+    Ok(parsed)
+}
+
+pub(crate) async fn xgroup(
+    subcommand: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let name = subcommand.to_ascii_lowercase();
+
+    match (name.as_slice(), args) {
+        (b"create", [key, group, id, options @ ..]) => {
+            let options = parse_group_options(options, true)?;
+            let mut map_write = HASH_MAP.write().await;
+
+            if !options.make_stream && map_write.get(key).is_none() {
+                return Err(CommandExecutionError::GroupKeyMissing);
+            }
+
+            let stream = map_write
+                .get_or_insert_with(key, || Object::Stream(Stream::default()))
+                .data_mut()
+                .as_stream_mut()?;
+
+            if stream.groups.contains_key(group) {
+                return Err(CommandExecutionError::BusyGroup);
+            }
+
+            let id = parse_group_id(stream, id)?;
+            let entries_read = options
+                .entries_read
+                .unwrap_or_else(|| stream.entries_read_until(&id));
+
+            stream
+                .groups
+                .insert(group.clone(), ConsumerGroup::new(id, entries_read));
+            Db::mark_dirty(1);
+
+            Ok(RespType::simple_string("OK"))
+        }
+        (b"setid", [key, group, id, options @ ..]) => {
+            let options = parse_group_options(options, false)?;
+            let mut map_write = HASH_MAP.write().await;
+            let stream = existing_stream(&mut map_write, key)?;
+
+            let id = parse_group_id(stream, id)?;
+            let entries_read = options
+                .entries_read
+                .unwrap_or_else(|| stream.entries_read_until(&id));
+
+            let group = stream
+                .groups
+                .get_mut(group)
+                .ok_or_else(|| no_group_error(key, group))?;
+
+            group.last_delivered_id = id;
+            group.entries_read = entries_read;
+            Db::mark_dirty(1);
+
+            Ok(RespType::simple_string("OK"))
+        }
+        (b"destroy", [key, group]) => {
+            let mut map_write = HASH_MAP.write().await;
+            let destroyed = existing_stream(&mut map_write, key)?
+                .groups
+                .remove(group)
+                .is_some();
+
+            if destroyed {
+                Db::mark_dirty(1);
+
+                // clients blocked reading through the group get told it is gone
+                serve_blocked_clients(&mut map_write, key);
+            }
+
+            Ok(RespType::Integer(Some(destroyed as i64)))
+        }
+        (b"createconsumer", [key, group, consumer]) => {
+            let mut map_write = HASH_MAP.write().await;
+            let group = existing_stream(&mut map_write, key)?
+                .groups
+                .get_mut(group)
+                .ok_or_else(|| no_group_error(key, group))?;
+
+            let created = !group.consumers.contains_key(consumer);
+
+            if created {
+                group.consumer_mut(consumer, unix_time_ms());
+                Db::mark_dirty(1);
+            }
+
+            Ok(RespType::Integer(Some(created as i64)))
+        }
+        (b"delconsumer", [key, group, consumer]) => {
+            let mut map_write = HASH_MAP.write().await;
+            let group = existing_stream(&mut map_write, key)?
+                .groups
+                .get_mut(group)
+                .ok_or_else(|| no_group_error(key, group))?;
+
+            if !group.consumers.contains_key(consumer) {
+                return Ok(RespType::Integer(Some(0)));
+            }
+
+            let pending = group.remove_consumer(consumer);
+            Db::mark_dirty(1);
+
+            Ok(RespType::Integer(Some(pending as i64)))
+        }
+        (b"create" | b"setid" | b"destroy" | b"createconsumer" | b"delconsumer", _) => Err(
+            CommandExecutionError::WrongNumberOfArguments(format!("xgroup|{}", lossy(&name))),
+        ),
+        _ => Err(CommandExecutionError::UnknownSubcommand(
+            lossy(subcommand),
+            "XGROUP".to_string(),
+        )),
+    }
+}
+
+/// Delivers the entries the group has not handed out yet to the consumer, as replied by
+/// `XREADGROUP` for the key, or `None` when there are none.
+pub(crate) fn read_group(
+    db: &mut Db,
+    key: &Bytes,
+    group: &Bytes,
+    consumer: &Bytes,
+    count: Option<usize>,
+    no_ack: bool,
+) -> Result<Option<RespType>, CommandExecutionError> {
+    let entries = db
+        .get_mut(key)
+        .ok_or_else(|| read_group_error(key, group))?
+        .data_mut()
+        .as_stream_mut()?
+        .deliver_new_entries(group, consumer, count, no_ack)
+        .ok_or_else(|| read_group_error(key, group))?;
+
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    Db::mark_dirty(entries.len());
+
+    Ok(Some(RespType::Array(Some(vec![
+        RespType::bulk_string(key),
+        RespType::Array(Some(
+            entries
+                .iter()
+                .map(|(id, entry)| entry_reply(id, entry))
+                .collect(),
+        )),
+    ]))))
+}
+
+/// Entries already delivered to the consumer after the id and not acknowledged yet,
+/// those deleted from the stream meanwhile come without their fields.
+fn read_group_history(
+    db: &mut Db,
+    key: &Bytes,
+    group: &Bytes,
+    consumer: &Bytes,
+    id: &StreamId,
+    count: Option<usize>,
+) -> Result<RespType, CommandExecutionError> {
+    let Stream {
+        entries, groups, ..
+    } = db
+        .get_mut(key)
+        .ok_or_else(|| read_group_error(key, group))?
+        .data_mut()
+        .as_stream_mut()?;
+
+    let consumer = groups
+        .get_mut(group)
+        .ok_or_else(|| read_group_error(key, group))?
+        .consumer_mut(consumer, unix_time_ms());
+
+    let replies = consumer
+        .pending
+        .range((Bound::Excluded(*id), Bound::Unbounded))
+        .take(count.unwrap_or(usize::MAX))
+        .map(|id| match entries.get(id) {
+            Some(entry) => entry_reply(id, entry),
+            None => RespType::Array(Some(vec![id_reply(id), RespType::Array(None)])),
+        })
+        .collect();
+
+    Ok(RespType::Array(Some(vec![
+        RespType::bulk_string(key),
+        RespType::Array(Some(replies)),
+    ])))
+}
+
+pub(crate) async fn xreadgroup(args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let mut group = None;
+    let mut count = None;
+    let mut block = None;
+    let mut no_ack = false;
+    let mut idx = 0;
+
+    let streams = loop {
+        match (
+            args.get(idx).map(|arg| arg.to_ascii_lowercase()).as_deref(),
+            args.get(idx + 1),
+        ) {
+            (Some(b"group"), Some(name)) => {
+                let consumer = args
+                    .get(idx + 2)
+                    .ok_or(CommandExecutionError::SyntaxError)?;
+
+                group = Some((name, consumer));
+                idx += 3;
+            }
+            (Some(b"count"), Some(value)) => {
+                count = Some(parse_int(value)?)
+                    .filter(|count| *count > 0)
+                    .map(|count| count as usize);
+                idx += 2;
+            }
+            (Some(b"block"), Some(value)) => {
+                match parse_int(value) {
+                    Ok(timeout) if timeout < 0 => {
+                        return Err(CommandExecutionError::NegativeTimeout);
+                    }
+                    Ok(timeout) => block = Some(timeout as u64),
+                    Err(_) => return Err(CommandExecutionError::InvalidIntegerTimeout),
+                }
+                idx += 2;
+            }
+            (Some(b"noack"), _) => {
+                no_ack = true;
+                idx += 1;
+            }
+            (Some(b"streams"), Some(_)) => break &args[idx + 1..],
+            _ => return Err(CommandExecutionError::SyntaxError),
+        }
+    };
+
+    let Some((group, consumer)) = group else {
+        return Err(CommandExecutionError::MissingGroupOption);
+    };
+
+    if streams.len() % 2 != 0 {
+        return Err(CommandExecutionError::UnbalancedStreams(
+            "xreadgroup".to_string(),
+        ));
+    }
+
+    let (keys, ids) = streams.split_at(streams.len() / 2);
+
+    // `None` stands for `>`, the entries never delivered to any consumer of the group
+    let ids = ids
+        .iter()
+        .map(|id| match id.as_ref() {
+            b">" => Ok(None),
+            b"$" => Err(CommandExecutionError::LastIdInReadGroup),
+            id => StreamId::parse(id, 0).map(Some),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (id, receiver) = {
+        let mut map_write = HASH_MAP.write().await;
+
+        // nothing gets delivered unless every key has the group
+        for key in keys {
+            let has_group = match map_write.get(key) {
+                Some(record) => record.data().as_stream()?.groups.contains_key(group),
+                None => false,
+            };
+
+            if !has_group {
+                return Err(read_group_error(key, group));
+            }
+        }
+
+        let mut replies = vec![];
+
+        for (key, id) in keys.iter().zip(&ids) {
+            match id {
+                None => {
+                    if let Some(reply) =
+                        read_group(&mut map_write, key, group, consumer, count, no_ack)?
+                    {
+                        replies.push(reply);
+                    }
+                }
+                Some(id) => replies.push(read_group_history(
+                    &mut map_write,
+                    key,
+                    group,
+                    consumer,
+                    id,
+                    count,
+                )?),
+            }
+        }
+
+        if !replies.is_empty() {
+            return Ok(RespType::Array(Some(replies)));
+        }
+
+        if block.is_none() {
+            return Ok(RespType::Array(None));
+        }
+
+        BLOCKED_CLIENTS
+            .lock()
+            .expect("blocked clients lock is poisoned")
+            .block(
+                map_write.index(),
+                keys,
+                BlockedOp::ReadGroup {
+                    group: group.clone(),
+                    consumer: consumer.clone(),
+                    count,
+                    no_ack,
+                },
+            )
+    };
+
+    let timeout = block.filter(|block| *block > 0).map(Duration::from_millis);
+
+    Ok(wait_until_served(id, receiver, timeout, RespType::Array(None)).await)
+}
+
+pub(crate) async fn xack(
+    key: &Bytes,
+    group: &Bytes,
+    ids: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let ids = ids
+        .iter()
+        .map(|id| StreamId::parse(id, 0))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let Some(group) = record.data_mut().as_stream_mut()?.groups.get_mut(group) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let acknowledged = ids.iter().filter(|id| group.ack(id)).count();
+
+    Db::mark_dirty(acknowledged);
+
+    Ok(RespType::Integer(Some(acknowledged as i64)))
+}
+
+pub(crate) async fn xpending(
+    key: &Bytes,
+    group: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let (min_idle, range_args) = match args {
+        [option, min_idle, rest @ ..] if option.eq_ignore_ascii_case(b"idle") => {
+            (Some(parse_int(min_idle)?.max(0) as u64), rest)
+        }
+        rest => (None, rest),
+    };
+
+    let range = match range_args {
+        [] if min_idle.is_none() => None,
+        [start, end, count, consumer @ ..] if consumer.len() <= 1 => Some((
+            parse_range_bound(start, true)?,
+            parse_range_bound(end, false)?,
+            parse_int(count)?.max(0) as usize,
+            consumer.first(),
+        )),
+        _ => return Err(CommandExecutionError::SyntaxError),
+    };
+
+    let map_read = HASH_MAP.read().await;
+
+    let group_name = group;
+    let group = map_read
+        .get(key)
+        .ok_or_else(|| no_key_or_group_error(key, group_name))?
+        .data()
+        .as_stream()?
+        .groups
+        .get(group_name)
+        .ok_or_else(|| no_key_or_group_error(key, group_name))?;
+
+    let Some((start, end, count, consumer)) = range else {
+        let (Some(first_id), Some(last_id)) = (
+            group.pending.keys().next(),
+            group.pending.keys().next_back(),
+        ) else {
+            return Ok(RespType::Array(Some(vec![
+                RespType::Integer(Some(0)),
+                RespType::BulkString(None),
+                RespType::BulkString(None),
+                RespType::Array(None),
+            ])));
+        };
+
+        let consumers = group
+            .consumers
+            .iter()
+            .filter(|(_, consumer)| !consumer.pending.is_empty())
+            .map(|(name, consumer)| {
+                RespType::Array(Some(vec![
+                    RespType::bulk_string(name),
+                    RespType::bulk_string(consumer.pending.len().to_string()),
+                ]))
+            })
+            .collect();
+
+        return Ok(RespType::Array(Some(vec![
+            RespType::Integer(Some(group.pending.len() as i64)),
+            id_reply(first_id),
+            id_reply(last_id),
+            RespType::Array(Some(consumers)),
+        ])));
+    };
+
+    let now = unix_time_ms();
+
+    let entries = group
+        .pending
+        .range(range_bounds(start, end))
+        .filter(|(_, pending)| consumer.is_none_or(|consumer| pending.consumer == consumer))
+        .map(|(id, pending)| (id, pending, now.saturating_sub(pending.delivery_time)))
+        .filter(|(_, _, idle)| min_idle.is_none_or(|min_idle| *idle >= min_idle))
+        .take(count)
+        .map(|(id, pending, idle)| {
+            RespType::Array(Some(vec![
+                id_reply(id),
+                RespType::bulk_string(&pending.consumer),
+                RespType::Integer(Some(idle as i64)),
+                RespType::Integer(Some(pending.delivery_count as i64)),
+            ]))
+        })
+        .collect();
+
+    Ok(RespType::Array(Some(entries)))
+}
+
+fn parse_min_idle(min_idle: &Bytes, command: &str) -> Result<u64, CommandExecutionError> {
+    parse_int(min_idle)
+        .map(|min_idle| min_idle.max(0) as u64)
+        .map_err(|_| CommandExecutionError::InvalidMinIdleTime(command.to_string()))
+}
+
+pub(crate) async fn xclaim(
+    key: &Bytes,
+    group: &Bytes,
+    consumer: &Bytes,
+    min_idle: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let min_idle = parse_min_idle(min_idle, "XCLAIM")?;
+    let now = unix_time_ms();
+
+    let mut ids = vec![];
+
+    for arg in args {
+        match StreamId::parse(arg, 0) {
+            Ok(id) => ids.push(id),
+            // the ids are followed by the options, the first argument has to be one though
+            Err(e) if ids.is_empty() => return Err(e),
+            Err(_) => break,
+        }
+    }
+
+    let mut delivery_time = now;
+    let mut retry_count = None;
+    let mut force = false;
+    let mut just_id = false;
+    let mut last_id = None;
+    let mut idx = ids.len();
+
+    while let Some(option) = args.get(idx) {
+        match (option.to_ascii_lowercase().as_slice(), args.get(idx + 1)) {
+            (b"idle", Some(value)) => {
+                delivery_time = now.saturating_sub(parse_int(value)?.max(0) as u64);
+                idx += 2;
+            }
+            (b"time", Some(value)) => {
+                delivery_time = parse_int(value)?.max(0) as u64;
+                idx += 2;
+            }
+            (b"retrycount", Some(value)) => {
+                retry_count = Some(parse_int(value)?.max(0) as u64);
+                idx += 2;
+            }
+            (b"lastid", Some(value)) => {
+                last_id = Some(StreamId::parse(value, 0)?);
+                idx += 2;
+            }
+            (b"force", _) => {
+                force = true;
+                idx += 1;
+            }
+            (b"justid", _) => {
+                just_id = true;
+                idx += 1;
+            }
+            _ => return Err(CommandExecutionError::SyntaxError),
+        }
+    }
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let group_name = group;
+    let Stream {
+        entries, groups, ..
+    } = map_write
+        .get_mut(key)
+        .ok_or_else(|| no_key_or_group_error(key, group_name))?
+        .data_mut()
+        .as_stream_mut()?;
+    let group = groups
+        .get_mut(group_name)
+        .ok_or_else(|| no_key_or_group_error(key, group_name))?;
+
+    if let Some(last_id) = last_id
+        && last_id > group.last_delivered_id
+    {
+        group.last_delivered_id = last_id;
+    }
+
+    group.consumer_mut(consumer, now);
+
+    let mut replies = vec![];
+
+    for id in ids {
+        let Some(entry) = entries.get(&id) else {
+            // deleted entries can never be delivered again
+            group.ack(&id);
+
+            continue;
+        };
+
+        let delivery_count = match group.pending.get(&id) {
+            Some(pending) if now.saturating_sub(pending.delivery_time) < min_idle => continue,
+            Some(pending) => pending.delivery_count,
+            // forcing creates the pending entry regardless of the idle time
+            None if force => 0,
+            None => continue,
+        };
+
+        let delivery_count = match retry_count {
+            Some(retry_count) => retry_count,
+            None if just_id => delivery_count,
+            None => delivery_count + 1,
+        };
+
+        group.assign(id, consumer, delivery_time, delivery_count);
+
+        replies.push(if just_id {
+            id_reply(&id)
+        } else {
+            entry_reply(&id, entry)
+        });
+    }
+
+    if !replies.is_empty() {
+        group.consumer_mut(consumer, now).active_time = Some(now);
+    }
+
+    Db::mark_dirty(replies.len());
+
+    Ok(RespType::Array(Some(replies)))
+}
+
+pub(crate) async fn xautoclaim(
+    key: &Bytes,
+    group: &Bytes,
+    consumer: &Bytes,
+    min_idle: &Bytes,
+    start: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let min_idle = parse_min_idle(min_idle, "XAUTOCLAIM")?;
+    let start = parse_range_bound(start, true)?;
+
+    let mut count = 100;
+    let mut just_id = false;
+    let mut idx = 0;
+
+    while let Some(option) = args.get(idx) {
+        match (option.to_ascii_lowercase().as_slice(), args.get(idx + 1)) {
+            (b"count", Some(value)) => {
+                count = match parse_int(value)? {
+                    count if count <= 0 => return Err(CommandExecutionError::CountNotPositive),
+                    count => count as usize,
+                };
+                idx += 2;
+            }
+            (b"justid", _) => {
+                just_id = true;
+                idx += 1;
+            }
+            _ => return Err(CommandExecutionError::SyntaxError),
+        }
+    }
+
+    let now = unix_time_ms();
+    let mut map_write = HASH_MAP.write().await;
+
+    let group_name = group;
+    let Stream {
+        entries, groups, ..
+    } = map_write
+        .get_mut(key)
+        .ok_or_else(|| no_key_or_group_error(key, group_name))?
+        .data_mut()
+        .as_stream_mut()?;
+    let group = groups
+        .get_mut(group_name)
+        .ok_or_else(|| no_key_or_group_error(key, group_name))?;
+
+    group.consumer_mut(consumer, now);
+
+    // scanning is bounded even when hardly any pending entry is idle enough
+    let attempts = count.saturating_mul(10);
+    let candidates: Vec<StreamId> = group
+        .pending
+        .range(range_bounds(start, Bound::Unbounded))
+        .map(|(id, _)| *id)
+        .take(attempts.saturating_add(1))
+        .collect();
+
+    let mut claimed = vec![];
+    let mut deleted = vec![];
+    let mut scanned = 0;
+
+    while scanned < attempts && claimed.len() < count {
+        let Some(id) = candidates.get(scanned) else {
+            break;
+        };
+
+        scanned += 1;
+
+        let Some(entry) = entries.get(id) else {
+            group.ack(id);
+            deleted.push(id_reply(id));
+
+            continue;
+        };
+
+        let Some(pending) = group.pending.get(id) else {
+            continue;
+        };
+
+        if now.saturating_sub(pending.delivery_time) < min_idle {
+            continue;
+        }
+
+        let delivery_count = pending.delivery_count + if just_id { 0 } else { 1 };
+
+        group.assign(*id, consumer, now, delivery_count);
+
+        claimed.push(if just_id {
+            id_reply(id)
+        } else {
+            entry_reply(id, entry)
+        });
+    }
+
+    if !claimed.is_empty() {
+        group.consumer_mut(consumer, now).active_time = Some(now);
+    }
+
+    Db::mark_dirty(claimed.len() + deleted.len());
+
+    let cursor = candidates.get(scanned).copied().unwrap_or(StreamId::MIN);
+
+    Ok(RespType::Array(Some(vec![
+        id_reply(&cursor),
+        RespType::Array(Some(claimed)),
+        RespType::Array(Some(deleted)),
+    ])))
+}
+
+fn group_info(stream: &Stream, name: &Bytes, group: &ConsumerGroup) -> RespType {
+    RespType::Map(Some(vec![
+        (RespType::bulk_string("name"), RespType::bulk_string(name)),
+        (
+            RespType::bulk_string("consumers"),
+            RespType::Integer(Some(group.consumers.len() as i64)),
+        ),
+        (
+            RespType::bulk_string("pending"),
+            RespType::Integer(Some(group.pending.len() as i64)),
+        ),
+        (
+            RespType::bulk_string("last-delivered-id"),
+            id_reply(&group.last_delivered_id),
+        ),
+        (
+            RespType::bulk_string("entries-read"),
+            optional_integer(group.entries_read),
+        ),
+        (
+            RespType::bulk_string("lag"),
+            optional_integer(stream.lag(group)),
+        ),
+    ]))
+}
+
+fn full_group_info(stream: &Stream, name: &Bytes, group: &ConsumerGroup) -> RespType {
+    let pending = group
+        .pending
+        .iter()
+        .map(|(id, pending)| {
+            RespType::Array(Some(vec![
+                id_reply(id),
+                RespType::bulk_string(&pending.consumer),
+                RespType::Integer(Some(pending.delivery_time as i64)),
+                RespType::Integer(Some(pending.delivery_count as i64)),
+            ]))
+        })
+        .collect();
+
+    let consumers = group
+        .consumers
+        .iter()
+        .map(|(name, consumer)| {
+            let pending = consumer
+                .pending
+                .iter()
+                .filter_map(|id| group.pending.get(id).map(|pending| (id, pending)))
+                .map(|(id, pending)| {
+                    RespType::Array(Some(vec![
+                        id_reply(id),
+                        RespType::Integer(Some(pending.delivery_time as i64)),
+                        RespType::Integer(Some(pending.delivery_count as i64)),
+                    ]))
+                })
+                .collect();
+
+            RespType::Map(Some(vec![
+                (RespType::bulk_string("name"), RespType::bulk_string(name)),
+                (
+                    RespType::bulk_string("seen-time"),
+                    RespType::Integer(Some(consumer.seen_time as i64)),
+                ),
+                (
+                    RespType::bulk_string("active-time"),
+                    RespType::Integer(Some(consumer.active_time.map_or(-1, |time| time as i64))),
+                ),
+                (
+                    RespType::bulk_string("pel-count"),
+                    RespType::Integer(Some(consumer.pending.len() as i64)),
+                ),
+                (RespType::bulk_string("pel"), RespType::Array(Some(pending))),
+            ]))
+        })
+        .collect();
+
+    RespType::Map(Some(vec![
+        (RespType::bulk_string("name"), RespType::bulk_string(name)),
+        (
+            RespType::bulk_string("last-delivered-id"),
+            id_reply(&group.last_delivered_id),
+        ),
+        (
+            RespType::bulk_string("entries-read"),
+            optional_integer(group.entries_read),
+        ),
+        (
+            RespType::bulk_string("lag"),
+            optional_integer(stream.lag(group)),
+        ),
+        (
+            RespType::bulk_string("pel-count"),
+            RespType::Integer(Some(group.pending.len() as i64)),
+        ),
+        (RespType::bulk_string("pel"), RespType::Array(Some(pending))),
+        (
+            RespType::bulk_string("consumers"),
+            RespType::Array(Some(consumers)),
+        ),
+    ]))
+}
+
+/// `XINFO STREAM`, `full_count` is set for the `FULL` form with zero meaning every entry.
+fn stream_info(stream: &Stream, full_count: Option<usize>) -> RespType {
+    // entries are not packed into radix tree nodes here, each one counts as its own node
+    let mut info = vec![
+        (
+            RespType::bulk_string("length"),
+            RespType::Integer(Some(stream.len() as i64)),
+        ),
+        (
+            RespType::bulk_string("radix-tree-keys"),
+            RespType::Integer(Some(stream.len() as i64)),
+        ),
+        (
+            RespType::bulk_string("radix-tree-nodes"),
+            RespType::Integer(Some(stream.len() as i64)),
+        ),
+        (
+            RespType::bulk_string("last-generated-id"),
+            id_reply(&stream.last_id),
+        ),
+        (
+            RespType::bulk_string("max-deleted-entry-id"),
+            id_reply(&stream.max_deleted_id),
+        ),
+        (
+            RespType::bulk_string("entries-added"),
+            RespType::Integer(Some(stream.entries_added as i64)),
+        ),
+        (
+            RespType::bulk_string("recorded-first-entry-id"),
+            id_reply(&stream.first_id().unwrap_or_default()),
+        ),
+    ];
+
+    match full_count {
+        None => {
+            let first_entry = stream.entries.iter().next();
+            let last_entry = stream.entries.iter().next_back();
+            let entry_or_null = |entry: Option<_>| {
+                entry.map_or(RespType::Null, |(id, entry)| entry_reply(id, entry))
+            };
+
+            info.extend([
+                (
+                    RespType::bulk_string("groups"),
+                    RespType::Integer(Some(stream.groups.len() as i64)),
+                ),
+                (
+                    RespType::bulk_string("first-entry"),
+                    entry_or_null(first_entry),
+                ),
+                (
+                    RespType::bulk_string("last-entry"),
+                    entry_or_null(last_entry),
+                ),
+            ]);
+        }
+        Some(count) => {
+            let count = if count == 0 { usize::MAX } else { count };
+
+            info.extend([
+                (
+                    RespType::bulk_string("entries"),
+                    RespType::Array(Some(
+                        stream
+                            .entries
+                            .iter()
+                            .take(count)
+                            .map(|(id, entry)| entry_reply(id, entry))
+                            .collect(),
+                    )),
+                ),
+                (
+                    RespType::bulk_string("groups"),
+                    RespType::Array(Some(
+                        stream
+                            .groups
+                            .iter()
+                            .map(|(name, group)| full_group_info(stream, name, group))
+                            .collect(),
+                    )),
+                ),
+            ]);
+        }
+    }
+
+    RespType::Map(Some(info))
+}
+
+pub(crate) async fn xinfo(
+    subcommand: &Bytes,
+    args: &[Bytes],
+) -> Result<RespType, CommandExecutionError> {
+    let name = subcommand.to_ascii_lowercase();
+
+    let (key, rest) = match (name.as_slice(), args) {
+        (b"groups", [key]) | (b"stream", [key, ..]) | (b"consumers", [key, _]) => (key, &args[1..]),
+        (b"groups" | b"stream" | b"consumers", _) => {
+            return Err(CommandExecutionError::WrongNumberOfArguments(format!(
+                "xinfo|{}",
+                lossy(&name)
+            )));
+        }
+        _ => {
+            return Err(CommandExecutionError::UnknownSubcommand(
+                lossy(subcommand),
+                "XINFO".to_string(),
+            ));
+        }
+    };
+
+    let map_read = HASH_MAP.read().await;
+
+    let stream = map_read
+        .get(key)
+        .ok_or(CommandExecutionError::NoSuchKey)?
+        .data()
+        .as_stream()?;
+
+    match (name.as_slice(), rest) {
+        (b"groups", _) => Ok(RespType::Array(Some(
+            stream
+                .groups
+                .iter()
+                .map(|(name, group)| group_info(stream, name, group))
+                .collect(),
+        ))),
+        (b"consumers", [group]) => {
+            let now = unix_time_ms();
+            let group = stream
+                .groups
+                .get(group)
+                .ok_or_else(|| no_group_error(key, group))?;
+
+            Ok(RespType::Array(Some(
+                group
+                    .consumers
+                    .iter()
+                    .map(|(name, consumer)| {
+                        let inactive = consumer
+                            .active_time
+                            .map_or(-1, |time| now.saturating_sub(time) as i64);
+
+                        RespType::Map(Some(vec![
+                            (RespType::bulk_string("name"), RespType::bulk_string(name)),
+                            (
+                                RespType::bulk_string("pending"),
+                                RespType::Integer(Some(consumer.pending.len() as i64)),
+                            ),
+                            (
+                                RespType::bulk_string("idle"),
+                                RespType::Integer(Some(
+                                    now.saturating_sub(consumer.seen_time) as i64
+                                )),
+                            ),
+                            (
+                                RespType::bulk_string("inactive"),
+                                RespType::Integer(Some(inactive)),
+                            ),
+                        ]))
+                    })
+                    .collect(),
+            )))
+        }
+        (_, []) => Ok(stream_info(stream, None)),
+        (_, [full]) if full.eq_ignore_ascii_case(b"full") => Ok(stream_info(stream, Some(10))),
+        (_, [full, option, count])
+            if full.eq_ignore_ascii_case(b"full") && option.eq_ignore_ascii_case(b"count") =>
+        {
+            Ok(stream_info(stream, Some(parse_int(count)?.max(0) as usize)))
+        }
+        _ => Err(CommandExecutionError::SyntaxError),
+    }
+}
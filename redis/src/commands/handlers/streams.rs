@@ -0,0 +1,398 @@
+use std::{ops::Bound, time::Duration};
+
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        blocking::{BLOCKED_CLIENTS, BlockedOp, serve_blocked_clients, wait_until_served},
+        errors::CommandExecutionError,
+        hash_map::{
+            Db, HASH_MAP,
+            object::Object,
+            stream::{NewStreamId, Stream, StreamEntry, StreamId, TrimStrategy},
+        },
+        utils::{parse_count, parse_int},
+    },
+    resp::types::RespType,
+};
+
+struct TrimOptions {
+    strategy: TrimStrategy,
+    limit: Option<usize>,
+}
+
+/// Parses `<MAXLEN|MINID> [=|~] threshold [LIMIT count]` starting at the strategy name,
+/// returning the options and the number of consumed arguments.
+fn parse_trim_options(args: &[Bytes]) -> Result<(TrimOptions, usize), CommandExecutionError> {
+    let [strategy, rest @ ..] = args else {
+        return Err(CommandExecutionError::SyntaxError);
+    };
+
+    let (is_approximate, rest, mut consumed) = match rest {
+        [modifier, rest @ ..] if modifier.as_ref() == b"~" => (true, rest, 2),
+        [modifier, rest @ ..] if modifier.as_ref() == b"=" => (false, rest, 2),
+        rest => (false, rest, 1),
+    };
+
+    let Some(threshold) = rest.first() else {
+        return Err(CommandExecutionError::SyntaxError);
+    };
+
+    let strategy = match strategy.to_ascii_lowercase().as_slice() {
+        b"maxlen" => match parse_int(threshold)? {
+            max_len if max_len < 0 => return Err(CommandExecutionError::NegativeMaxLen),
+            max_len => TrimStrategy::MaxLen(max_len as usize),
+        },
+        b"minid" => TrimStrategy::MinId(StreamId::parse(threshold, 0)?),
+        _ => return Err(CommandExecutionError::SyntaxError),
+    };
+
+    consumed += 1;
+
+    let limit = match &rest[1..] {
+        [limit, count, ..] if limit.eq_ignore_ascii_case(b"limit") => {
+            if !is_approximate {
+                return Err(CommandExecutionError::LimitWithoutApproximation);
+            }
+
+            consumed += 2;
+
+            Some(parse_count(count)?)
+        }
+        _ => None,
+    };
+
+    Ok((TrimOptions { strategy, limit }, consumed))
+}
+
+pub(crate) fn entry_reply(id: &StreamId, entry: &StreamEntry) -> RespType {
+    RespType::Array(Some(vec![
+        RespType::bulk_string(id.to_string()),
+        RespType::Array(Some(
+            entry
+                .iter()
+                .flat_map(|(field, value)| {
+                    [RespType::bulk_string(field), RespType::bulk_string(value)]
+                })
+                .collect(),
+        )),
+    ]))
+}
+
+pub(crate) async fn xadd(key: &Bytes, args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let mut make_stream = true;
+    let mut trim_options = None;
+    let mut idx = 0;
+
+    while let Some(arg) = args.get(idx) {
+        match arg.to_ascii_lowercase().as_slice() {
+            b"nomkstream" => {
+                make_stream = false;
+                idx += 1;
+            }
+            b"maxlen" | b"minid" => {
+                let (options, consumed) = parse_trim_options(&args[idx..])?;
+
+                trim_options = Some(options);
+                idx += consumed;
+            }
+            _ => break,
+        }
+    }
+
+    let [id, fields @ ..] = &args[idx..] else {
+        return Err(CommandExecutionError::WrongNumberOfArguments(
+            "xadd".to_string(),
+        ));
+    };
+
+    if fields.is_empty() || fields.len() % 2 != 0 {
+        return Err(CommandExecutionError::WrongNumberOfArguments(
+            "xadd".to_string(),
+        ));
+    }
+
+    let id = NewStreamId::parse(id)?;
+    let entry: StreamEntry = fields
+        .chunks_exact(2)
+        .map(|pair| (pair[0].clone(), pair[1].clone()))
+        .collect();
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let exists = match map_write.get(key) {
+        Some(record) => {
+            record.data().as_stream()?;
+
+            true
+        }
+        None => false,
+    };
+
+    if !exists && !make_stream {
+        return Ok(RespType::BulkString(None));
+    }
+
+    let stream = map_write
+        .get_or_insert_with(key, || Object::Stream(Stream::default()))
+        .data_mut()
+        .as_stream_mut()?;
+
+    let added = stream.add(id, entry);
+
+    if let (Ok(_), Some(options)) = (&added, trim_options) {
+        Db::mark_dirty(stream.trim(options.strategy, options.limit));
+    }
+
+    match added {
+        Ok(id) => {
+            Db::mark_dirty(1);
+            serve_blocked_clients(&mut map_write, key);
+
+            Ok(RespType::bulk_string(id.to_string()))
+        }
+        Err(e) => {
+            if !exists {
+                map_write.remove(key);
+            }
+
+            Err(e)
+        }
+    }
+}
+
+pub(crate) async fn xlen(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let len = match map_read.get(key) {
+        Some(record) => record.data().as_stream()?.len(),
+        None => 0,
+    };
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+pub(crate) async fn xtrim(key: &Bytes, args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let (options, consumed) = parse_trim_options(args)?;
+
+    if consumed != args.len() {
+        return Err(CommandExecutionError::SyntaxError);
+    }
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let evicted = record
+        .data_mut()
+        .as_stream_mut()?
+        .trim(options.strategy, options.limit);
+
+    Db::mark_dirty(evicted);
+
+    Ok(RespType::Integer(Some(evicted as i64)))
+}
+
+pub(crate) async fn xdel(key: &Bytes, ids: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let ids = ids
+        .iter()
+        .map(|id| StreamId::parse(id, 0))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(record) = map_write.get_mut(key) else {
+        return Ok(RespType::Integer(Some(0)));
+    };
+
+    let stream = record.data_mut().as_stream_mut()?;
+    let deleted = ids.iter().filter(|id| stream.delete(id)).count();
+
+    Db::mark_dirty(deleted);
+
+    Ok(RespType::Integer(Some(deleted as i64)))
+}
+
+/// Parses an `XRANGE` interval bound: `-`, `+`, an id or an id prefixed by `(` to exclude it.
+/// Incomplete ids default to the lowest sequence for a start and to the highest for an end.
+pub(crate) fn parse_range_bound(
+    arg: &Bytes,
+    is_start: bool,
+) -> Result<Bound<StreamId>, CommandExecutionError> {
+    let missing_seq = if is_start { 0 } else { u64::MAX };
+
+    match arg.as_ref() {
+        b"-" => Ok(Bound::Included(StreamId::MIN)),
+        b"+" => Ok(Bound::Included(StreamId::MAX)),
+        [b'(', id @ ..] => Ok(Bound::Excluded(StreamId::parse(id, missing_seq)?)),
+        id => Ok(Bound::Included(StreamId::parse(id, missing_seq)?)),
+    }
+}
+
+fn parse_range_count(args: &[Bytes]) -> Result<Option<usize>, CommandExecutionError> {
+    match args {
+        [] => Ok(None),
+        [option, count] if option.eq_ignore_ascii_case(b"count") => {
+            Ok(Some(parse_int(count)?.max(0) as usize))
+        }
+        _ => Err(CommandExecutionError::SyntaxError),
+    }
+}
+
+pub(crate) async fn xrange(
+    key: &Bytes,
+    start: &Bytes,
+    end: &Bytes,
+    args: &[Bytes],
+    is_reversed: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let (start, end) = (
+        parse_range_bound(start, true)?,
+        parse_range_bound(end, false)?,
+    );
+    let count = parse_range_count(args)?.unwrap_or(usize::MAX);
+
+    let map_read = HASH_MAP.read().await;
+
+    let Some(record) = map_read.get(key) else {
+        return Ok(RespType::Array(Some(vec![])));
+    };
+
+    let range = record.data().as_stream()?.range(start, end);
+
+    let entries = if is_reversed {
+        range
+            .rev()
+            .take(count)
+            .map(|(id, entry)| entry_reply(id, entry))
+            .collect()
+    } else {
+        range
+            .take(count)
+            .map(|(id, entry)| entry_reply(id, entry))
+            .collect()
+    };
+
+    Ok(RespType::Array(Some(entries)))
+}
+
+/// Entries added after `id` to the stream stored at the key, as replied by `XREAD`.
+pub(crate) fn read_stream(
+    db: &Db,
+    key: &Bytes,
+    id: &StreamId,
+    count: Option<usize>,
+) -> Result<Option<RespType>, CommandExecutionError> {
+    let Some(record) = db.get(key) else {
+        return Ok(None);
+    };
+
+    let entries: Vec<RespType> = record
+        .data()
+        .as_stream()?
+        .range(Bound::Excluded(*id), Bound::Unbounded)
+        .take(count.unwrap_or(usize::MAX))
+        .map(|(id, entry)| entry_reply(id, entry))
+        .collect();
+
+    if entries.is_empty() {
+        return Ok(None);
+    }
+
+    Ok(Some(RespType::Array(Some(vec![
+        RespType::bulk_string(key),
+        RespType::Array(Some(entries)),
+    ]))))
+}
+
+pub(crate) async fn xread(args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let mut count = None;
+    let mut block = None;
+    let mut idx = 0;
+
+    let streams = loop {
+        match (
+            args.get(idx).map(|arg| arg.to_ascii_lowercase()).as_deref(),
+            args.get(idx + 1),
+        ) {
+            (Some(b"count"), Some(value)) => {
+                count = Some(parse_int(value)?)
+                    .filter(|count| *count > 0)
+                    .map(|count| count as usize)
+            }
+            (Some(b"block"), Some(value)) => match parse_int(value) {
+                Ok(timeout) if timeout < 0 => return Err(CommandExecutionError::NegativeTimeout),
+                Ok(timeout) => block = Some(timeout as u64),
+                Err(_) => return Err(CommandExecutionError::InvalidIntegerTimeout),
+            },
+            (Some(b"streams"), Some(_)) => break &args[idx + 1..],
+            _ => return Err(CommandExecutionError::SyntaxError),
+        }
+
+        idx += 2;
+    };
+
+    if streams.len() % 2 != 0 {
+        return Err(CommandExecutionError::UnbalancedStreams(
+            "xread".to_string(),
+        ));
+    }
+
+    let (keys, ids) = streams.split_at(streams.len() / 2);
+
+    let (id, receiver) = {
+        let map_write = HASH_MAP.write().await;
+        let mut resolved_ids = Vec::with_capacity(keys.len());
+        let mut replies = vec![];
+
+        for (key, id) in keys.iter().zip(ids) {
+            let stream = match map_write.get(key) {
+                Some(record) => Some(record.data().as_stream()?),
+                None => None,
+            };
+
+            let id = match id.as_ref() {
+                b"$" => stream.map(|stream| stream.last_id).unwrap_or_default(),
+                // `+` stands for the last entry, so read right before it
+                b"+" => stream
+                    .and_then(|stream| stream.entries.keys().next_back())
+                    .and_then(StreamId::prev)
+                    .unwrap_or(StreamId::MAX),
+                b">" => return Err(CommandExecutionError::NewIdOutsideGroup),
+                id => StreamId::parse(id, 0)?,
+            };
+
+            if let Some(reply) = read_stream(&map_write, key, &id, count)? {
+                replies.push(reply);
+            }
+
+            resolved_ids.push((key.clone(), id));
+        }
+
+        if !replies.is_empty() {
+            return Ok(RespType::Array(Some(replies)));
+        }
+
+        if block.is_none() {
+            return Ok(RespType::Array(None));
+        }
+
+        BLOCKED_CLIENTS
+            .lock()
+            .expect("blocked clients lock is poisoned")
+            .block(
+                map_write.index(),
+                keys,
+                BlockedOp::ReadStreams {
+                    ids: resolved_ids,
+                    count,
+                },
+            )
+    };
+
+    let timeout = block.filter(|block| *block > 0).map(Duration::from_millis);
+
+    Ok(wait_until_served(id, receiver, timeout, RespType::Array(None)).await)
+}
@@ -0,0 +1,294 @@
+use bytes::Bytes;
+
+use crate::{
+    commands::{
+        errors::CommandExecutionError,
+        hash_map::{Db, HASH_MAP, Value, object::Object},
+        utils::{format_float, parse_expire_at, parse_float, parse_int, unix_time_ms},
+    },
+    resp::types::RespType,
+};
+
+/// Strings, and so the values built by `APPEND` and `SETRANGE`, are capped to 512MB.
+pub(crate) const MAX_STRING_LEN: usize = 512 * 1024 * 1024;
+
+fn get_string<'a>(db: &'a Db, key: &Bytes) -> Result<Option<&'a Bytes>, CommandExecutionError> {
+    db.get(key)
+        .map(|record| record.data().as_string())
+        .transpose()
+}
+
+/// `INCR` and `DECR`, which are `INCRBY` and `DECRBY` by one.
+pub(crate) async fn incr(key: &Bytes, increment: i64) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let current = match get_string(&map_write, key)? {
+        Some(value) => parse_int(value)?,
+        None => 0,
+    };
+
+    let value = current
+        .checked_add(increment)
+        .ok_or(CommandExecutionError::IncrementOverflow)?;
+
+    map_write.set_data(key, Object::String(Bytes::from(value.to_string())));
+
+    Ok(RespType::Integer(Some(value)))
+}
+
+pub(crate) async fn incrby(
+    key: &Bytes,
+    increment: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    incr(key, parse_int(increment)?).await
+}
+
+/// `DECRBY` cannot negate the smallest integer, which redis reports on its own.
+pub(crate) async fn decrby(
+    key: &Bytes,
+    decrement: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let decrement = parse_int(decrement)?
+        .checked_neg()
+        .ok_or(CommandExecutionError::DecrementOverflow)?;
+
+    incr(key, decrement).await
+}
+
+pub(crate) async fn incrbyfloat(
+    key: &Bytes,
+    increment: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let increment = parse_float(increment)?;
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let current = match get_string(&map_write, key)? {
+        Some(value) => parse_float(value)?,
+        None => 0.0,
+    };
+
+    let value = current + increment;
+
+    if !value.is_finite() {
+        return Err(CommandExecutionError::NanOrInfinity);
+    }
+
+    let value = Bytes::from(format_float(value));
+
+    map_write.set_data(key, Object::String(value.clone()));
+
+    Ok(RespType::bulk_string(&value))
+}
+
+pub(crate) async fn append(key: &Bytes, value: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let mut string = match get_string(&map_write, key)? {
+        Some(current) => {
+            if current.len() + value.len() > MAX_STRING_LEN {
+                return Err(CommandExecutionError::StringTooLong);
+            }
+
+            current.to_vec()
+        }
+        None => vec![],
+    };
+
+    string.extend_from_slice(value);
+
+    let len = string.len();
+
+    map_write.set_data(key, Object::String(Bytes::from(string)));
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+pub(crate) async fn strlen(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let len = get_string(&map_read, key)?
+        .map(Bytes::len)
+        .unwrap_or_default();
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+/// Unlike list ranges, a negative `end` before the start of the string is clamped
+/// to the first byte rather than making the range empty.
+pub(crate) async fn getrange(
+    key: &Bytes,
+    start: &Bytes,
+    end: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let (start, end) = (parse_int(start)?, parse_int(end)?);
+
+    let map_read = HASH_MAP.read().await;
+
+    let string = get_string(&map_read, key)?.cloned().unwrap_or_default();
+    let len = string.len() as i64;
+
+    if (start < 0 && end < 0 && start > end) || len == 0 {
+        return Ok(RespType::bulk_string(""));
+    }
+
+    let normalize = |index: i64| {
+        if index < 0 {
+            (len + index).max(0)
+        } else {
+            index
+        }
+    };
+    let (start, end) = (normalize(start), normalize(end).min(len - 1));
+
+    if start > end {
+        return Ok(RespType::bulk_string(""));
+    }
+
+    Ok(RespType::bulk_string(
+        string.slice(start as usize..=end as usize),
+    ))
+}
+
+pub(crate) async fn setrange(
+    key: &Bytes,
+    offset: &Bytes,
+    value: &Bytes,
+) -> Result<RespType, CommandExecutionError> {
+    let offset = parse_int(offset)?;
+
+    if offset < 0 {
+        return Err(CommandExecutionError::OffsetOutOfRange);
+    }
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let current = get_string(&map_write, key)?;
+
+    // an empty value neither creates the key nor pads the string
+    if value.is_empty() {
+        let len = current.map(Bytes::len).unwrap_or_default();
+
+        return Ok(RespType::Integer(Some(len as i64)));
+    }
+
+    let offset = offset as usize;
+
+    if offset + value.len() > MAX_STRING_LEN {
+        return Err(CommandExecutionError::StringTooLong);
+    }
+
+    let mut string = current.map(|current| current.to_vec()).unwrap_or_default();
+
+    if string.len() < offset + value.len() {
+        string.resize(offset + value.len(), 0);
+    }
+
+    string[offset..offset + value.len()].copy_from_slice(value);
+
+    let len = string.len();
+
+    map_write.set_data(key, Object::String(Bytes::from(string)));
+
+    Ok(RespType::Integer(Some(len as i64)))
+}
+
+/// Values of the keys holding anything but a string are reported as missing.
+pub(crate) async fn mget(keys: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let map_read = HASH_MAP.read().await;
+
+    let values = keys
+        .iter()
+        .map(|key| match map_read.get(key).map(Value::data) {
+            Some(Object::String(value)) => RespType::bulk_string(value),
+            _ => RespType::BulkString(None),
+        })
+        .collect();
+
+    Ok(RespType::Array(Some(values)))
+}
+
+/// `MSET` and, when `only_new` is set, `MSETNX` which sets nothing if any of the keys exists.
+pub(crate) async fn mset(
+    pairs: &[Bytes],
+    only_new: bool,
+) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    if only_new
+        && pairs
+            .chunks_exact(2)
+            .any(|pair| map_write.get(&pair[0]).is_some())
+    {
+        return Ok(RespType::Integer(Some(0)));
+    }
+
+    for pair in pairs.chunks_exact(2) {
+        map_write.insert(
+            pair[0].clone(),
+            Value::new(Object::String(pair[1].clone()), None),
+        );
+    }
+
+    if only_new {
+        return Ok(RespType::Integer(Some(1)));
+    }
+
+    Ok(RespType::simple_string("OK"))
+}
+
+/// Sets the key, discarding its time to live, and replies with the previous value.
+pub(crate) async fn getset(key: &Bytes, value: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let previous = get_string(&map_write, key)?.cloned();
+
+    map_write.insert(key.clone(), Value::new(Object::String(value.clone()), None));
+
+    Ok(previous.map_or(RespType::BulkString(None), RespType::bulk_string))
+}
+
+pub(crate) async fn getdel(key: &Bytes) -> Result<RespType, CommandExecutionError> {
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(value) = get_string(&map_write, key)?.cloned() else {
+        return Ok(RespType::BulkString(None));
+    };
+
+    map_write.remove(key);
+
+    Ok(RespType::bulk_string(value))
+}
+
+/// `GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds |
+/// PXAT unix-time-milliseconds | PERSIST]`.
+pub(crate) async fn getex(key: &Bytes, args: &[Bytes]) -> Result<RespType, CommandExecutionError> {
+    let now = unix_time_ms();
+
+    // `None` leaves the time to live untouched, `Some(None)` persists the key
+    let expire_at = match args {
+        [] => None,
+        [option] if option.eq_ignore_ascii_case(b"persist") => Some(None),
+        [option, time] => Some(Some(parse_expire_at("getex", option, time, now)?)),
+        _ => return Err(CommandExecutionError::SyntaxError),
+    };
+
+    let mut map_write = HASH_MAP.write().await;
+
+    let Some(value) = get_string(&map_write, key)?.cloned() else {
+        return Ok(RespType::BulkString(None));
+    };
+
+    match expire_at {
+        None => {}
+        // a deadline in the past deletes the key right away
+        Some(Some(at)) if at <= now => {
+            map_write.remove(key);
+        }
+        Some(at) => {
+            map_write.set_expires_at(key, at);
+        }
+    }
+
+    Ok(RespType::bulk_string(value))
+}
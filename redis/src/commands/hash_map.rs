@@ -1,44 +1,487 @@
 use std::{
-    collections::HashMap,
-    sync::LazyLock,
-    time::{Duration, Instant},
+    collections::{BTreeSet, HashMap},
+    hash::{DefaultHasher, Hash as _, Hasher},
+    ops::Bound,
+    sync::{
+        LazyLock,
+        atomic::{AtomicU8, AtomicU64, Ordering},
+    },
 };
 
-use tokio::sync::RwLock;
+use bytes::Bytes;
+use rand::Rng;
+use tokio::sync::{RwLock, RwLockMappedWriteGuard, RwLockReadGuard, RwLockWriteGuard};
 
-use crate::resp::types::RespType;
+use crate::{
+    commands::{active_expire::STATS, hash_map::object::Object, utils::unix_time_ms},
+    rdb::snapshot::SAVE_STATE,
+};
+
+pub(crate) mod hash;
+pub(crate) mod hyperloglog;
+pub(crate) mod object;
+pub(crate) mod sorted_set;
+pub(crate) mod stream;
+
+pub type Key = Bytes;
+
+/// Position of the key in the order `SCAN` visits the keyspace, which only depends on the key
+/// itself so that keys added or removed meanwhile do not move the others around.
+pub fn scan_hash(key: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    key.hash(&mut hasher);
 
-pub type Key = RespType;
+    hasher.finish()
+}
+
+/// Access frequency of a fresh value, so that it is not the first one to look unused.
+const INITIAL_FREQUENCY: u8 = 5;
+/// How hard it gets to increase the access frequency as it grows, like the default
+/// `lfu-log-factor` of redis.
+const FREQUENCY_LOG_FACTOR: f64 = 10.0;
+/// Idle time after which the access frequency decreases by one, like the default
+/// `lfu-decay-time` of redis.
+const FREQUENCY_DECAY_PERIOD_MS: u64 = 60_000;
 
-#[derive(PartialEq, Eq, PartialOrd, Ord)]
 pub struct Value {
-    data: RespType,
-    ttl: Option<Duration>,
-    created_at: Instant,
+    data: Object,
+    /// Deadline in unix milliseconds, absolute so that it survives persistence as is.
+    expires_at: Option<u64>,
+    /// Last access in unix milliseconds. Atomic as reads only hold a shared lock.
+    accessed_at: AtomicU64,
+    /// Logarithmic counter of the accesses, decaying while the value is not accessed.
+    frequency: AtomicU8,
 }
 
 impl Value {
-    pub fn new(data: RespType, ttl: Option<Duration>) -> Self {
+    pub fn new(data: Object, expires_at: Option<u64>) -> Self {
         Self {
             data,
-            ttl,
-            created_at: Instant::now(),
+            expires_at,
+            accessed_at: AtomicU64::new(unix_time_ms()),
+            frequency: AtomicU8::new(INITIAL_FREQUENCY),
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| at <= unix_time_ms())
+            || matches!(&self.data, Object::Hash(hash) if hash.is_expired())
+    }
+
+    pub fn expires_at(&self) -> Option<u64> {
+        self.expires_at
+    }
+
+    pub fn data(&self) -> &Object {
+        &self.data
+    }
+
+    pub fn data_mut(&mut self) -> &mut Object {
+        &mut self.data
+    }
+
+    /// Milliseconds since the value was last accessed.
+    pub fn idle_time(&self) -> u64 {
+        unix_time_ms().saturating_sub(self.accessed_at.load(Ordering::Relaxed))
+    }
+
+    /// Access frequency, as a logarithmic counter from 0 to 255.
+    pub fn frequency(&self) -> u8 {
+        let periods = self.idle_time() / FREQUENCY_DECAY_PERIOD_MS;
+
+        self.frequency
+            .load(Ordering::Relaxed)
+            .saturating_sub(periods.min(u8::MAX as u64) as u8)
+    }
+
+    /// Records an access, the frequency growing less likely the more it was accessed already.
+    pub fn touch(&self) {
+        let frequency = self.frequency();
+        let base = frequency.saturating_sub(INITIAL_FREQUENCY) as f64;
+
+        let frequency = if frequency < u8::MAX
+            && rand::rng().random::<f64>() < 1.0 / (base * FREQUENCY_LOG_FACTOR + 1.0)
+        {
+            frequency + 1
+        } else {
+            frequency
+        };
+
+        self.frequency.store(frequency, Ordering::Relaxed);
+        self.accessed_at.store(unix_time_ms(), Ordering::Relaxed);
+    }
+}
+
+/// Number of databases unless configured otherwise, like redis.
+pub const DEFAULT_DATABASES: usize = 16;
+
+/// Keyspace of binary safe keys, expired entries are never handed out
+/// even though they might still be stored until removed.
+pub struct Db {
+    /// Position among the databases, which `SELECT` refers to.
+    index: usize,
+    entries: HashMap<Key, Value>,
+    /// Every stored key along with its `scan_hash`, in the order `SCAN` visits them.
+    scan_index: BTreeSet<(u64, Key)>,
+    /// Keys with a deadline, sampled by the active expire cycle. Might still list keys that
+    /// no longer exist or have a deadline.
+    volatile_keys: BTreeSet<Key>,
+    /// Keys of the hashes with fields that have their own deadline, visited by the active
+    /// expire cycle. Might still list keys that no longer hold such a hash.
+    volatile_hashes: BTreeSet<Key>,
+}
+
+impl Db {
+    pub fn new(index: usize) -> Self {
+        Self {
+            index,
+            entries: HashMap::new(),
+            scan_index: BTreeSet::new(),
+            volatile_keys: BTreeSet::new(),
+            volatile_hashes: BTreeSet::new(),
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Number of stored keys, including those expired but not removed yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Counts changes to the dataset, which decide when it is saved again. Writes through
+    /// `get_mut` and `get_or_insert_with` are counted by their callers, which know whether
+    /// anything changed.
+    pub fn mark_dirty(changes: usize) {
+        SAVE_STATE
+            .dirty
+            .fetch_add(changes as u64, Ordering::Relaxed);
+    }
+
+    /// Empties the database, handing out what it held.
+    pub fn take(&mut self) -> Db {
+        Self::mark_dirty(self.len());
+
+        std::mem::replace(self, Db::new(self.index))
+    }
+
+    /// Exchanges the keys of two databases, each keeping its index.
+    pub fn swap(&mut self, other: &mut Db) {
+        Self::mark_dirty(1);
+
+        std::mem::swap(self, other);
+        std::mem::swap(&mut self.index, &mut other.index);
+    }
+
+    /// Returns the live value of the key, counting as an access to it.
+    pub fn get(&self, key: &[u8]) -> Option<&Value> {
+        let value = self.peek(key)?;
+
+        value.touch();
+
+        Some(value)
+    }
+
+    /// Returns the live value of the key without counting as an access, for introspection.
+    pub fn peek(&self, key: &[u8]) -> Option<&Value> {
+        self.entries.get(key).filter(|value| !value.is_expired())
+    }
+
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut Value> {
+        self.remove_expired(key);
+
+        let value = self.entries.get_mut(key)?;
+
+        value.touch();
+
+        // expired fields are reclaimed whenever the hash is about to be written
+        if let Object::Hash(hash) = &mut value.data {
+            hash.remove_expired();
+        }
+
+        Some(value)
+    }
+
+    /// Returns the live value of the key, storing a fresh one built by `init` if there is none.
+    pub fn get_or_insert_with(&mut self, key: &Key, init: impl FnOnce() -> Object) -> &mut Value {
+        self.remove_expired(key);
+
+        if !self.entries.contains_key(key) {
+            self.scan_index.insert((scan_hash(key), key.clone()));
+        }
+
+        self.entries
+            .entry(key.clone())
+            .or_insert_with(|| Value::new(init(), None))
+    }
+
+    pub fn insert(&mut self, key: Key, value: Value) -> Option<Value> {
+        Self::mark_dirty(1);
+
+        if value.expires_at.is_some() {
+            self.volatile_keys.insert(key.clone());
+        }
+
+        if matches!(&value.data, Object::Hash(hash) if hash.has_volatile_fields()) {
+            self.volatile_hashes.insert(key.clone());
+        }
+
+        self.scan_index.insert((scan_hash(&key), key.clone()));
+
+        self.entries
+            .insert(key, value)
+            .filter(|value| !value.is_expired())
+    }
+
+    /// Replaces the data of the key while keeping its deadline, or stores it without one.
+    pub fn set_data(&mut self, key: &Key, data: Object) {
+        match self.get_mut(key) {
+            Some(value) => {
+                value.data = data;
+                Self::mark_dirty(1);
+            }
+            None => {
+                self.insert(key.clone(), Value::new(data, None));
+            }
         }
     }
 
-    pub fn get_data(&self) -> Option<RespType> {
-        match self.ttl {
-            Some(ref ttl) => {
-                if self.created_at.elapsed() > *ttl {
-                    None
-                } else {
-                    Some(self.data.clone())
+    /// Sets or, with `None`, removes the deadline of the key. Returns whether the key exists.
+    pub fn set_expires_at(&mut self, key: &Key, expires_at: Option<u64>) -> bool {
+        let Some(value) = self.get_mut(key) else {
+            return false;
+        };
+
+        value.expires_at = expires_at;
+        Self::mark_dirty(1);
+
+        if expires_at.is_some() {
+            self.volatile_keys.insert(key.clone());
+        }
+
+        true
+    }
+
+    pub fn remove(&mut self, key: &[u8]) -> Option<Value> {
+        let removed = self.remove_entry(key).filter(|value| !value.is_expired());
+
+        if removed.is_some() {
+            Self::mark_dirty(1);
+        }
+
+        removed
+    }
+
+    fn remove_entry(&mut self, key: &[u8]) -> Option<Value> {
+        let (key, value) = self.entries.remove_entry(key)?;
+
+        self.scan_index.remove(&(scan_hash(&key), key));
+
+        Some(value)
+    }
+
+    /// Whether the key is still stored but must not be visible anymore.
+    pub fn is_expired(&self, key: &[u8]) -> bool {
+        self.entries.get(key).is_some_and(Value::is_expired)
+    }
+
+    pub fn remove_expired(&mut self, key: &[u8]) -> bool {
+        if self.is_expired(key) {
+            self.remove_entry(key);
+
+            STATS.expired_keys.fetch_add(1, Ordering::Relaxed);
+
+            return true;
+        }
+
+        false
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Key> {
+        self.entries.keys()
+    }
+
+    /// Live keys along with their values, without counting as an access to them.
+    pub fn iter(&self) -> impl Iterator<Item = (&Key, &Value)> {
+        self.entries.iter().filter(|(_, value)| !value.is_expired())
+    }
+
+    /// Keys of the next `SCAN` call: about `count` of them from `cursor` on, keys sharing a
+    /// `scan_hash` never being split across calls. Returns them along with the cursor to resume
+    /// from, 0 once every key was visited. Might include keys that already expired.
+    pub fn scan(&self, cursor: u64, count: usize) -> (Vec<Key>, u64) {
+        let mut keys = vec![];
+        let mut last_hash = None;
+
+        for (hash, key) in self.scan_index.range((cursor, Key::new())..) {
+            if keys.len() >= count && last_hash != Some(*hash) {
+                return (keys, *hash);
+            }
+
+            keys.push(key.clone());
+            last_hash = Some(*hash);
+        }
+
+        (keys, 0)
+    }
+
+    /// A random live key, removing the expired ones it comes across.
+    pub fn random_key(&mut self) -> Option<Key> {
+        loop {
+            let hash = rand::rng().random::<u64>();
+
+            // keys are spread evenly over the hashes, the first one from a random hash on is
+            // about as likely as any other
+            let (_, key) = self
+                .scan_index
+                .range((hash, Key::new())..)
+                .next()
+                .or_else(|| self.scan_index.first())?
+                .clone();
+
+            if !self.remove_expired(&key) {
+                return Some(key);
+            }
+        }
+    }
+
+    /// Whether the active expire cycle has keys or hash fields with a deadline to visit.
+    pub fn has_volatile(&self) -> bool {
+        !self.volatile_keys.is_empty() || !self.volatile_hashes.is_empty()
+    }
+
+    /// Removes the expired keys among up to `limit` keys with a deadline tracked after `cursor`.
+    /// Returns how many of those still had a deadline and how many of them expired, along with
+    /// the cursor to resume from, `None` once all were visited.
+    pub fn expire_keys(
+        &mut self,
+        cursor: Option<&Key>,
+        limit: usize,
+    ) -> (usize, usize, Option<Key>) {
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(cursor),
+            None => Bound::Unbounded,
+        };
+
+        let keys: Vec<Key> = self
+            .volatile_keys
+            .range::<Key, _>((start, Bound::Unbounded))
+            .take(limit)
+            .cloned()
+            .collect();
+
+        let (mut sampled, mut expired) = (0, 0);
+
+        for key in &keys {
+            match self.entries.get(key).and_then(|value| value.expires_at) {
+                Some(_) => sampled += 1,
+                None => {
+                    self.volatile_keys.remove(key);
+
+                    continue;
                 }
             }
-            None => Some(self.data.clone()),
+
+            if self.remove_expired(key) {
+                self.volatile_keys.remove(key);
+
+                expired += 1;
+            }
+        }
+
+        if keys.len() < limit {
+            return (sampled, expired, None);
+        }
+
+        (sampled, expired, keys.into_iter().next_back())
+    }
+
+    /// Lets the active expire cycle know the hash stored at the key has fields with a deadline.
+    pub fn track_volatile_hash(&mut self, key: &Key) {
+        self.volatile_hashes.insert(key.clone());
+    }
+
+    /// Removes the expired fields of up to `limit` hashes tracked after `cursor`, removing the
+    /// hashes left empty. Returns the cursor to resume from, `None` once all were visited.
+    pub fn expire_hash_fields(&mut self, cursor: Option<&Key>, limit: usize) -> Option<Key> {
+        let start = match cursor {
+            Some(cursor) => Bound::Excluded(cursor),
+            None => Bound::Unbounded,
+        };
+
+        let keys: Vec<Key> = self
+            .volatile_hashes
+            .range::<Key, _>((start, Bound::Unbounded))
+            .take(limit)
+            .cloned()
+            .collect();
+
+        for key in &keys {
+            let Some(Object::Hash(hash)) = self.entries.get_mut(key).map(|value| &mut value.data)
+            else {
+                self.volatile_hashes.remove(key);
+
+                continue;
+            };
+
+            hash.remove_expired();
+
+            if !hash.has_volatile_fields() {
+                self.volatile_hashes.remove(key);
+            }
+
+            if hash.is_empty() {
+                self.remove_entry(key);
+            }
         }
+
+        if keys.len() < limit {
+            return None;
+        }
+
+        keys.into_iter().next_back()
+    }
+}
+
+tokio::task_local! {
+    /// Database selected by the connection whose command is running, see `Databases`.
+    pub(crate) static SELECTED_DB: usize;
+}
+
+/// The databases, all behind a single lock so that commands spanning several of them like
+/// `SWAPDB` or `MOVE` are atomic. Commands run within a `SELECTED_DB` scope and get the
+/// database selected by their connection from `read` and `write`.
+pub(crate) struct Databases(RwLock<Vec<Db>>);
+
+impl Databases {
+    pub fn selected() -> usize {
+        // the background tasks, e.g. the rdb loader, work on the first database by default
+        SELECTED_DB.try_with(|index| *index).unwrap_or_default()
+    }
+
+    pub async fn read(&self) -> RwLockReadGuard<'_, Db> {
+        let index = Self::selected();
+
+        RwLockReadGuard::map(self.0.read().await, |dbs| &dbs[index])
+    }
+
+    pub async fn write(&self) -> RwLockMappedWriteGuard<'_, Db> {
+        let index = Self::selected();
+
+        RwLockWriteGuard::map(self.0.write().await, |dbs| &mut dbs[index])
+    }
+
+    pub async fn read_all(&self) -> RwLockReadGuard<'_, Vec<Db>> {
+        self.0.read().await
+    }
+
+    pub async fn write_all(&self) -> RwLockWriteGuard<'_, Vec<Db>> {
+        self.0.write().await
     }
 }
 
-pub(crate) static HASH_MAP: LazyLock<RwLock<HashMap<Key, Value>>> =
-    LazyLock::new(|| RwLock::new(HashMap::new()));
+pub(crate) static HASH_MAP: LazyLock<Databases> =
+    LazyLock::new(|| Databases(RwLock::new((0..DEFAULT_DATABASES).map(Db::new).collect())));
@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+
+use bytes::Bytes;
+
+use crate::commands::utils::unix_time_ms;
+
+/// Hash whose fields can expire on their own. Expired fields are never handed out
+/// even though they might still be stored until removed.
+#[derive(Debug, Clone, Default)]
+pub struct Hash {
+    fields: HashMap<Bytes, Bytes>,
+    /// Absolute deadlines in unix milliseconds of the fields that have one.
+    expires: HashMap<Bytes, u64>,
+}
+
+impl Hash {
+    fn is_field_expired(&self, field: &[u8], now: u64) -> bool {
+        self.expires.get(field).is_some_and(|at| *at <= now)
+    }
+
+    pub fn get(&self, field: &[u8]) -> Option<&Bytes> {
+        if self.is_field_expired(field, unix_time_ms()) {
+            return None;
+        }
+
+        self.fields.get(field)
+    }
+
+    pub fn contains_key(&self, field: &[u8]) -> bool {
+        self.get(field).is_some()
+    }
+
+    /// Sets the field, dropping its deadline, and returns whether it is a new one.
+    pub fn insert(&mut self, field: Bytes, value: Bytes) -> bool {
+        let is_new = !self.contains_key(&field);
+
+        self.expires.remove(&field);
+        self.fields.insert(field, value);
+
+        is_new
+    }
+
+    /// Replaces the value of a field while keeping its deadline, like `HINCRBY` does.
+    pub fn update(&mut self, field: Bytes, value: Bytes) {
+        if self.is_field_expired(&field, unix_time_ms()) {
+            self.expires.remove(&field);
+        }
+
+        self.fields.insert(field, value);
+    }
+
+    /// Removes the field and returns whether it was there.
+    pub fn remove(&mut self, field: &[u8]) -> bool {
+        let existed = self.contains_key(field);
+
+        self.expires.remove(field);
+        self.fields.remove(field);
+
+        existed
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Bytes, &Bytes)> {
+        let now = unix_time_ms();
+
+        self.fields
+            .iter()
+            .filter(move |(field, _)| !self.is_field_expired(field, now))
+    }
+
+    pub fn len(&self) -> usize {
+        if self.expires.is_empty() {
+            return self.fields.len();
+        }
+
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Whether the hash only holds fields that have expired, which makes the whole key gone.
+    pub fn is_expired(&self) -> bool {
+        if self.fields.is_empty() || self.expires.len() < self.fields.len() {
+            return false;
+        }
+
+        let now = unix_time_ms();
+
+        self.expires.values().all(|at| *at <= now)
+    }
+
+    pub fn has_volatile_fields(&self) -> bool {
+        !self.expires.is_empty()
+    }
+
+    pub fn expire_at(&self, field: &[u8]) -> Option<u64> {
+        self.expires.get(field).copied()
+    }
+
+    /// Sets the deadline of an existing field, in unix milliseconds.
+    pub fn set_expire_at(&mut self, field: &Bytes, at: u64) {
+        if self.fields.contains_key(field) {
+            self.expires.insert(field.clone(), at);
+        }
+    }
+
+    /// Drops the deadline of the field and returns whether it had one.
+    pub fn persist(&mut self, field: &[u8]) -> bool {
+        self.expires.remove(field).is_some()
+    }
+
+    /// Removes the expired fields and returns how many there were.
+    pub fn remove_expired(&mut self) -> usize {
+        let now = unix_time_ms();
+        let expired: Vec<Bytes> = self
+            .expires
+            .iter()
+            .filter(|(_, at)| **at <= now)
+            .map(|(field, _)| field.clone())
+            .collect();
+
+        for field in &expired {
+            self.expires.remove(field);
+            self.fields.remove(field);
+        }
+
+        expired.len()
+    }
+}
@@ -0,0 +1,629 @@
+use crate::commands::errors::CommandExecutionError;
+
+/// Registers are addressed with the first 14 bits of the hash.
+const P: u32 = 14;
+pub const REGISTERS: usize = 1 << P;
+/// Bits of the hash left to count the run of zeros.
+const Q: u32 = 64 - P;
+const REGISTER_BITS: usize = 6;
+const REGISTER_MAX: u8 = (1 << REGISTER_BITS) - 1;
+
+const MAGIC: &[u8; 4] = b"HYLL";
+const HEADER_SIZE: usize = 16;
+const CARDINALITY: usize = 8;
+const DENSE: u8 = 0;
+const SPARSE: u8 = 1;
+const DENSE_SIZE: usize = HEADER_SIZE + (REGISTERS * REGISTER_BITS).div_ceil(8);
+
+/// Sparse values beyond this size are promoted to the dense encoding, redis'
+/// `hll-sparse-max-bytes` default.
+const SPARSE_MAX_BYTES: usize = 3000;
+const SPARSE_VAL_MAX_VALUE: u8 = 32;
+const SPARSE_VAL_MAX_LEN: usize = 4;
+const SPARSE_ZERO_MAX_LEN: usize = 64;
+const SPARSE_XZERO_MAX_LEN: usize = 16384;
+
+const ALPHA_INF: f64 = 0.721_347_520_444_481_7;
+
+/// Sparse opcodes, each covering a run of registers: `00xxxxxx` for up to 64 zeros,
+/// `01xxxxxx yyyyyyyy` for up to 16384 zeros and `1vvvvvxx` for up to 4 registers set to
+/// a value of up to 32.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Opcode {
+    Zero(usize),
+    XZero(usize),
+    Val(u8, usize),
+}
+
+impl Opcode {
+    /// Decodes the opcode starting the slice, `None` if it is cut short.
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        let op = *bytes.first()?;
+
+        Some(match op & 0xc0 {
+            0x00 => Opcode::Zero((op & 0x3f) as usize + 1),
+            0x40 => Opcode::XZero(((((op & 0x3f) as usize) << 8) | *bytes.get(1)? as usize) + 1),
+            _ => Opcode::Val(((op >> 2) & 0x1f) + 1, (op & 0x03) as usize + 1),
+        })
+    }
+
+    /// Zeros run, spread over the two opcode sizes as needed.
+    fn zeros(len: usize) -> Self {
+        if len > SPARSE_ZERO_MAX_LEN {
+            Opcode::XZero(len)
+        } else {
+            Opcode::Zero(len)
+        }
+    }
+
+    fn encode(self, out: &mut Vec<u8>) {
+        match self {
+            Opcode::Zero(len) => out.push((len - 1) as u8),
+            Opcode::XZero(len) => {
+                out.push(((len - 1) >> 8) as u8 | 0x40);
+                out.push(((len - 1) & 0xff) as u8);
+            }
+            Opcode::Val(value, len) => out.push(((value - 1) << 2 | (len - 1) as u8) | 0x80),
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Opcode::XZero(_) => 2,
+            _ => 1,
+        }
+    }
+
+    fn span(self) -> usize {
+        match self {
+            Opcode::Zero(len) | Opcode::XZero(len) | Opcode::Val(_, len) => len,
+        }
+    }
+}
+
+fn corrupted() -> CommandExecutionError {
+    CommandExecutionError::CorruptedHyperLogLog
+}
+
+/// MurmurHash64A, the hash redis feeds the registers with.
+fn murmur_hash64a(data: &[u8], seed: u64) -> u64 {
+    const M: u64 = 0xc6a4_a793_5bd1_e995;
+    const R: u32 = 47;
+
+    let mut h = seed ^ (data.len() as u64).wrapping_mul(M);
+    let mut chunks = data.chunks_exact(8);
+
+    for chunk in &mut chunks {
+        let mut k = u64::from_le_bytes(chunk.try_into().unwrap_or_default());
+
+        k = k.wrapping_mul(M);
+        k ^= k >> R;
+        k = k.wrapping_mul(M);
+
+        h ^= k;
+        h = h.wrapping_mul(M);
+    }
+
+    let tail = chunks.remainder();
+
+    if !tail.is_empty() {
+        for (i, byte) in tail.iter().enumerate() {
+            h ^= (*byte as u64) << (8 * i);
+        }
+
+        h = h.wrapping_mul(M);
+    }
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+
+    h
+}
+
+/// Register the element falls into and the length of the run of zeros that follows, plus one.
+fn register_and_count(element: &[u8]) -> (usize, u8) {
+    let hash = murmur_hash64a(element, 0xadc8_3b19);
+    let index = (hash & (REGISTERS as u64 - 1)) as usize;
+    // the extra bit makes sure the count is at most Q + 1
+    let hash = (hash >> P) | (1 << Q);
+
+    (index, hash.trailing_zeros() as u8 + 1)
+}
+
+fn dense_get(registers: &[u8], index: usize) -> u8 {
+    let (byte, shift) = (index * REGISTER_BITS / 8, index * REGISTER_BITS % 8);
+    let low = registers[byte] as u16 >> shift;
+    let high = (registers.get(byte + 1).copied().unwrap_or_default() as u16) << (8 - shift);
+
+    ((low | high) as u8) & REGISTER_MAX
+}
+
+fn dense_set(registers: &mut [u8], index: usize, value: u8) {
+    let (byte, shift) = (index * REGISTER_BITS / 8, index * REGISTER_BITS % 8);
+
+    registers[byte] &= !(REGISTER_MAX << shift);
+    registers[byte] |= value << shift;
+
+    // the last register fits in its byte
+    if let Some(next) = registers.get_mut(byte + 1) {
+        *next &= !((REGISTER_MAX as u16 >> (8 - shift)) as u8);
+        *next |= (value as u16 >> (8 - shift)) as u8;
+    }
+}
+
+/// `σ` of Ertl's estimator.
+fn sigma(mut x: f64) -> f64 {
+    if x == 1.0 {
+        return f64::INFINITY;
+    }
+
+    let (mut y, mut z) = (1.0, x);
+
+    loop {
+        x *= x;
+        let previous = z;
+        z += x * y;
+        y += y;
+
+        if previous == z {
+            return z;
+        }
+    }
+}
+
+/// `τ` of Ertl's estimator.
+fn tau(mut x: f64) -> f64 {
+    if x == 0.0 || x == 1.0 {
+        return 0.0;
+    }
+
+    let (mut y, mut z) = (1.0, 1.0 - x);
+
+    loop {
+        x = x.sqrt();
+        let previous = z;
+        y *= 0.5;
+        z -= (1.0 - x).powi(2) * y;
+
+        if previous == z {
+            return z / 3.0;
+        }
+    }
+}
+
+/// Cardinality out of how many registers hold each value, see "New cardinality estimation
+/// algorithms for HyperLogLog sketches" by Otmar Ertl.
+fn estimate(histogram: &[usize; 64]) -> u64 {
+    let m = REGISTERS as f64;
+    let mut z = m * tau((m - histogram[Q as usize + 1] as f64) / m);
+
+    for count in histogram[1..=Q as usize].iter().rev() {
+        z += *count as f64;
+        z *= 0.5;
+    }
+
+    z += m * sigma(histogram[0] as f64 / m);
+
+    (ALPHA_INF * m * m / z).round() as u64
+}
+
+/// Cardinality of raw registers, one byte each, e.g. the union computed by `PFCOUNT`.
+pub fn count_registers(registers: &[u8]) -> u64 {
+    let mut histogram = [0; 64];
+
+    for register in registers {
+        histogram[*register as usize] += 1;
+    }
+
+    estimate(&histogram)
+}
+
+/// HyperLogLog stored as a string, byte compatible with redis: a 16 bytes header holding
+/// `HYLL`, the encoding and the cached cardinality, then the registers either sparse or dense.
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    bytes: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    /// Sparse with every register set to zero.
+    fn default() -> Self {
+        let mut bytes = Vec::with_capacity(HEADER_SIZE + 2);
+
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&[SPARSE, 0, 0, 0]);
+        bytes.extend_from_slice(&[0; 8]);
+
+        let mut remaining = REGISTERS;
+
+        while remaining > 0 {
+            let len = remaining.min(SPARSE_XZERO_MAX_LEN);
+
+            Opcode::XZero(len).encode(&mut bytes);
+            remaining -= len;
+        }
+
+        Self { bytes }
+    }
+}
+
+impl HyperLogLog {
+    /// Checks the header of a string value, the registers are only checked when used.
+    pub fn parse(bytes: &[u8]) -> Result<Self, CommandExecutionError> {
+        let is_valid = bytes.len() >= HEADER_SIZE
+            && bytes.starts_with(MAGIC)
+            && match bytes[4] {
+                DENSE => bytes.len() == DENSE_SIZE,
+                SPARSE => true,
+                _ => false,
+            };
+
+        if !is_valid {
+            return Err(CommandExecutionError::NotAHyperLogLog);
+        }
+
+        Ok(Self {
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    fn is_sparse(&self) -> bool {
+        self.bytes[4] == SPARSE
+    }
+
+    pub fn is_dense(&self) -> bool {
+        !self.is_sparse()
+    }
+
+    fn registers(&self) -> &[u8] {
+        &self.bytes[HEADER_SIZE..]
+    }
+
+    fn registers_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes[HEADER_SIZE..]
+    }
+
+    pub fn invalidate_cache(&mut self) {
+        self.bytes[CARDINALITY + 7] |= 1 << 7;
+    }
+
+    /// Decodes the sparse opcodes, failing if they do not cover exactly every register.
+    fn opcodes(&self) -> Result<Vec<Opcode>, CommandExecutionError> {
+        let mut opcodes = vec![];
+        let (mut offset, mut covered) = (0, 0);
+        let registers = self.registers();
+
+        while offset < registers.len() {
+            let opcode = Opcode::decode(&registers[offset..]).ok_or_else(corrupted)?;
+
+            offset += opcode.size();
+            covered += opcode.span();
+            opcodes.push(opcode);
+        }
+
+        if covered != REGISTERS {
+            return Err(corrupted());
+        }
+
+        Ok(opcodes)
+    }
+
+    fn make_dense(&mut self) -> Result<(), CommandExecutionError> {
+        if !self.is_sparse() {
+            return Ok(());
+        }
+
+        let mut dense = vec![0; DENSE_SIZE];
+        let mut index = 0;
+
+        dense[..HEADER_SIZE].copy_from_slice(&self.bytes[..HEADER_SIZE]);
+        dense[4] = DENSE;
+
+        for opcode in self.opcodes()? {
+            if let Opcode::Val(value, len) = opcode {
+                for register in index..index + len {
+                    dense_set(&mut dense[HEADER_SIZE..], register, value);
+                }
+            }
+
+            index += opcode.span();
+        }
+
+        self.bytes = dense;
+
+        Ok(())
+    }
+
+    /// Raises the register to the count, returns whether it changed.
+    fn set(&mut self, index: usize, count: u8) -> Result<bool, CommandExecutionError> {
+        if !self.is_sparse() {
+            if dense_get(self.registers(), index) >= count {
+                return Ok(false);
+            }
+
+            dense_set(self.registers_mut(), index, count);
+
+            return Ok(true);
+        }
+
+        if count > SPARSE_VAL_MAX_VALUE {
+            return self.promote_and_set(index, count);
+        }
+
+        // locate the opcode covering the register
+        let end = self.bytes.len();
+        let (mut offset, mut first) = (HEADER_SIZE, 0);
+        let mut previous = None;
+
+        let opcode = loop {
+            let opcode = Opcode::decode(self.bytes.get(offset..end).unwrap_or_default())
+                .ok_or_else(corrupted)?;
+
+            if index < first + opcode.span() {
+                break opcode;
+            }
+
+            previous = Some(offset);
+            offset += opcode.size();
+            first += opcode.span();
+        };
+
+        let last = first + opcode.span() - 1;
+
+        match opcode {
+            Opcode::Val(value, _) if value >= count => return Ok(false),
+            // the register alone is covered, so the opcode is simply replaced
+            Opcode::Val(_, 1) | Opcode::Zero(1) => {
+                let mut replacement = vec![];
+
+                Opcode::Val(count, 1).encode(&mut replacement);
+                self.bytes[offset] = replacement[0];
+            }
+            _ => {
+                // the opcode is split around the register, at most XZERO-VAL-XZERO
+                let mut sequence = Vec::with_capacity(5);
+                let run = |len| match opcode {
+                    Opcode::Val(value, _) => Opcode::Val(value, len),
+                    _ => Opcode::zeros(len),
+                };
+
+                if index != first {
+                    run(index - first).encode(&mut sequence);
+                }
+
+                Opcode::Val(count, 1).encode(&mut sequence);
+
+                if index != last {
+                    run(last - index).encode(&mut sequence);
+                }
+
+                if sequence.len() > opcode.size()
+                    && self.bytes.len() + sequence.len() - opcode.size() > SPARSE_MAX_BYTES
+                {
+                    return self.promote_and_set(index, count);
+                }
+
+                self.bytes.splice(offset..offset + opcode.size(), sequence);
+            }
+        }
+
+        self.merge_adjacent_values(previous.unwrap_or(HEADER_SIZE));
+
+        Ok(true)
+    }
+
+    /// Merges adjacent VAL opcodes holding the same value, scanning a few opcodes from
+    /// the offset on as redis does after an update.
+    fn merge_adjacent_values(&mut self, mut offset: usize) {
+        let mut scan = 5;
+
+        while offset < self.bytes.len() && scan > 0 {
+            scan -= 1;
+
+            let Some(opcode) = Opcode::decode(&self.bytes[offset..]) else {
+                return;
+            };
+
+            let Opcode::Val(value, len) = opcode else {
+                offset += opcode.size();
+                continue;
+            };
+
+            if let Some(Opcode::Val(next_value, next_len)) =
+                Opcode::decode(self.bytes.get(offset + 1..).unwrap_or_default())
+                && next_value == value
+                && len + next_len <= SPARSE_VAL_MAX_LEN
+            {
+                let mut merged = vec![];
+
+                Opcode::Val(value, len + next_len).encode(&mut merged);
+                self.bytes[offset + 1] = merged[0];
+                self.bytes.remove(offset);
+
+                // the merged value might merge with the next one too
+                continue;
+            }
+
+            offset += 1;
+        }
+    }
+
+    fn promote_and_set(&mut self, index: usize, count: u8) -> Result<bool, CommandExecutionError> {
+        self.make_dense()?;
+        dense_set(self.registers_mut(), index, count);
+
+        Ok(true)
+    }
+
+    /// Adds the element, returns whether a register changed.
+    pub fn add(&mut self, element: &[u8]) -> Result<bool, CommandExecutionError> {
+        let (index, count) = register_and_count(element);
+
+        self.set(index, count)
+    }
+
+    /// Cardinality, served from the cache when valid and cached otherwise.
+    pub fn count(&mut self) -> Result<u64, CommandExecutionError> {
+        let cached: [u8; 8] = self.bytes[CARDINALITY..HEADER_SIZE]
+            .try_into()
+            .unwrap_or_default();
+
+        if cached[7] & (1 << 7) == 0 {
+            return Ok(u64::from_le_bytes(cached));
+        }
+
+        let mut histogram = [0; 64];
+
+        if self.is_sparse() {
+            for opcode in self.opcodes()? {
+                match opcode {
+                    Opcode::Zero(len) | Opcode::XZero(len) => histogram[0] += len,
+                    Opcode::Val(value, len) => histogram[value as usize] += len,
+                }
+            }
+        } else {
+            for index in 0..REGISTERS {
+                histogram[dense_get(self.registers(), index) as usize] += 1;
+            }
+        }
+
+        let cardinality = estimate(&histogram);
+
+        self.bytes[CARDINALITY..HEADER_SIZE].copy_from_slice(&cardinality.to_le_bytes());
+
+        Ok(cardinality)
+    }
+
+    /// Raises the raw registers, one byte each, to the registers of this value.
+    pub fn merge_into(&self, registers: &mut [u8]) -> Result<(), CommandExecutionError> {
+        if !self.is_sparse() {
+            for (index, register) in registers.iter_mut().enumerate() {
+                *register = (*register).max(dense_get(self.registers(), index));
+            }
+
+            return Ok(());
+        }
+
+        let mut index = 0;
+
+        for opcode in self.opcodes()? {
+            if let Opcode::Val(value, len) = opcode {
+                for register in &mut registers[index..index + len] {
+                    *register = (*register).max(value);
+                }
+            }
+
+            index += opcode.span();
+        }
+
+        Ok(())
+    }
+
+    /// Stores the raw registers, one byte each, the way `PFMERGE` does: densely if asked to,
+    /// otherwise by raising the registers one by one which keeps small values sparse.
+    pub fn store_registers(
+        &mut self,
+        registers: &[u8],
+        dense: bool,
+    ) -> Result<(), CommandExecutionError> {
+        if dense {
+            self.make_dense()?;
+
+            for (index, register) in registers.iter().enumerate() {
+                dense_set(self.registers_mut(), index, *register);
+            }
+        } else {
+            for (index, register) in registers.iter().enumerate() {
+                if *register != 0 {
+                    self.set(index, *register)?;
+                }
+            }
+        }
+
+        self.invalidate_cache();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_value_matches_redis() {
+        let mut hll = HyperLogLog::default();
+
+        // what `PFADD key` stores in redis
+        hll.invalidate_cache();
+
+        assert_eq!(
+            hll.clone().into_bytes(),
+            b"HYLL\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x80\x7f\xff"
+        );
+        assert_eq!(hll.count().unwrap(), 0);
+    }
+
+    #[test]
+    fn sparse_and_dense_agree() {
+        let mut sparse = HyperLogLog::default();
+
+        for i in 0..100 {
+            sparse.add(format!("element:{i}").as_bytes()).unwrap();
+        }
+
+        assert!(!sparse.is_dense());
+
+        let mut dense = sparse.clone();
+
+        dense.make_dense().unwrap();
+        sparse.invalidate_cache();
+        dense.invalidate_cache();
+
+        assert_eq!(sparse.count().unwrap(), dense.count().unwrap());
+        assert!((95..=105).contains(&sparse.count().unwrap()));
+    }
+
+    #[test]
+    fn promotes_to_dense_and_estimates_large_sets() {
+        let mut hll = HyperLogLog::default();
+
+        for i in 0..100_000 {
+            hll.add(format!("element:{i}").as_bytes()).unwrap();
+        }
+
+        hll.invalidate_cache();
+
+        assert!(hll.is_dense());
+        assert_eq!(hll.clone().into_bytes().len(), DENSE_SIZE);
+
+        let count = hll.count().unwrap() as f64;
+
+        assert!((count - 100_000.0).abs() / 100_000.0 < 0.02);
+
+        let mut registers = vec![0; REGISTERS];
+
+        hll.merge_into(&mut registers).unwrap();
+
+        assert_eq!(count_registers(&registers), count as u64);
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(HyperLogLog::parse(b"not a hll").is_err());
+        assert!(
+            HyperLogLog::parse(b"HYLL\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00").is_err()
+        );
+
+        let mut truncated =
+            HyperLogLog::parse(b"HYLL\x01\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x80\x7f")
+                .unwrap();
+
+        assert!(truncated.count().is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use std::{collections::VecDeque, fmt::Display};
+
+use bytes::Bytes;
+use indexmap::IndexSet;
+
+use crate::commands::{
+    errors::CommandExecutionError,
+    hash_map::{hash::Hash, sorted_set::SortedSet, stream::Stream},
+    utils::string_to_int,
+};
+
+// defaults of the redis `*-max-listpack-*` and `set-max-intset-entries` configs,
+// only used to report the same encodings redis would pick for a value
+const LIST_MAX_LISTPACK_BYTES: usize = 8 * 1024;
+const MAX_LISTPACK_ENTRIES: usize = 128;
+const MAX_LISTPACK_VALUE: usize = 64;
+const SET_MAX_INTSET_ENTRIES: usize = 512;
+const EMBSTR_MAX_LEN: usize = 44;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectType {
+    String,
+    List,
+    Hash,
+    Set,
+    ZSet,
+    Stream,
+}
+
+impl ObjectType {
+    /// Parses the name `TYPE` replies with, in any case.
+    pub fn parse(name: &[u8]) -> Option<Self> {
+        match name.to_ascii_lowercase().as_slice() {
+            b"string" => Some(ObjectType::String),
+            b"list" => Some(ObjectType::List),
+            b"hash" => Some(ObjectType::Hash),
+            b"set" => Some(ObjectType::Set),
+            b"zset" => Some(ObjectType::ZSet),
+            b"stream" => Some(ObjectType::Stream),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObjectType::String => write!(f, "string"),
+            ObjectType::List => write!(f, "list"),
+            ObjectType::Hash => write!(f, "hash"),
+            ObjectType::Set => write!(f, "set"),
+            ObjectType::ZSet => write!(f, "zset"),
+            ObjectType::Stream => write!(f, "stream"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Object {
+    String(Bytes),
+    List(VecDeque<Bytes>),
+    Hash(Hash),
+    Set(IndexSet<Bytes>),
+    ZSet(SortedSet),
+    Stream(Stream),
+}
+
+fn fits_listpack<'a>(len: usize, mut values: impl Iterator<Item = &'a Bytes>) -> bool {
+    len <= MAX_LISTPACK_ENTRIES && values.all(|v| v.len() <= MAX_LISTPACK_VALUE)
+}
+
+impl Object {
+    pub fn object_type(&self) -> ObjectType {
+        match self {
+            Object::String(_) => ObjectType::String,
+            Object::List(_) => ObjectType::List,
+            Object::Hash(_) => ObjectType::Hash,
+            Object::Set(_) => ObjectType::Set,
+            Object::ZSet(_) => ObjectType::ZSet,
+            Object::Stream(_) => ObjectType::Stream,
+        }
+    }
+
+    /// Name of the internal representation redis would use for the same value.
+    pub fn encoding(&self) -> &'static str {
+        match self {
+            Object::String(s) if s.len() <= 20 && string_to_int(s).is_some() => "int",
+            Object::String(s) if s.len() <= EMBSTR_MAX_LEN => "embstr",
+            Object::String(_) => "raw",
+            Object::List(list) => {
+                if list.iter().map(|v| v.len() + 2).sum::<usize>() <= LIST_MAX_LISTPACK_BYTES {
+                    "listpack"
+                } else {
+                    "quicklist"
+                }
+            }
+            Object::Hash(hash) => {
+                if !fits_listpack(hash.len(), hash.iter().flat_map(|(f, v)| [f, v])) {
+                    "hashtable"
+                } else if hash.has_volatile_fields() {
+                    "listpackex"
+                } else {
+                    "listpack"
+                }
+            }
+            Object::Set(set) => {
+                if set.len() <= SET_MAX_INTSET_ENTRIES
+                    && set.iter().all(|m| string_to_int(m).is_some())
+                {
+                    "intset"
+                } else if fits_listpack(set.len(), set.iter()) {
+                    "listpack"
+                } else {
+                    "hashtable"
+                }
+            }
+            Object::ZSet(zset) => {
+                if fits_listpack(zset.len(), zset.members()) {
+                    "listpack"
+                } else {
+                    "skiplist"
+                }
+            }
+            Object::Stream(_) => "stream",
+        }
+    }
+
+    pub fn as_string(&self) -> Result<&Bytes, CommandExecutionError> {
+        match self {
+            Object::String(s) => Ok(s),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&VecDeque<Bytes>, CommandExecutionError> {
+        match self {
+            Object::List(list) => Ok(list),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Result<&mut VecDeque<Bytes>, CommandExecutionError> {
+        match self {
+            Object::List(list) => Ok(list),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_hash(&self) -> Result<&Hash, CommandExecutionError> {
+        match self {
+            Object::Hash(hash) => Ok(hash),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_hash_mut(&mut self) -> Result<&mut Hash, CommandExecutionError> {
+        match self {
+            Object::Hash(hash) => Ok(hash),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_set(&self) -> Result<&IndexSet<Bytes>, CommandExecutionError> {
+        match self {
+            Object::Set(set) => Ok(set),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_set_mut(&mut self) -> Result<&mut IndexSet<Bytes>, CommandExecutionError> {
+        match self {
+            Object::Set(set) => Ok(set),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_zset(&self) -> Result<&SortedSet, CommandExecutionError> {
+        match self {
+            Object::ZSet(zset) => Ok(zset),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_zset_mut(&mut self) -> Result<&mut SortedSet, CommandExecutionError> {
+        match self {
+            Object::ZSet(zset) => Ok(zset),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_stream(&self) -> Result<&Stream, CommandExecutionError> {
+        match self {
+            Object::Stream(stream) => Ok(stream),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+
+    pub fn as_stream_mut(&mut self) -> Result<&mut Stream, CommandExecutionError> {
+        match self {
+            Object::Stream(stream) => Ok(stream),
+            _ => Err(CommandExecutionError::WrongType),
+        }
+    }
+}
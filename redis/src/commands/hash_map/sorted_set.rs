@@ -0,0 +1,651 @@
+use std::{cmp::Ordering, collections::HashMap};
+
+use bytes::Bytes;
+use rand::Rng;
+
+use crate::commands::{errors::CommandExecutionError, utils::parse_float};
+
+const MAX_LEVEL: usize = 32;
+const LEVEL_PROBABILITY: f64 = 0.25;
+const HEAD: usize = 0;
+
+#[derive(Debug, Clone, Default)]
+struct Level {
+    forward: Option<usize>,
+    /// Number of nodes the forward link jumps over, which is what makes ranks O(log n).
+    span: usize,
+}
+
+#[derive(Debug, Clone)]
+struct Node {
+    member: Bytes,
+    score: f64,
+    backward: Option<usize>,
+    levels: Vec<Level>,
+}
+
+impl Node {
+    fn cmp(&self, score: f64, member: &[u8]) -> Ordering {
+        self.score
+            .total_cmp(&score)
+            .then_with(|| self.member.as_ref().cmp(member))
+    }
+}
+
+/// Skiplist ordered by score then member, nodes live in an arena and link to each other
+/// by index, the head being a sentinel at index 0.
+#[derive(Debug, Clone)]
+struct SkipList {
+    nodes: Vec<Node>,
+    free: Vec<usize>,
+    tail: Option<usize>,
+    level: usize,
+    len: usize,
+}
+
+impl Default for SkipList {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node {
+                member: Bytes::new(),
+                score: 0.0,
+                backward: None,
+                levels: vec![Level::default(); MAX_LEVEL],
+            }],
+            free: vec![],
+            tail: None,
+            level: 1,
+            len: 0,
+        }
+    }
+}
+
+impl SkipList {
+    fn random_level() -> usize {
+        let mut rng = rand::rng();
+        let mut level = 1;
+
+        while level < MAX_LEVEL && rng.random_bool(LEVEL_PROBABILITY) {
+            level += 1;
+        }
+
+        level
+    }
+
+    fn forward(&self, node: usize, level: usize) -> Option<usize> {
+        self.nodes[node].levels[level].forward
+    }
+
+    /// Last node of every level that sorts before the score and member, with its rank.
+    fn predecessors(&self, score: f64, member: &[u8]) -> ([usize; MAX_LEVEL], [usize; MAX_LEVEL]) {
+        let mut update = [HEAD; MAX_LEVEL];
+        let mut rank = [0; MAX_LEVEL];
+        let mut x = HEAD;
+
+        for i in (0..self.level).rev() {
+            rank[i] = if i + 1 == self.level { 0 } else { rank[i + 1] };
+
+            while let Some(next) = self.forward(x, i)
+                && self.nodes[next].cmp(score, member) == Ordering::Less
+            {
+                rank[i] += self.nodes[x].levels[i].span;
+                x = next;
+            }
+
+            update[i] = x;
+        }
+
+        (update, rank)
+    }
+
+    fn alloc(&mut self, node: Node) -> usize {
+        match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = node;
+
+                idx
+            }
+            None => {
+                self.nodes.push(node);
+
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Inserts a member that is not in the list yet.
+    fn insert(&mut self, score: f64, member: Bytes) {
+        let (mut update, mut rank) = self.predecessors(score, &member);
+        let level = Self::random_level();
+
+        if level > self.level {
+            for i in self.level..level {
+                rank[i] = 0;
+                update[i] = HEAD;
+                self.nodes[HEAD].levels[i].span = self.len;
+            }
+
+            self.level = level;
+        }
+
+        let x = self.alloc(Node {
+            member,
+            score,
+            backward: (update[0] != HEAD).then_some(update[0]),
+            levels: vec![Level::default(); level],
+        });
+
+        for i in 0..level {
+            let previous = &self.nodes[update[i]].levels[i];
+            let (forward, span) = (previous.forward, previous.span);
+
+            self.nodes[x].levels[i] = Level {
+                forward,
+                span: span - (rank[0] - rank[i]),
+            };
+            self.nodes[update[i]].levels[i] = Level {
+                forward: Some(x),
+                span: rank[0] - rank[i] + 1,
+            };
+        }
+
+        for (i, previous) in update.iter().enumerate().take(self.level).skip(level) {
+            self.nodes[*previous].levels[i].span += 1;
+        }
+
+        match self.forward(x, 0) {
+            Some(next) => self.nodes[next].backward = Some(x),
+            None => self.tail = Some(x),
+        }
+
+        self.len += 1;
+    }
+
+    /// Removes the member stored with the score, returns whether it was there.
+    fn remove(&mut self, score: f64, member: &[u8]) -> bool {
+        let (update, _) = self.predecessors(score, member);
+
+        let Some(x) = self.forward(update[0], 0) else {
+            return false;
+        };
+
+        if self.nodes[x].cmp(score, member) != Ordering::Equal {
+            return false;
+        }
+
+        for (i, previous) in update.iter().enumerate().take(self.level) {
+            if self.forward(*previous, i) == Some(x) {
+                let removed = &self.nodes[x].levels[i];
+                let (forward, span) = (removed.forward, removed.span);
+                let level = &mut self.nodes[*previous].levels[i];
+
+                level.span = level.span + span - 1;
+                level.forward = forward;
+            } else {
+                self.nodes[*previous].levels[i].span -= 1;
+            }
+        }
+
+        match self.forward(x, 0) {
+            Some(next) => self.nodes[next].backward = self.nodes[x].backward,
+            None => self.tail = self.nodes[x].backward,
+        }
+
+        while self.level > 1 && self.forward(HEAD, self.level - 1).is_none() {
+            self.level -= 1;
+        }
+
+        self.nodes[x].member = Bytes::new();
+        self.nodes[x].levels = vec![];
+        self.free.push(x);
+        self.len -= 1;
+
+        true
+    }
+
+    /// 1-based rank of the member stored with the score.
+    fn rank(&self, score: f64, member: &[u8]) -> Option<usize> {
+        let mut rank = 0;
+        let mut x = HEAD;
+
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i)
+                && self.nodes[next].cmp(score, member) != Ordering::Greater
+            {
+                rank += self.nodes[x].levels[i].span;
+                x = next;
+            }
+
+            if x != HEAD && self.nodes[x].cmp(score, member) == Ordering::Equal {
+                return Some(rank);
+            }
+        }
+
+        None
+    }
+
+    /// Node at the 1-based rank.
+    fn by_rank(&self, rank: usize) -> Option<usize> {
+        let mut traversed = 0;
+        let mut x = HEAD;
+
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i)
+                && traversed + self.nodes[x].levels[i].span <= rank
+            {
+                traversed += self.nodes[x].levels[i].span;
+                x = next;
+            }
+
+            if traversed == rank {
+                return (x != HEAD).then_some(x);
+            }
+        }
+
+        None
+    }
+
+    /// First node for which `is_after_start` holds, the predicate being monotonic along the list.
+    fn first_where(&self, is_after_start: impl Fn(&Node) -> bool) -> Option<usize> {
+        let mut x = HEAD;
+
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i)
+                && !is_after_start(&self.nodes[next])
+            {
+                x = next;
+            }
+        }
+
+        self.forward(x, 0)
+    }
+
+    /// Last node for which `is_before_end` holds, the predicate being monotonic along the list.
+    fn last_where(&self, is_before_end: impl Fn(&Node) -> bool) -> Option<usize> {
+        let mut x = HEAD;
+
+        for i in (0..self.level).rev() {
+            while let Some(next) = self.forward(x, i)
+                && is_before_end(&self.nodes[next])
+            {
+                x = next;
+            }
+        }
+
+        (x != HEAD).then_some(x)
+    }
+}
+
+/// Score interval of `ZRANGE BYSCORE` and friends.
+#[derive(Debug, Clone, Copy)]
+pub struct ScoreRange {
+    pub min: f64,
+    pub max: f64,
+    pub min_exclusive: bool,
+    pub max_exclusive: bool,
+}
+
+impl ScoreRange {
+    /// Parses bounds like `1.5`, `(1.5`, `-inf` or `+inf`.
+    pub fn parse(min: &Bytes, max: &Bytes) -> Result<Self, CommandExecutionError> {
+        let parse_bound = |bound: &Bytes| match bound.strip_prefix(b"(") {
+            Some(value) => parse_float(&Bytes::copy_from_slice(value)).map(|value| (value, true)),
+            None => parse_float(bound).map(|value| (value, false)),
+        };
+
+        let ((min, min_exclusive), (max, max_exclusive)) = parse_bound(min)
+            .and_then(|min| Ok((min, parse_bound(max)?)))
+            .map_err(|_| CommandExecutionError::InvalidScoreRange)?;
+
+        Ok(Self {
+            min,
+            max,
+            min_exclusive,
+            max_exclusive,
+        })
+    }
+
+    fn gte_min(&self, score: f64) -> bool {
+        if self.min_exclusive {
+            score > self.min
+        } else {
+            score >= self.min
+        }
+    }
+
+    fn lte_max(&self, score: f64) -> bool {
+        if self.max_exclusive {
+            score < self.max
+        } else {
+            score <= self.max
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LexBound {
+    /// `-`
+    Min,
+    /// `+`
+    Max,
+    Inclusive(Bytes),
+    Exclusive(Bytes),
+}
+
+impl LexBound {
+    fn parse(bound: &Bytes) -> Result<Self, CommandExecutionError> {
+        match bound.as_ref() {
+            b"-" => Ok(LexBound::Min),
+            b"+" => Ok(LexBound::Max),
+            [b'[', value @ ..] => Ok(LexBound::Inclusive(Bytes::copy_from_slice(value))),
+            [b'(', value @ ..] => Ok(LexBound::Exclusive(Bytes::copy_from_slice(value))),
+            _ => Err(CommandExecutionError::InvalidLexRange),
+        }
+    }
+}
+
+/// Member interval of `ZRANGE BYLEX` and friends, only meaningful when all scores are equal.
+#[derive(Debug, Clone)]
+pub struct LexRange {
+    pub min: LexBound,
+    pub max: LexBound,
+}
+
+impl LexRange {
+    pub fn parse(min: &Bytes, max: &Bytes) -> Result<Self, CommandExecutionError> {
+        Ok(Self {
+            min: LexBound::parse(min)?,
+            max: LexBound::parse(max)?,
+        })
+    }
+
+    fn gte_min(&self, member: &[u8]) -> bool {
+        match &self.min {
+            LexBound::Min => true,
+            LexBound::Max => false,
+            LexBound::Inclusive(min) => member >= min.as_ref(),
+            LexBound::Exclusive(min) => member > min.as_ref(),
+        }
+    }
+
+    fn lte_max(&self, member: &[u8]) -> bool {
+        match &self.max {
+            LexBound::Min => false,
+            LexBound::Max => true,
+            LexBound::Inclusive(max) => member <= max.as_ref(),
+            LexBound::Exclusive(max) => member < max.as_ref(),
+        }
+    }
+}
+
+/// Iterates over the members from a node towards the tail, or the head when reversed.
+pub struct Iter<'a> {
+    list: &'a SkipList,
+    next: Option<usize>,
+    is_reversed: bool,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a Bytes, f64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = &self.list.nodes[self.next?];
+
+        self.next = if self.is_reversed {
+            node.backward
+        } else {
+            node.levels[0].forward
+        };
+
+        Some((&node.member, node.score))
+    }
+}
+
+/// Members ordered by score then member, with O(1) score lookups and O(log n) ranks.
+#[derive(Debug, Clone, Default)]
+pub struct SortedSet {
+    scores: HashMap<Bytes, f64>,
+    list: SkipList,
+}
+
+impl SortedSet {
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    pub fn members(&self) -> impl Iterator<Item = &Bytes> {
+        self.scores.keys()
+    }
+
+    pub fn score(&self, member: &[u8]) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    /// Sets the score of the member and returns whether it is a new one.
+    pub fn insert(&mut self, member: Bytes, score: f64) -> bool {
+        // -0 and 0 sort differently with a total order while being the same score
+        let score = if score == 0.0 { 0.0 } else { score };
+
+        match self.scores.insert(member.clone(), score) {
+            Some(previous) if previous == score => false,
+            Some(previous) => {
+                self.list.remove(previous, &member);
+                self.list.insert(score, member);
+
+                false
+            }
+            None => {
+                self.list.insert(score, member);
+
+                true
+            }
+        }
+    }
+
+    pub fn remove(&mut self, member: &[u8]) -> bool {
+        let Some(score) = self.scores.remove(member) else {
+            return false;
+        };
+
+        self.list.remove(score, member)
+    }
+
+    /// 0-based rank of the member, counted from the highest score when reversed.
+    pub fn rank(&self, member: &[u8], is_reversed: bool) -> Option<usize> {
+        let score = self.score(member)?;
+        let rank = self.list.rank(score, member)?;
+
+        Some(if is_reversed {
+            self.len() - rank
+        } else {
+            rank - 1
+        })
+    }
+
+    fn iter_from(&self, node: Option<usize>, is_reversed: bool) -> Iter<'_> {
+        Iter {
+            list: &self.list,
+            next: node,
+            is_reversed,
+        }
+    }
+
+    /// Members from the 0-based rank on, counted from the highest score when reversed.
+    pub fn iter_from_rank(&self, rank: usize, is_reversed: bool) -> Iter<'_> {
+        let node = if rank >= self.len() {
+            None
+        } else if is_reversed {
+            self.list.by_rank(self.len() - rank)
+        } else {
+            self.list.by_rank(rank + 1)
+        };
+
+        self.iter_from(node, is_reversed)
+    }
+
+    /// Members within the score range, from the lowest score or the highest when reversed.
+    pub fn iter_score_range(
+        &self,
+        range: ScoreRange,
+        is_reversed: bool,
+    ) -> impl Iterator<Item = (&Bytes, f64)> {
+        let start = if is_reversed {
+            self.list.last_where(|node| range.lte_max(node.score))
+        } else {
+            self.list.first_where(|node| range.gte_min(node.score))
+        };
+
+        self.iter_from(start, is_reversed)
+            .take_while(move |(_, score)| range.gte_min(*score) && range.lte_max(*score))
+    }
+
+    /// Members within the lex range, from the lowest or the highest when reversed.
+    pub fn iter_lex_range<'a>(
+        &'a self,
+        range: &'a LexRange,
+        is_reversed: bool,
+    ) -> impl Iterator<Item = (&'a Bytes, f64)> {
+        let start = if is_reversed {
+            self.list.last_where(|node| range.lte_max(&node.member))
+        } else {
+            self.list.first_where(|node| range.gte_min(&node.member))
+        };
+
+        self.iter_from(start, is_reversed)
+            .take_while(|(member, _)| range.gte_min(member) && range.lte_max(member))
+    }
+
+    /// Number of members within the score range, in O(log n).
+    pub fn count_in_score_range(&self, range: ScoreRange) -> usize {
+        let first = self.list.first_where(|node| range.gte_min(node.score));
+        let last = self.list.last_where(|node| range.lte_max(node.score));
+
+        let (Some(first), Some(last)) = (first, last) else {
+            return 0;
+        };
+
+        let rank_of = |node: usize| {
+            let node = &self.list.nodes[node];
+
+            self.list.rank(node.score, &node.member).unwrap_or_default()
+        };
+
+        (rank_of(last) + 1).saturating_sub(rank_of(first))
+    }
+
+    /// Removes the member with the lowest score, or the highest one when reversed.
+    pub fn pop(&mut self, is_reversed: bool) -> Option<(Bytes, f64)> {
+        let (member, score) = self
+            .iter_from_rank(0, is_reversed)
+            .next()
+            .map(|(member, score)| (member.clone(), score))?;
+
+        self.remove(&member);
+
+        Some((member, score))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(zset: &SortedSet, rank: usize, is_reversed: bool) -> Vec<(String, f64)> {
+        zset.iter_from_rank(rank, is_reversed)
+            .map(|(m, s)| (String::from_utf8_lossy(m).into_owned(), s))
+            .collect()
+    }
+
+    #[test]
+    fn orders_by_score_then_member() {
+        let mut zset = SortedSet::default();
+
+        for (member, score) in [("c", 1.0), ("a", 2.0), ("b", 1.0), ("d", -1.0)] {
+            assert!(zset.insert(Bytes::from(member), score));
+        }
+
+        assert!(!zset.insert(Bytes::from("a"), 0.5));
+
+        assert_eq!(
+            members(&zset, 0, false),
+            [("d", -1.0), ("a", 0.5), ("b", 1.0), ("c", 1.0)].map(|(m, s)| (m.to_string(), s))
+        );
+        assert_eq!(members(&zset, 1, true).len(), 3);
+        assert_eq!(members(&zset, 1, true)[0].0, "b");
+    }
+
+    #[test]
+    fn ranks_stay_consistent_through_updates() {
+        let mut zset = SortedSet::default();
+
+        for i in 0..500 {
+            zset.insert(Bytes::from(format!("m{i:03}")), ((i * 7919) % 500) as f64);
+        }
+
+        for i in (0..500).step_by(3) {
+            assert!(zset.remove(format!("m{i:03}").as_bytes()));
+        }
+
+        for i in (1..500).step_by(3) {
+            zset.insert(Bytes::from(format!("m{i:03}")), -(i as f64));
+        }
+
+        let ordered = members(&zset, 0, false);
+
+        assert_eq!(ordered.len(), zset.len());
+        assert!(ordered.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        for (rank, (member, _)) in ordered.iter().enumerate() {
+            assert_eq!(zset.rank(member.as_bytes(), false), Some(rank));
+            assert_eq!(
+                zset.rank(member.as_bytes(), true),
+                Some(zset.len() - 1 - rank)
+            );
+            assert_eq!(members(&zset, rank, false)[0].0, *member);
+        }
+    }
+
+    #[test]
+    fn score_and_lex_ranges() {
+        let mut zset = SortedSet::default();
+
+        for (i, member) in ["a", "b", "c", "d", "e"].into_iter().enumerate() {
+            zset.insert(Bytes::from(member), i as f64);
+        }
+
+        let range = ScoreRange::parse(&Bytes::from("(1"), &Bytes::from("+inf")).unwrap();
+
+        assert_eq!(zset.count_in_score_range(range), 3);
+        assert_eq!(
+            zset.iter_score_range(range, true)
+                .map(|(_, s)| s)
+                .collect::<Vec<_>>(),
+            [4.0, 3.0, 2.0]
+        );
+
+        let range = ScoreRange::parse(&Bytes::from("3"), &Bytes::from("(3")).unwrap();
+
+        assert_eq!(zset.count_in_score_range(range), 0);
+        assert!(ScoreRange::parse(&Bytes::from("x"), &Bytes::from("1")).is_err());
+
+        let mut zset = SortedSet::default();
+
+        for member in ["a", "b", "c", "d"] {
+            zset.insert(Bytes::from(member), 0.0);
+        }
+
+        let range = LexRange::parse(&Bytes::from("(a"), &Bytes::from("[c")).unwrap();
+
+        assert_eq!(
+            zset.iter_lex_range(&range, false)
+                .map(|(m, _)| m.clone())
+                .collect::<Vec<_>>(),
+            ["b", "c"]
+        );
+        assert!(LexRange::parse(&Bytes::from("a"), &Bytes::from("+")).is_err());
+    }
+}
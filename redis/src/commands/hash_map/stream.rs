@@ -0,0 +1,448 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt::Display,
+    ops::Bound,
+};
+
+use bytes::Bytes;
+
+use crate::commands::{errors::CommandExecutionError, utils::unix_time_ms};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    /// Parses `<ms>-<seq>` or just `<ms>`, in which case the sequence is `missing_seq`.
+    pub fn parse(arg: &[u8], missing_seq: u64) -> Result<Self, CommandExecutionError> {
+        let parse_part = |part: &[u8]| {
+            str::from_utf8(part)
+                .ok()
+                .filter(|part| !part.starts_with('+') && !part.starts_with('-'))
+                .and_then(|part| part.parse::<u64>().ok())
+                .ok_or(CommandExecutionError::InvalidStreamId)
+        };
+
+        match arg.iter().position(|b| *b == b'-') {
+            Some(idx) => Ok(StreamId {
+                ms: parse_part(&arg[..idx])?,
+                seq: parse_part(&arg[idx + 1..])?,
+            }),
+            None => Ok(StreamId {
+                ms: parse_part(arg)?,
+                seq: missing_seq,
+            }),
+        }
+    }
+
+    pub fn next(&self) -> Option<StreamId> {
+        match self.seq.checked_add(1) {
+            Some(seq) => Some(StreamId { ms: self.ms, seq }),
+            None => self.ms.checked_add(1).map(|ms| StreamId { ms, seq: 0 }),
+        }
+    }
+
+    pub fn prev(&self) -> Option<StreamId> {
+        match self.seq.checked_sub(1) {
+            Some(seq) => Some(StreamId { ms: self.ms, seq }),
+            None => self
+                .ms
+                .checked_sub(1)
+                .map(|ms| StreamId { ms, seq: u64::MAX }),
+        }
+    }
+}
+
+impl Display for StreamId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+/// Id requested by `XADD`: `*`, `<ms>-*` or a fully explicit one.
+#[derive(Debug, Clone, Copy)]
+pub enum NewStreamId {
+    Auto,
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+impl NewStreamId {
+    pub fn parse(arg: &[u8]) -> Result<Self, CommandExecutionError> {
+        match arg {
+            b"*" => Ok(NewStreamId::Auto),
+            _ => match arg.strip_suffix(b"-*") {
+                Some(ms) => Ok(NewStreamId::AutoSeq(StreamId::parse(ms, 0)?.ms)),
+                None => Ok(NewStreamId::Explicit(StreamId::parse(arg, 0)?)),
+            },
+        }
+    }
+}
+
+pub type StreamEntry = Vec<(Bytes, Bytes)>;
+
+#[derive(Debug, Clone, Copy)]
+pub enum TrimStrategy {
+    MaxLen(usize),
+    MinId(StreamId),
+}
+
+/// Entry delivered to a consumer of a group but not acknowledged yet.
+#[derive(Debug, Clone)]
+pub struct PendingEntry {
+    pub consumer: Bytes,
+    pub delivery_time: u64,
+    pub delivery_count: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct Consumer {
+    pub seen_time: u64,
+    pub active_time: Option<u64>,
+    pub pending: BTreeSet<StreamId>,
+}
+
+impl Consumer {
+    fn new(now: u64) -> Self {
+        Self {
+            seen_time: now,
+            active_time: None,
+            pending: BTreeSet::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConsumerGroup {
+    pub last_delivered_id: StreamId,
+    /// Logical number of entries the group has read, `None` when it cannot be known
+    /// because of entries deleted in between.
+    pub entries_read: Option<u64>,
+    pub pending: BTreeMap<StreamId, PendingEntry>,
+    pub consumers: BTreeMap<Bytes, Consumer>,
+}
+
+impl ConsumerGroup {
+    pub fn new(last_delivered_id: StreamId, entries_read: Option<u64>) -> Self {
+        Self {
+            last_delivered_id,
+            entries_read,
+            pending: BTreeMap::new(),
+            consumers: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the consumer, creating it if needed, and marks it as just seen.
+    pub fn consumer_mut(&mut self, name: &Bytes, now: u64) -> &mut Consumer {
+        let consumer = self
+            .consumers
+            .entry(name.clone())
+            .or_insert_with(|| Consumer::new(now));
+
+        consumer.seen_time = now;
+
+        consumer
+    }
+
+    /// Assigns the pending entry to the consumer, moving it from its previous owner if any.
+    pub fn assign(
+        &mut self,
+        id: StreamId,
+        consumer: &Bytes,
+        delivery_time: u64,
+        delivery_count: u64,
+    ) {
+        if let Some(previous) = self.pending.get(&id)
+            && let Some(previous_consumer) = self.consumers.get_mut(&previous.consumer)
+        {
+            previous_consumer.pending.remove(&id);
+        }
+
+        self.pending.insert(
+            id,
+            PendingEntry {
+                consumer: consumer.clone(),
+                delivery_time,
+                delivery_count,
+            },
+        );
+        self.consumers
+            .entry(consumer.clone())
+            .or_insert_with(|| Consumer::new(delivery_time))
+            .pending
+            .insert(id);
+    }
+
+    pub fn ack(&mut self, id: &StreamId) -> bool {
+        let Some(pending) = self.pending.remove(id) else {
+            return false;
+        };
+
+        if let Some(consumer) = self.consumers.get_mut(&pending.consumer) {
+            consumer.pending.remove(id);
+        }
+
+        true
+    }
+
+    /// Removes the consumer and its pending entries, returning how many it had.
+    pub fn remove_consumer(&mut self, name: &[u8]) -> usize {
+        let Some(consumer) = self.consumers.remove(name) else {
+            return 0;
+        };
+
+        for id in &consumer.pending {
+            self.pending.remove(id);
+        }
+
+        consumer.pending.len()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Stream {
+    pub entries: BTreeMap<StreamId, StreamEntry>,
+    pub last_id: StreamId,
+    pub max_deleted_id: StreamId,
+    pub entries_added: u64,
+    pub groups: BTreeMap<Bytes, ConsumerGroup>,
+}
+
+impl Stream {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn first_id(&self) -> Option<StreamId> {
+        self.entries.keys().next().copied()
+    }
+
+    /// Whether some entry at or after the id was deleted, which makes counting entries unreliable.
+    fn has_tombstones_from(&self, id: &StreamId) -> bool {
+        !self.entries.is_empty()
+            && self.max_deleted_id != StreamId::MIN
+            && *id <= self.max_deleted_id
+    }
+
+    /// Number of entries ever added up to the id, if it can be known despite deletions.
+    pub fn entries_read_until(&self, id: &StreamId) -> Option<u64> {
+        if self.entries_added == 0 {
+            return Some(0);
+        }
+
+        if (self.entries.is_empty() && *id <= self.last_id) || *id == self.last_id {
+            return Some(self.entries_added);
+        }
+
+        if *id > self.last_id {
+            return None;
+        }
+
+        let first_id = self.first_id()?;
+
+        // without deletions past the first entry everything before it was trimmed away
+        if self.max_deleted_id == StreamId::MIN || self.max_deleted_id < first_id {
+            let trimmed = self.entries_added - self.len() as u64;
+
+            match id.cmp(&first_id) {
+                Ordering::Less => return Some(trimmed),
+                Ordering::Equal => return Some(trimmed + 1),
+                Ordering::Greater => {}
+            }
+        }
+
+        None
+    }
+
+    /// Number of entries the group still has to read, if it can be known.
+    pub fn lag(&self, group: &ConsumerGroup) -> Option<u64> {
+        if self.entries_added == 0 {
+            return Some(0);
+        }
+
+        let entries_read = match group.entries_read {
+            Some(entries_read)
+                if !self.has_tombstones_from(&group.last_delivered_id)
+                    && self
+                        .first_id()
+                        .is_some_and(|first_id| group.last_delivered_id >= first_id) =>
+            {
+                Some(entries_read)
+            }
+            _ => self.entries_read_until(&group.last_delivered_id),
+        };
+
+        entries_read.map(|entries_read| self.entries_added.saturating_sub(entries_read))
+    }
+
+    /// Delivers the entries the group has not delivered yet to the consumer, adding them to
+    /// its pending entries unless `no_ack` is set. `None` when the group does not exist.
+    pub fn deliver_new_entries(
+        &mut self,
+        group: &[u8],
+        consumer: &Bytes,
+        count: Option<usize>,
+        no_ack: bool,
+    ) -> Option<Vec<(StreamId, StreamEntry)>> {
+        let now = unix_time_ms();
+        let last_delivered_id = self.groups.get(group)?.last_delivered_id;
+
+        let entries: Vec<(StreamId, StreamEntry)> = self
+            .range(Bound::Excluded(last_delivered_id), Bound::Unbounded)
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(id, entry)| (*id, entry.clone()))
+            .collect();
+
+        for (id, _) in &entries {
+            let entries_read = match self.groups.get(group)?.entries_read {
+                Some(entries_read) if !self.has_tombstones_from(id) => Some(entries_read + 1),
+                _ => self.entries_read_until(id),
+            };
+
+            let group = self.groups.get_mut(group)?;
+
+            group.entries_read = entries_read;
+            group.last_delivered_id = *id;
+
+            if !no_ack {
+                group.assign(*id, consumer, now, 1);
+            }
+        }
+
+        let consumer = self.groups.get_mut(group)?.consumer_mut(consumer, now);
+
+        if !entries.is_empty() {
+            consumer.active_time = Some(now);
+        }
+
+        Some(entries)
+    }
+
+    fn next_id(&self, id: NewStreamId) -> Result<StreamId, CommandExecutionError> {
+        let id = match id {
+            NewStreamId::Auto => {
+                let now = unix_time_ms();
+
+                if now > self.last_id.ms {
+                    StreamId { ms: now, seq: 0 }
+                } else {
+                    self.last_id
+                        .next()
+                        .ok_or(CommandExecutionError::StreamExhausted)?
+                }
+            }
+            NewStreamId::AutoSeq(ms) if ms == self.last_id.ms => {
+                if self.last_id.seq == u64::MAX {
+                    return Err(CommandExecutionError::StreamIdTooSmall);
+                }
+
+                StreamId {
+                    ms,
+                    seq: self.last_id.seq + 1,
+                }
+            }
+            NewStreamId::AutoSeq(ms) => StreamId {
+                ms,
+                seq: if ms == 0 { 1 } else { 0 },
+            },
+            NewStreamId::Explicit(id) => id,
+        };
+
+        if id == StreamId::MIN {
+            return Err(CommandExecutionError::StreamIdZero);
+        }
+
+        if id <= self.last_id {
+            return Err(CommandExecutionError::StreamIdTooSmall);
+        }
+
+        Ok(id)
+    }
+
+    pub fn add(
+        &mut self,
+        id: NewStreamId,
+        entry: StreamEntry,
+    ) -> Result<StreamId, CommandExecutionError> {
+        let id = self.next_id(id)?;
+
+        self.entries.insert(id, entry);
+        self.last_id = id;
+        self.entries_added += 1;
+
+        Ok(id)
+    }
+
+    /// Evicts the oldest entries according to the strategy, evicting at most `limit` of them.
+    pub fn trim(&mut self, strategy: TrimStrategy, limit: Option<usize>) -> usize {
+        let mut evicted = 0;
+
+        while limit.is_none_or(|limit| evicted < limit) {
+            let Some(first_id) = self.entries.keys().next().copied() else {
+                break;
+            };
+
+            let should_evict = match strategy {
+                TrimStrategy::MaxLen(max_len) => self.entries.len() > max_len,
+                TrimStrategy::MinId(min_id) => first_id < min_id,
+            };
+
+            if !should_evict {
+                break;
+            }
+
+            self.entries.remove(&first_id);
+            self.max_deleted_id = self.max_deleted_id.max(first_id);
+            evicted += 1;
+        }
+
+        evicted
+    }
+
+    pub fn delete(&mut self, id: &StreamId) -> bool {
+        let deleted = self.entries.remove(id).is_some();
+
+        if deleted {
+            self.max_deleted_id = self.max_deleted_id.max(*id);
+        }
+
+        deleted
+    }
+
+    pub fn range(
+        &self,
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+    ) -> impl DoubleEndedIterator<Item = (&StreamId, &StreamEntry)> {
+        self.entries.range(range_bounds(start, end))
+    }
+}
+
+/// Bounds safe to pass to `BTreeMap::range`, which panics on inverted ones
+/// while those should simply match nothing.
+pub fn range_bounds(
+    start: Bound<StreamId>,
+    end: Bound<StreamId>,
+) -> (Bound<StreamId>, Bound<StreamId>) {
+    let is_empty = match (start, end) {
+        (Bound::Included(s), Bound::Included(e)) => s > e,
+        (Bound::Included(s) | Bound::Excluded(s), Bound::Excluded(e))
+        | (Bound::Excluded(s), Bound::Included(e)) => s >= e,
+        _ => false,
+    };
+
+    if is_empty {
+        (Bound::Excluded(StreamId::MAX), Bound::Unbounded)
+    } else {
+        (start, end)
+    }
+}
@@ -1,7 +1,51 @@
+use bytes::Bytes;
+
 use crate::{
     commands::{
         errors::CommandExecutionError,
-        handlers::{config::config, echo::echo, get::get, keys::keys, ping::ping, set::set},
+        handlers::{
+            bitmaps::{bitcount, bitfield, bitop, bitpos, getbit, setbit},
+            config::config,
+            databases::{dbsize, flushall, flushdb, move_key, select, swapdb},
+            echo::echo,
+            expire::{expire, persist, ttl},
+            geo::{geoadd, geodist, geopos, geosearch, geosearchstore},
+            get::get,
+            hashes::{
+                hdel, hexists, hexpire, hget, hgetall, hincrby, hincrbyfloat, hkeys_or_values,
+                hlen, hmget, hpersist, hset, hsetnx, httl,
+            },
+            hello::hello,
+            hyperloglog::{pfadd, pfcount, pfmerge},
+            info::info,
+            key_type::key_type,
+            keys::{copy, del, exists, keys, random_key, rename, touch, unlink},
+            lists::{
+                ListEnd, blmove, blocking_pop, lindex, llen, lmove, lrange, lrem, lset, ltrim, pop,
+                push,
+            },
+            object::object,
+            persistence::{bgsave, lastsave, save},
+            ping::ping,
+            scan::{hscan, scan, sscan, zscan},
+            set::set,
+            sets::{
+                SetOp, sadd, scard, set_op, set_op_store, sismember, smembers, spop, srandmember,
+                srem,
+            },
+            sorted_sets::{
+                RangeBy, zadd, zcard, zcount, zincrby, zpop, zrange, zrange_legacy, zrank, zrem,
+                zscore,
+            },
+            stream_groups::{xack, xautoclaim, xclaim, xgroup, xinfo, xpending, xreadgroup},
+            streams::{xadd, xdel, xlen, xrange, xread, xtrim},
+            strings::{
+                append, decrby, getdel, getex, getrange, getset, incr, incrby, incrbyfloat, mget,
+                mset, setrange, strlen,
+            },
+        },
+        hash_map::SELECTED_DB,
+        session::Session,
     },
     resp::types::RespType,
 };
@@ -9,47 +53,648 @@ use crate::{
 pub struct Processor;
 
 impl Processor {
-    pub async fn exec_from_resp(value: RespType) -> Result<RespType, CommandExecutionError> {
+    fn args_from_resp(value: RespType) -> Result<Vec<Bytes>, CommandExecutionError> {
         match value {
-            RespType::Array(Some(arr)) => match arr.as_slice() {
-                [RespType::BulkString(Some(cmd)), params @ ..] => {
-                    match cmd.to_ascii_lowercase().as_slice() {
-                        b"keys" => Ok(keys().await),
-                        b"echo" => match params {
-                            [RespType::BulkString(Some(arg))] => Ok(echo(arg).await),
-                            _ => Err(CommandExecutionError::IncorrectCommandFormatError),
-                        },
-                        b"ping" => Ok(ping().await),
-                        b"set" => match params {
-                            [
-                                key @ RespType::BulkString(Some(_)),
-                                value @ RespType::BulkString(Some(_)),
-                            ] => Ok(set(key, value, None, None).await?),
-                            [
-                                key @ RespType::BulkString(Some(_)),
-                                value @ RespType::BulkString(Some(_)),
-                                RespType::BulkString(Some(time_op)),
-                                RespType::BulkString(Some(time)),
-                            ] => Ok(set(key, value, Some(time_op), Some(time)).await?),
-                            _ => Err(CommandExecutionError::IncorrectCommandFormatError),
-                        },
-                        b"get" => match params {
-                            [key @ RespType::BulkString(Some(_))] => Ok(get(key).await),
-                            _ => Err(CommandExecutionError::IncorrectCommandFormatError),
-                        },
-                        b"config" => match params {
-                            [
-                                RespType::BulkString(Some(action)),
-                                RespType::BulkString(Some(key)),
-                            ] => Ok(config(action, key).await?),
-                            _ => Err(CommandExecutionError::IncorrectCommandFormatError),
-                        },
-                        _ => Err(CommandExecutionError::UnsupportedCommandError),
-                    }
-                }
-                _ => Err(CommandExecutionError::IncorrectCommandFormatError),
-            },
+            RespType::Array(Some(arr)) => arr
+                .into_iter()
+                .map(|arg| match arg {
+                    RespType::BulkString(Some(arg)) => Ok(arg.freeze()),
+                    _ => Err(CommandExecutionError::IncorrectCommandFormatError),
+                })
+                .collect(),
             _ => Err(CommandExecutionError::UnsupportedRespType),
         }
     }
+
+    /// Runs the command against the database selected by the session.
+    pub async fn exec_from_resp(
+        value: RespType,
+        session: &mut Session,
+    ) -> Result<RespType, CommandExecutionError> {
+        let args = Self::args_from_resp(value)?;
+
+        SELECTED_DB
+            .scope(session.db, Self::exec(&args, session))
+            .await
+    }
+
+    async fn exec(
+        args: &[Bytes],
+        session: &mut Session,
+    ) -> Result<RespType, CommandExecutionError> {
+        let [cmd, params @ ..] = args else {
+            return Err(CommandExecutionError::IncorrectCommandFormatError);
+        };
+        let wrong_arity = || {
+            CommandExecutionError::WrongNumberOfArguments(
+                String::from_utf8_lossy(cmd).to_ascii_lowercase(),
+            )
+        };
+
+        match cmd.to_ascii_lowercase().as_slice() {
+            b"keys" => match params {
+                [pattern] => Ok(keys(pattern).await),
+                _ => Err(wrong_arity()),
+            },
+            b"del" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(del(keys).await),
+            },
+            b"unlink" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(unlink(keys).await),
+            },
+            b"exists" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(exists(keys).await),
+            },
+            b"touch" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(touch(keys).await),
+            },
+            b"rename" | b"renamenx" => match params {
+                [source, destination] => {
+                    Ok(rename(source, destination, cmd.eq_ignore_ascii_case(b"renamenx")).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"copy" => match params {
+                [source, destination, args @ ..] => Ok(copy(source, destination, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"randomkey" => match params {
+                [] => Ok(random_key().await),
+                _ => Err(wrong_arity()),
+            },
+            b"scan" => match params {
+                [cursor, args @ ..] => Ok(scan(cursor, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hscan" => match params {
+                [key, cursor, args @ ..] => Ok(hscan(key, cursor, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"sscan" => match params {
+                [key, cursor, args @ ..] => Ok(sscan(key, cursor, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zscan" => match params {
+                [key, cursor, args @ ..] => Ok(zscan(key, cursor, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"echo" => match params {
+                [arg] => Ok(echo(arg).await),
+                _ => Err(wrong_arity()),
+            },
+            b"ping" => Ok(ping().await),
+            b"hello" => Ok(hello(session, params).await?),
+            b"set" => match params {
+                [key, value, args @ ..] => Ok(set(key, value, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"get" => match params {
+                [key] => Ok(get(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"incr" => match params {
+                [key] => Ok(incr(key, 1).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"decr" => match params {
+                [key] => Ok(incr(key, -1).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"incrby" => match params {
+                [key, increment] => Ok(incrby(key, increment).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"decrby" => match params {
+                [key, decrement] => Ok(decrby(key, decrement).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"incrbyfloat" => match params {
+                [key, increment] => Ok(incrbyfloat(key, increment).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"append" => match params {
+                [key, value] => Ok(append(key, value).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"strlen" => match params {
+                [key] => Ok(strlen(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"getrange" => match params {
+                [key, start, end] => Ok(getrange(key, start, end).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"setrange" => match params {
+                [key, offset, value] => Ok(setrange(key, offset, value).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"mget" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(mget(keys).await?),
+            },
+            b"mset" | b"msetnx" => match params {
+                pairs if !pairs.is_empty() && pairs.len().is_multiple_of(2) => {
+                    Ok(mset(pairs, cmd.eq_ignore_ascii_case(b"msetnx")).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"getset" => match params {
+                [key, value] => Ok(getset(key, value).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"getdel" => match params {
+                [key] => Ok(getdel(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"getex" => match params {
+                [key, args @ ..] => Ok(getex(key, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"expire" | b"pexpire" | b"expireat" | b"pexpireat" => match params {
+                [key, time, args @ ..] => {
+                    let command = String::from_utf8_lossy(cmd).to_ascii_lowercase();
+                    let in_millis = command.starts_with("p");
+                    let absolute = command.ends_with("at");
+
+                    Ok(expire(&command, key, time, args, in_millis, absolute).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"ttl" => match params {
+                [key] => Ok(ttl(key, false, false).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"pttl" => match params {
+                [key] => Ok(ttl(key, true, false).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"expiretime" => match params {
+                [key] => Ok(ttl(key, false, true).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"pexpiretime" => match params {
+                [key] => Ok(ttl(key, true, true).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"persist" => match params {
+                [key] => Ok(persist(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"type" => match params {
+                [key] => Ok(key_type(key).await),
+                _ => Err(wrong_arity()),
+            },
+            b"object" => match params {
+                [subcommand, key] => Ok(object(subcommand, key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"lpush" => match params {
+                [key, values @ ..] if !values.is_empty() => {
+                    Ok(push(key, values, ListEnd::Left).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"rpush" => match params {
+                [key, values @ ..] if !values.is_empty() => {
+                    Ok(push(key, values, ListEnd::Right).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"lpop" => match params {
+                [key] => Ok(pop(key, None, ListEnd::Left).await?),
+                [key, count] => Ok(pop(key, Some(count), ListEnd::Left).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"rpop" => match params {
+                [key] => Ok(pop(key, None, ListEnd::Right).await?),
+                [key, count] => Ok(pop(key, Some(count), ListEnd::Right).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"blpop" => match params {
+                [keys @ .., timeout] if !keys.is_empty() => {
+                    Ok(blocking_pop(keys, timeout, ListEnd::Left).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"brpop" => match params {
+                [keys @ .., timeout] if !keys.is_empty() => {
+                    Ok(blocking_pop(keys, timeout, ListEnd::Right).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"lmove" => match params {
+                [source, destination, from, to] => Ok(lmove(source, destination, from, to).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"blmove" => match params {
+                [source, destination, from, to, timeout] => {
+                    Ok(blmove(source, destination, from, to, timeout).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"llen" => match params {
+                [key] => Ok(llen(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"lrange" => match params {
+                [key, start, stop] => Ok(lrange(key, start, stop).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"lindex" => match params {
+                [key, index] => Ok(lindex(key, index).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"lset" => match params {
+                [key, index, value] => Ok(lset(key, index, value).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"ltrim" => match params {
+                [key, start, stop] => Ok(ltrim(key, start, stop).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"lrem" => match params {
+                [key, count, value] => Ok(lrem(key, count, value).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xadd" => match params {
+                [key, args @ ..] => Ok(xadd(key, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xlen" => match params {
+                [key] => Ok(xlen(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xrange" => match params {
+                [key, start, end, args @ ..] => Ok(xrange(key, start, end, args, false).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xrevrange" => match params {
+                [key, end, start, args @ ..] => Ok(xrange(key, start, end, args, true).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xtrim" => match params {
+                [key, args @ ..] if !args.is_empty() => Ok(xtrim(key, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xdel" => match params {
+                [key, ids @ ..] if !ids.is_empty() => Ok(xdel(key, ids).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xread" => Ok(xread(params).await?),
+            b"xgroup" => match params {
+                [subcommand, args @ ..] => Ok(xgroup(subcommand, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xreadgroup" => Ok(xreadgroup(params).await?),
+            b"xack" => match params {
+                [key, group, ids @ ..] if !ids.is_empty() => Ok(xack(key, group, ids).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xpending" => match params {
+                [key, group, args @ ..] => Ok(xpending(key, group, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"xclaim" => match params {
+                [key, group, consumer, min_idle, args @ ..] if !args.is_empty() => {
+                    Ok(xclaim(key, group, consumer, min_idle, args).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"xautoclaim" => match params {
+                [key, group, consumer, min_idle, start, args @ ..] => {
+                    Ok(xautoclaim(key, group, consumer, min_idle, start, args).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"xinfo" => match params {
+                [subcommand, args @ ..] => Ok(xinfo(subcommand, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hset" => match params {
+                [key, pairs @ ..] => Ok(hset(key, pairs).await?),
+                _ => Err(CommandExecutionError::WrongNumberOfArguments(
+                    "hset".to_string(),
+                )),
+            },
+            b"hsetnx" => match params {
+                [key, field, value] => Ok(hsetnx(key, field, value).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hget" => match params {
+                [key, field] => Ok(hget(key, field).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hmget" => match params {
+                [key, fields @ ..] if !fields.is_empty() => Ok(hmget(key, fields).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hgetall" => match params {
+                [key] => Ok(hgetall(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hdel" => match params {
+                [key, fields @ ..] if !fields.is_empty() => Ok(hdel(key, fields).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hexists" => match params {
+                [key, field] => Ok(hexists(key, field).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hlen" => match params {
+                [key] => Ok(hlen(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hkeys" => match params {
+                [key] => Ok(hkeys_or_values(key, true).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hvals" => match params {
+                [key] => Ok(hkeys_or_values(key, false).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hincrby" => match params {
+                [key, field, increment] => Ok(hincrby(key, field, increment).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hincrbyfloat" => match params {
+                [key, field, increment] => Ok(hincrbyfloat(key, field, increment).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hexpire" | b"hpexpire" | b"hexpireat" | b"hpexpireat" => match params {
+                [key, time, args @ ..] => {
+                    let command = String::from_utf8_lossy(cmd).to_ascii_lowercase();
+                    let in_millis = command.starts_with("hp");
+                    let absolute = command.ends_with("at");
+
+                    Ok(hexpire(&command, key, time, args, in_millis, absolute).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"httl" => match params {
+                [key, args @ ..] => Ok(httl(key, args, false, false).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hpttl" => match params {
+                [key, args @ ..] => Ok(httl(key, args, true, false).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hexpiretime" => match params {
+                [key, args @ ..] => Ok(httl(key, args, false, true).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hpexpiretime" => match params {
+                [key, args @ ..] => Ok(httl(key, args, true, true).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"hpersist" => match params {
+                [key, args @ ..] => Ok(hpersist(key, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"sadd" => match params {
+                [key, members @ ..] if !members.is_empty() => Ok(sadd(key, members).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"srem" => match params {
+                [key, members @ ..] if !members.is_empty() => Ok(srem(key, members).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"smembers" => match params {
+                [key] => Ok(smembers(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"sismember" => match params {
+                [key, member] => Ok(sismember(key, std::slice::from_ref(member), false).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"smismember" => match params {
+                [key, members @ ..] if !members.is_empty() => {
+                    Ok(sismember(key, members, true).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"scard" => match params {
+                [key] => Ok(scard(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"spop" => match params {
+                [key] => Ok(spop(key, None).await?),
+                [key, count] => Ok(spop(key, Some(count)).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"srandmember" => match params {
+                [key] => Ok(srandmember(key, None).await?),
+                [key, count] => Ok(srandmember(key, Some(count)).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"sinter" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(set_op(SetOp::Inter, keys).await?),
+            },
+            b"sunion" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(set_op(SetOp::Union, keys).await?),
+            },
+            b"sdiff" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(set_op(SetOp::Diff, keys).await?),
+            },
+            b"sinterstore" => match params {
+                [destination, keys @ ..] if !keys.is_empty() => {
+                    Ok(set_op_store(SetOp::Inter, destination, keys).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"sunionstore" => match params {
+                [destination, keys @ ..] if !keys.is_empty() => {
+                    Ok(set_op_store(SetOp::Union, destination, keys).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"sdiffstore" => match params {
+                [destination, keys @ ..] if !keys.is_empty() => {
+                    Ok(set_op_store(SetOp::Diff, destination, keys).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"zadd" => match params {
+                [key, args @ ..] if !args.is_empty() => Ok(zadd(key, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zincrby" => match params {
+                [key, increment, member] => Ok(zincrby(key, increment, member).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zrem" => match params {
+                [key, members @ ..] if !members.is_empty() => Ok(zrem(key, members).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zcard" => match params {
+                [key] => Ok(zcard(key).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zscore" => match params {
+                [key, member] => Ok(zscore(key, member).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zrank" => match params {
+                [key, member, args @ ..] => Ok(zrank(key, member, args, false).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zrevrank" => match params {
+                [key, member, args @ ..] => Ok(zrank(key, member, args, true).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zcount" => match params {
+                [key, min, max] => Ok(zcount(key, min, max).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zrange" => match params {
+                [key, start, stop, args @ ..] => {
+                    Ok(zrange(key, start, stop, args, session.protocol).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"zrevrange" | b"zrangebyscore" | b"zrevrangebyscore" | b"zrangebylex"
+            | b"zrevrangebylex" => match params {
+                [key, start, stop, args @ ..] => {
+                    let cmd = cmd.to_ascii_lowercase();
+                    let by = if cmd.ends_with(b"byscore") {
+                        RangeBy::Score
+                    } else if cmd.ends_with(b"bylex") {
+                        RangeBy::Lex
+                    } else {
+                        RangeBy::Rank
+                    };
+                    let is_reversed = cmd.starts_with(b"zrev");
+
+                    Ok(
+                        zrange_legacy(key, start, stop, args, by, is_reversed, session.protocol)
+                            .await?,
+                    )
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"zpopmin" => match params {
+                [key] => Ok(zpop(key, None, false, session.protocol).await?),
+                [key, count] => Ok(zpop(key, Some(count), false, session.protocol).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"zpopmax" => match params {
+                [key] => Ok(zpop(key, None, true, session.protocol).await?),
+                [key, count] => Ok(zpop(key, Some(count), true, session.protocol).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"geoadd" => match params {
+                [key, args @ ..] if !args.is_empty() => Ok(geoadd(key, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"geopos" => match params {
+                [key, members @ ..] => Ok(geopos(key, members, session.protocol).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"geodist" => match params {
+                [key, member1, member2] => Ok(geodist(key, member1, member2, None).await?),
+                [key, member1, member2, unit] => {
+                    Ok(geodist(key, member1, member2, Some(unit)).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"geosearch" => match params {
+                [key, args @ ..] if !args.is_empty() => {
+                    let command = String::from_utf8_lossy(cmd);
+
+                    Ok(geosearch(&command, key, args, session.protocol).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"geosearchstore" => match params {
+                [destination, key, args @ ..] if !args.is_empty() => {
+                    let command = String::from_utf8_lossy(cmd);
+
+                    Ok(geosearchstore(&command, destination, key, args).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"pfadd" => match params {
+                [key, elements @ ..] => Ok(pfadd(key, elements).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"pfcount" => match params {
+                [] => Err(wrong_arity()),
+                keys => Ok(pfcount(keys).await?),
+            },
+            b"pfmerge" => match params {
+                [destination, sources @ ..] => Ok(pfmerge(destination, sources).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"setbit" => match params {
+                [key, offset, value] => Ok(setbit(key, offset, value).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"getbit" => match params {
+                [key, offset] => Ok(getbit(key, offset).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"bitcount" => match params {
+                [key, args @ ..] => Ok(bitcount(key, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"bitpos" => match params {
+                [key, bit, args @ ..] => Ok(bitpos(key, bit, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"bitop" => match params {
+                [operation, destination, keys @ ..] if !keys.is_empty() => {
+                    Ok(bitop(operation, destination, keys).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"bitfield" | b"bitfield_ro" => match params {
+                [key, args @ ..] => {
+                    Ok(bitfield(key, args, cmd.eq_ignore_ascii_case(b"bitfield_ro")).await?)
+                }
+                _ => Err(wrong_arity()),
+            },
+            b"select" => match params {
+                [index] => Ok(select(session, index).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"swapdb" => match params {
+                [first, second] => Ok(swapdb(first, second).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"move" => match params {
+                [key, db] => Ok(move_key(key, db).await?),
+                _ => Err(wrong_arity()),
+            },
+            b"dbsize" => match params {
+                [] => Ok(dbsize().await),
+                _ => Err(wrong_arity()),
+            },
+            b"flushdb" => Ok(flushdb(params).await?),
+            b"flushall" => Ok(flushall(params).await?),
+            b"save" => match params {
+                [] => Ok(save().await?),
+                _ => Err(wrong_arity()),
+            },
+            b"bgsave" => match params {
+                [] => Ok(bgsave().await?),
+                _ => Err(wrong_arity()),
+            },
+            b"lastsave" => match params {
+                [] => Ok(lastsave()),
+                _ => Err(wrong_arity()),
+            },
+            b"info" => Ok(info(params).await?),
+            b"config" => match params {
+                [action, args @ ..] => Ok(config(action, args).await?),
+                _ => Err(wrong_arity()),
+            },
+            _ => Err(CommandExecutionError::UnsupportedCommandError),
+        }
+    }
 }
@@ -0,0 +1,28 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use bytes::Bytes;
+
+use crate::resp::types::ProtocolVersion;
+
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Per-connection state which commands such as `HELLO` are able to change.
+#[derive(Debug)]
+pub(crate) struct Session {
+    pub id: u64,
+    pub protocol: ProtocolVersion,
+    pub name: Option<Bytes>,
+    /// Index of the database the commands of the connection run against.
+    pub db: usize,
+}
+
+impl Session {
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed),
+            protocol: ProtocolVersion::default(),
+            name: None,
+            db: 0,
+        }
+    }
+}
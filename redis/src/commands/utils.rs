@@ -0,0 +1,294 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bytes::Bytes;
+
+use crate::commands::errors::CommandExecutionError;
+
+/// Parses an integer the way redis does, only accepting the canonical representation: no `+`
+/// sign, no leading zeros and no `-0`.
+pub(crate) fn string_to_int(bytes: &[u8]) -> Option<i64> {
+    let digits = bytes.strip_prefix(b"-").unwrap_or(bytes);
+
+    match digits {
+        [b'0'] if digits.len() == bytes.len() => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => {
+            str::from_utf8(bytes).ok()?.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_int(arg: &Bytes) -> Result<i64, CommandExecutionError> {
+    string_to_int(arg).ok_or(CommandExecutionError::NotAnInteger)
+}
+
+/// Parses a float the way redis does, rejecting `NaN` which cannot be stored nor compared.
+pub(crate) fn parse_float(arg: &Bytes) -> Result<f64, CommandExecutionError> {
+    str::from_utf8(arg)
+        .ok()
+        .filter(|arg| !arg.starts_with(char::is_whitespace) && !arg.ends_with(char::is_whitespace))
+        .and_then(|arg| arg.parse::<f64>().ok())
+        .filter(|value| !value.is_nan())
+        .ok_or(CommandExecutionError::NotAFloat)
+}
+
+/// Formats a float for a string reply, shortest representation that parses back to it
+/// and never in exponent notation.
+pub(crate) fn format_float(value: f64) -> String {
+    if value == 0.0 {
+        // -0 is stored as 0
+        return "0".to_string();
+    }
+
+    value.to_string()
+}
+
+/// Parses a non negative count, e.g. the optional count of `LPOP`.
+pub(crate) fn parse_count(arg: &Bytes) -> Result<usize, CommandExecutionError> {
+    match parse_int(arg)? {
+        count if count < 0 => Err(CommandExecutionError::NotPositive),
+        count => Ok(count as usize),
+    }
+}
+
+/// Converts redis style inclusive `start`/`stop` indexes, where negative values
+/// count from the end, into a valid range of `len` items or `None` if it is empty.
+pub(crate) fn normalize_range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+
+    if start > stop || start >= len {
+        return None;
+    }
+
+    Some((start as usize, stop as usize))
+}
+
+/// Converts a redis style index, where negative values count from the end,
+/// into a valid position within `len` items.
+pub(crate) fn normalize_index(index: i64, len: usize) -> Option<usize> {
+    let index = if index < 0 { len as i64 + index } else { index };
+
+    (0..len as i64).contains(&index).then_some(index as usize)
+}
+
+pub(crate) fn unix_time_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// `NX`, `XX`, `GT` and `LT` options of the commands setting a deadline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExpireCondition {
+    /// Only when there is no deadline yet.
+    Nx,
+    /// Only when there is a deadline already.
+    Xx,
+    /// Only when the new deadline is later, having none counts as an infinite one.
+    Gt,
+    /// Only when the new deadline is sooner, having none counts as an infinite one.
+    Lt,
+}
+
+impl ExpireCondition {
+    pub(crate) fn parse(arg: &[u8]) -> Option<Self> {
+        match arg.to_ascii_lowercase().as_slice() {
+            b"nx" => Some(ExpireCondition::Nx),
+            b"xx" => Some(ExpireCondition::Xx),
+            b"gt" => Some(ExpireCondition::Gt),
+            b"lt" => Some(ExpireCondition::Lt),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn allows(&self, current: Option<u64>, new: u64) -> bool {
+        match self {
+            ExpireCondition::Nx => current.is_none(),
+            ExpireCondition::Xx => current.is_some(),
+            ExpireCondition::Gt => current.is_some_and(|current| new > current),
+            ExpireCondition::Lt => current.is_none_or(|current| new < current),
+        }
+    }
+}
+
+/// Unix time in milliseconds of the deadline given to one of the `EX`, `PX`, `EXAT` and `PXAT`
+/// options of `command`, e.g. `SET key value EX 10`.
+pub(crate) fn parse_expire_at(
+    command: &str,
+    option: &[u8],
+    time: &Bytes,
+    now: u64,
+) -> Result<u64, CommandExecutionError> {
+    let invalid_time = || CommandExecutionError::InvalidExpireTime(command.to_string());
+
+    let time = parse_int(time)?;
+
+    if time <= 0 {
+        return Err(invalid_time());
+    }
+
+    let time = time as u64;
+
+    let at = match option.to_ascii_lowercase().as_slice() {
+        b"ex" => time.checked_mul(1000).and_then(|ms| ms.checked_add(now)),
+        b"px" => time.checked_add(now),
+        b"exat" => time.checked_mul(1000),
+        b"pxat" => Some(time),
+        _ => return Err(CommandExecutionError::SyntaxError),
+    };
+
+    at.ok_or_else(invalid_time)
+}
+
+/// Matches the string against a redis glob-style pattern: `*`, `?`, `[abc]`, `[^a-z]` and `\`
+/// escapes, an unterminated class ending with the pattern.
+pub(crate) fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut pattern, mut string) = (pattern, string);
+
+    while let (Some(&p), Some(&c)) = (pattern.first(), string.first()) {
+        match p {
+            b'*' => {
+                let rest = &pattern[pattern.iter().take_while(|p| **p == b'*').count()..];
+
+                if rest.is_empty() {
+                    return true;
+                }
+
+                return (0..string.len()).any(|i| glob_match(rest, &string[i..]));
+            }
+            b'?' => {}
+            b'[' => {
+                let (is_match, rest) = match_class(&pattern[1..], c);
+
+                if !is_match {
+                    return false;
+                }
+
+                (pattern, string) = (rest, &string[1..]);
+
+                continue;
+            }
+            b'\\' if pattern.len() >= 2 => {
+                if pattern[1] != c {
+                    return false;
+                }
+
+                pattern = &pattern[1..];
+            }
+            _ if p != c => return false,
+            _ => {}
+        }
+
+        (pattern, string) = (&pattern[1..], &string[1..]);
+    }
+
+    // trailing stars match the empty string
+    string.is_empty() && pattern.iter().all(|p| *p == b'*')
+}
+
+/// Matches a character against the class following a `[`, returning the pattern after it.
+fn match_class(pattern: &[u8], c: u8) -> (bool, &[u8]) {
+    let (is_negated, mut pattern) = match pattern {
+        [b'^', rest @ ..] => (true, rest),
+        _ => (false, pattern),
+    };
+    let mut is_match = false;
+
+    loop {
+        pattern = match pattern {
+            [] => break,
+            [b']', rest @ ..] => {
+                pattern = rest;
+                break;
+            }
+            [b'\\', escaped, rest @ ..] => {
+                is_match |= *escaped == c;
+                rest
+            }
+            [start, b'-', end, rest @ ..] => {
+                is_match |= (*start.min(end)..=*start.max(end)).contains(&c);
+                rest
+            }
+            [p, rest @ ..] => {
+                is_match |= *p == c;
+                rest
+            }
+        };
+    }
+
+    (is_match != is_negated, pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn string_to_int_like_redis() {
+        let cases: &[(&str, Option<i64>)] = &[
+            ("0", Some(0)),
+            ("5", Some(5)),
+            ("-5", Some(-5)),
+            ("9223372036854775807", Some(i64::MAX)),
+            ("-9223372036854775808", Some(i64::MIN)),
+            ("9223372036854775808", None),
+            ("", None),
+            ("-", None),
+            ("+5", None),
+            ("05", None),
+            ("-0", None),
+            ("-05", None),
+            (" 5", None),
+            ("5 ", None),
+            ("5a", None),
+        ];
+
+        for (string, expected) in cases {
+            assert_eq!(string_to_int(string.as_bytes()), *expected, "{string}");
+        }
+    }
+
+    #[test]
+    fn glob_match_like_redis() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("*", "", true),
+            ("h?llo", "hello", true),
+            ("h?llo", "hllo", false),
+            ("h*llo", "heeeello", true),
+            ("h*llo", "hello world", false),
+            ("h[ae]llo", "hallo", true),
+            ("h[ae]llo", "hillo", false),
+            ("h[^e]llo", "hallo", true),
+            ("h[^e]llo", "hello", false),
+            ("h[a-b]llo", "hbllo", true),
+            ("h[b-a]llo", "hallo", true),
+            ("h[a-b]llo", "hcllo", false),
+            ("h\\*llo", "h*llo", true),
+            ("h\\*llo", "hello", false),
+            ("h[\\]]llo", "h]llo", true),
+            ("h[abc", "hc", true),
+            ("a*b*c", "aXXbYYc", true),
+            ("a*b*c", "aXXbYY", false),
+            ("user:*:**", "user:1:", true),
+        ];
+
+        for (pattern, string, expected) in cases {
+            assert_eq!(
+                glob_match(pattern.as_bytes(), string.as_bytes()),
+                *expected,
+                "{pattern} against {string}"
+            );
+        }
+    }
+}
@@ -1,10 +1,56 @@
-use std::sync::LazyLock;
+use std::{path::PathBuf, sync::LazyLock};
 
 use tokio::sync::RwLock;
 
+use crate::commands::hash_map::DEFAULT_DATABASES;
+
+/// Version of redis the server behaves like.
+pub(crate) const SERVER_VERSION: &str = "7.4.0";
+
+/// Save the dataset once there were at least `changes` changes and `seconds` passed since the
+/// last save.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SaveRule {
+    pub seconds: u64,
+    pub changes: u64,
+}
+
+/// Rules of `save`, space separated `<seconds> <changes>` pairs. An empty string means no
+/// rules at all.
+pub(crate) fn parse_save_rules(value: &str) -> Option<Vec<SaveRule>> {
+    let numbers = value
+        .split_ascii_whitespace()
+        .map(|number| number.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+
+    if !numbers.len().is_multiple_of(2) {
+        return None;
+    }
+
+    Some(
+        numbers
+            .chunks_exact(2)
+            .map(|rule| SaveRule {
+                seconds: rule[0],
+                changes: rule[1],
+            })
+            .collect(),
+    )
+}
+
+pub(crate) fn format_save_rules(rules: &[SaveRule]) -> String {
+    rules
+        .iter()
+        .map(|rule| format!("{} {}", rule.seconds, rule.changes))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
 pub(crate) struct Config {
     pub dir: Option<String>,
     pub dbfilename: Option<String>,
+    pub databases: usize,
+    pub save: Vec<SaveRule>,
 }
 
 impl Config {
@@ -12,8 +58,39 @@ impl Config {
         Self {
             dir: None,
             dbfilename: None,
+            databases: DEFAULT_DATABASES,
+            // after an hour with a change, 5 minutes with 100 or a minute with 10000, like redis
+            save: vec![
+                SaveRule {
+                    seconds: 3600,
+                    changes: 1,
+                },
+                SaveRule {
+                    seconds: 300,
+                    changes: 100,
+                },
+                SaveRule {
+                    seconds: 60,
+                    changes: 10000,
+                },
+            ],
         }
     }
+
+    /// Whether a file to persist the dataset to is configured, without which nothing is loaded
+    /// at startup nor saved unless asked to.
+    pub fn has_rdb_file(&self) -> bool {
+        self.dir.is_some() || self.dbfilename.is_some()
+    }
+
+    /// File the dataset is saved to and loaded from, `dump.rdb` in the working directory
+    /// unless configured otherwise, like redis.
+    pub fn rdb_path(&self) -> PathBuf {
+        let dir = self.dir.as_deref().unwrap_or(".");
+        let dbfilename = self.dbfilename.as_deref().unwrap_or("dump.rdb");
+
+        PathBuf::from(dir).join(dbfilename)
+    }
 }
 
 pub(crate) static CONFIG: LazyLock<RwLock<Config>> = LazyLock::new(|| RwLock::new(Config::new()));
@@ -1,9 +1,8 @@
 use futures::{SinkExt, StreamExt};
 use std::error::Error;
-use std::path::Path;
-use tokio::fs::File;
+use std::sync::atomic::Ordering;
 use tokio::net::{TcpListener, TcpStream};
-use tokio_util::codec::Framed;
+use tokio_util::{codec::Framed, sync::CancellationToken};
 
 mod commands;
 mod config;
@@ -12,20 +11,34 @@ mod resp;
 
 use clap::Parser;
 
-use crate::commands::hash_map::HASH_MAP;
+use crate::commands::hash_map::{DEFAULT_DATABASES, Db, HASH_MAP};
 use crate::{
-    commands::processor::Processor,
-    config::CONFIG,
-    rdb::parser::RdbCodec,
+    commands::{
+        active_expire, blocking::DISCONNECTION, processor::Processor, session::Session,
+        utils::unix_time_ms,
+    },
+    config::{CONFIG, parse_save_rules},
+    rdb::snapshot,
     resp::{parser::RespCodec, types::RespType},
 };
 
 #[derive(Parser, Debug)]
 struct Args {
+    /// Directory of the rdb file, the working directory by default. Giving it or `--dbfilename`
+    /// loads the file at startup and saves it following the save rules.
     #[arg(long)]
     dir: Option<String>,
+    /// Name of the rdb file, `dump.rdb` by default.
     #[arg(long)]
     dbfilename: Option<String>,
+    /// Number of logical databases, 16 by default, which `SELECT`, `MOVE` and `SWAPDB` indexes
+    /// must be below.
+    #[arg(long, default_value_t = DEFAULT_DATABASES)]
+    databases: usize,
+    /// Space separated `<seconds> <changes>` rules, `""` to never save automatically. Defaults
+    /// to those of redis once an rdb file is configured, to none otherwise.
+    #[arg(long)]
+    save: Option<String>,
 }
 
 async fn send_frame(framed: &mut Framed<TcpStream, RespCodec>, resp: RespType) {
@@ -34,13 +47,46 @@ async fn send_frame(framed: &mut Framed<TcpStream, RespCodec>, resp: RespType) {
     }
 }
 
+/// Resolves once the peer closes the connection, which lets commands that park the client,
+/// like `BLPOP`, stop waiting instead of handing data to a client that is already gone.
+async fn wait_for_disconnect(stream: &TcpStream) {
+    let mut buf = [0; 1];
+
+    match stream.peek(&mut buf).await {
+        Ok(0) | Err(_) => {}
+        // the next request is already pending, the client is surely still connected
+        Ok(_) => std::future::pending::<()>().await,
+    }
+}
+
 async fn handle_tcp_stream(stream: TcpStream) {
     let mut framed = Framed::new(stream, RespCodec::new());
+    let mut session = Session::new();
+    let disconnection = CancellationToken::new();
 
     while let Some(parse_result) = framed.next().await {
         match parse_result {
             Ok(resp_value) => {
-                let maybe_cmd = Processor::exec_from_resp(resp_value.clone()).await;
+                // commands always run to completion and get their reply, a client that half
+                // closed the connection after pipelining them still reads the replies
+                let maybe_cmd = {
+                    let exec = DISCONNECTION.scope(
+                        disconnection.clone(),
+                        Processor::exec_from_resp(resp_value.clone(), &mut session),
+                    );
+                    tokio::pin!(exec);
+
+                    tokio::select! {
+                        biased;
+                        maybe_cmd = &mut exec => maybe_cmd,
+                        _ = wait_for_disconnect(framed.get_ref()) => {
+                            disconnection.cancel();
+                            exec.await
+                        }
+                    }
+                };
+
+                framed.codec_mut().protocol = session.protocol;
 
                 match maybe_cmd {
                     Ok(cmd) => send_frame(&mut framed, cmd).await,
@@ -79,35 +125,42 @@ async fn run_infinite_listener() -> Result<(), Box<dyn Error>> {
 async fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
 
-    let mut config = CONFIG.write().await;
-
-    config.dir = args.dir;
-    config.dbfilename = args.dbfilename;
+    if args.databases == 0 {
+        return Err("at least one database is required".into());
+    }
 
-    let (Some(dir), Some(dbfilename)) = (&config.dir, &config.dbfilename) else {
-        drop(config);
+    *HASH_MAP.write_all().await = (0..args.databases).map(Db::new).collect();
 
-        return run_infinite_listener().await;
-    };
+    tokio::spawn(active_expire::run());
 
-    let rdb_path = Path::new(&dir).join(dbfilename);
+    let mut config = CONFIG.write().await;
 
-    if rdb_path.exists() {
-        let commands_hash_map = HASH_MAP.write().await;
+    config.dir = args.dir;
+    config.dbfilename = args.dbfilename;
+    config.databases = args.databases;
 
-        let rdb_file_stream = File::open(rdb_path).await?;
+    match args.save {
+        Some(save) => config.save = parse_save_rules(&save).ok_or("invalid save rules")?,
+        None if !config.has_rdb_file() => config.save.clear(),
+        None => {}
+    }
 
-        let rdb_codec = RdbCodec::new(commands_hash_map);
-        let mut framed = Framed::new(rdb_file_stream, rdb_codec);
+    let rdb_path = config.rdb_path();
 
-        match framed.next().await {
-            Some(Ok(_)) => println!("rdb file was parsed successfully"),
-            Some(Err(e)) => panic!("cannot parse rdb file: {e}"),
-            None => panic!("cannot parse rdb file: unknown error"),
+    if config.has_rdb_file() && rdb_path.exists() {
+        match snapshot::load(&rdb_path).await {
+            Ok(()) => println!("rdb file was parsed successfully"),
+            Err(e) => panic!("cannot parse rdb file: {e}"),
         }
     }
 
+    snapshot::SAVE_STATE
+        .last_save
+        .store(unix_time_ms() / 1000, Ordering::Relaxed);
+
     drop(config);
 
+    tokio::spawn(snapshot::run_save_rules());
+
     run_infinite_listener().await
 }
@@ -1,5 +1,13 @@
 mod constants;
+mod crc64;
 pub(crate) mod decoder;
+pub(crate) mod encoder;
+mod intset;
+mod listpack;
+mod lzf;
 pub(crate) mod opcodes;
 pub(crate) mod parser;
+pub(crate) mod snapshot;
 pub(crate) mod types;
+mod ziplist;
+mod zipmap;
@@ -1 +1,19 @@
-pub(crate) const HEADER_STR: &str = "REDIS0011";
+/// Files are written in the format of redis 7.4, the first to store deadlines of hash fields.
+pub(crate) const HEADER_STR: &str = "REDIS0012";
+/// Files start with the magic string then the format version on 4 digits, which can be read
+/// up to the one of redis 7.4.
+pub(crate) const HEADER_MAGIC: &str = "REDIS";
+pub(crate) const MAX_RDB_VERSION: u32 = 12;
+/// Version since which files end with their checksum.
+pub(crate) const CHECKSUM_RDB_VERSION: u32 = 5;
+/// Strings are compressed past this length, like redis does.
+pub(crate) const COMPRESSION_MIN_LEN: usize = 20;
+
+/// Flags of the entries in a stream node.
+pub(crate) const STREAM_ITEM_FLAG_DELETED: i64 = 1;
+pub(crate) const STREAM_ITEM_FLAG_SAME_FIELDS: i64 = 2;
+/// Entries per stream node, like the default `stream-node-max-entries` of redis.
+pub(crate) const STREAM_NODE_MAX_ENTRIES: usize = 100;
+/// Containers of the nodes of a list: a large element on its own, or a listpack of smaller ones.
+pub(crate) const QUICKLIST_NODE_PLAIN: u64 = 1;
+pub(crate) const QUICKLIST_NODE_PACKED: u64 = 2;
@@ -1,242 +1,848 @@
-use crate::commands::hash_map::Value;
-use crate::rdb::types::RdbType;
-use crate::rdb::{constants::HEADER_STR, opcodes::OpCode, parser::RdbCodec};
-use crate::resp::types::RespType;
-use bytes::{Buf, BytesMut};
-use std::io::{self, Read};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::io;
+
+use bytes::{Buf, Bytes, BytesMut};
+use indexmap::IndexSet;
 use tokio_util::codec::Decoder;
 
+use crate::commands::hash_map::{
+    hash::Hash,
+    object::Object,
+    sorted_set::SortedSet,
+    stream::{Consumer, ConsumerGroup, PendingEntry, Stream, StreamEntry, StreamId},
+};
+use crate::rdb::listpack::{self, ListpackEntry};
+use crate::rdb::opcodes::{OpCode, OpCodeParseError};
+use crate::rdb::types::{RdbType, RdbTypeParseError};
+use crate::rdb::{
+    constants::{
+        CHECKSUM_RDB_VERSION, HEADER_MAGIC, HEADER_STR, MAX_RDB_VERSION, QUICKLIST_NODE_PACKED,
+        QUICKLIST_NODE_PLAIN, STREAM_ITEM_FLAG_DELETED, STREAM_ITEM_FLAG_SAME_FIELDS,
+    },
+    parser::{RdbCodec, RdbEntry},
+};
+use crate::rdb::{crc64::crc64, intset, lzf, ziplist, zipmap};
+
+#[derive(Debug)]
+enum DecodeError {
+    /// The entry goes on past the bytes read so far, by at least the given number of bytes.
+    Incomplete(usize),
+    Invalid(String),
+}
+
+impl From<OpCodeParseError> for DecodeError {
+    fn from(error: OpCodeParseError) -> Self {
+        DecodeError::Invalid(error.to_string())
+    }
+}
+
+impl From<RdbTypeParseError> for DecodeError {
+    fn from(error: RdbTypeParseError) -> Self {
+        DecodeError::Invalid(error.to_string())
+    }
+}
+
+type DecodeResult<T> = Result<T, DecodeError>;
+
+fn invalid<T>(reason: &str) -> DecodeResult<T> {
+    Err(DecodeError::Invalid(reason.to_string()))
+}
+
 #[derive(Debug)]
 enum LenEncodingType {
-    String(usize),
+    Len(u64),
+    /// Integer of the given number of bytes, standing for its decimal representation.
     StringInteger(usize),
+    /// LZF compressed string.
+    Compressed,
 }
 
-#[derive(Debug)]
-enum LenEncodingValue {
-    String(BytesMut),
-    StringInteger(String),
-}
-
-impl From<LenEncodingValue> for RespType {
-    fn from(value: LenEncodingValue) -> Self {
-        match value {
-            LenEncodingValue::String(v) => RespType::BulkString(Some(v)),
-            LenEncodingValue::StringInteger(v) => RespType::BulkString(Some(v.as_bytes().into())),
-        }
-    }
-}
-
-fn parse_len_encoding(bytes: &mut BytesMut) -> Option<LenEncodingType> {
-    match bytes.first() {
-        Some(first_byte) => {
-            let significant_bytes = first_byte >> 6;
-
-            if significant_bytes == 0 {
-                let len = Some(LenEncodingType::String((first_byte & 0x3f) as usize));
-
-                bytes.advance(1);
-
-                len
-            } else if significant_bytes == 0b01 {
-                let len = bytes.get(1).map(|second_byte| {
-                    LenEncodingType::String(
-                        (((first_byte & 0x3f) as usize) << 8) | *second_byte as usize,
-                    )
-                });
-
-                bytes.advance(2);
-
-                len
-            } else if significant_bytes == 0b10 {
-                let len = bytes.get(1).and_then(|second_byte| {
-                    bytes.get(2).and_then(|third_byte| {
-                        bytes.get(3).and_then(|fourth_byte| {
-                            bytes.get(4).map(|fifth_byte| {
-                                LenEncodingType::String(
-                                    (*second_byte as usize) << 24
-                                        | ((*third_byte as usize) << 16)
-                                        | ((*fourth_byte as usize) << 8)
-                                        | *fifth_byte as usize,
-                                )
-                            })
-                        })
-                    })
-                });
-
-                bytes.advance(5);
-
-                len
-            } else if significant_bytes == 0b11 {
-                let special_format = (first_byte & 0x3f) as usize;
-
-                let len = match special_format {
-                    0..=1 => Some(LenEncodingType::StringInteger(special_format + 1)),
-                    2 => Some(LenEncodingType::StringInteger(special_format.pow(2))),
-                    _ => None,
-                };
+/// Splits off the first `len` bytes, or reports the entry as incomplete.
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> DecodeResult<&'a [u8]> {
+    let (taken, rest) = bytes
+        .split_at_checked(len)
+        .ok_or_else(|| DecodeError::Incomplete(len - bytes.len()))?;
+
+    *bytes = rest;
+
+    Ok(taken)
+}
+
+fn take_array<const N: usize>(bytes: &mut &[u8]) -> DecodeResult<[u8; N]> {
+    Ok(take(bytes, N)?
+        .try_into()
+        .expect("exactly N bytes are taken"))
+}
+
+fn take_u8(bytes: &mut &[u8]) -> DecodeResult<u8> {
+    Ok(take(bytes, 1)?[0])
+}
+
+fn parse_len_encoding(bytes: &mut &[u8]) -> DecodeResult<LenEncodingType> {
+    let first_byte = take_u8(bytes)?;
+
+    match first_byte >> 6 {
+        0b00 => Ok(LenEncodingType::Len((first_byte & 0x3f) as u64)),
+        0b01 => Ok(LenEncodingType::Len(
+            ((first_byte as u64 & 0x3f) << 8) | take_u8(bytes)? as u64,
+        )),
+        0b10 => match first_byte {
+            0x80 => Ok(LenEncodingType::Len(
+                u32::from_be_bytes(take_array(bytes)?) as u64
+            )),
+            0x81 => Ok(LenEncodingType::Len(u64::from_be_bytes(take_array(bytes)?))),
+            _ => invalid("unexpected length encoding"),
+        },
+        _ => match first_byte & 0x3f {
+            0 => Ok(LenEncodingType::StringInteger(1)),
+            1 => Ok(LenEncodingType::StringInteger(2)),
+            2 => Ok(LenEncodingType::StringInteger(4)),
+            3 => Ok(LenEncodingType::Compressed),
+            _ => invalid("unexpected string encoding"),
+        },
+    }
+}
+
+fn decode_len(bytes: &mut &[u8]) -> DecodeResult<u64> {
+    match parse_len_encoding(bytes)? {
+        LenEncodingType::Len(len) => Ok(len),
+        _ => invalid("expected a length"),
+    }
+}
 
-                bytes.advance(1);
+fn decode_string(bytes: &mut &[u8]) -> DecodeResult<Bytes> {
+    match parse_len_encoding(bytes)? {
+        LenEncodingType::Len(len) => {
+            let len = usize::try_from(len).or_else(|_| invalid("string is too long"))?;
 
-                len
-            } else {
-                None
+            Ok(Bytes::copy_from_slice(take(bytes, len)?))
+        }
+        LenEncodingType::StringInteger(int_len) => {
+            let value = match int_len {
+                1 => i8::from_le_bytes(take_array(bytes)?) as i64,
+                2 => i16::from_le_bytes(take_array(bytes)?) as i64,
+                _ => i32::from_le_bytes(take_array(bytes)?) as i64,
+            };
+
+            Ok(Bytes::from(value.to_string()))
+        }
+        LenEncodingType::Compressed => {
+            let compressed_len =
+                usize::try_from(decode_len(bytes)?).or_else(|_| invalid("string is too long"))?;
+            let len =
+                usize::try_from(decode_len(bytes)?).or_else(|_| invalid("string is too long"))?;
+
+            match lzf::decompress(take(bytes, compressed_len)?, len) {
+                Some(value) => Ok(Bytes::from(value)),
+                None => invalid("invalid compressed string"),
             }
         }
-        _ => None,
     }
 }
 
-fn decode_single_value(bytes: &mut BytesMut) -> Option<LenEncodingValue> {
-    let len_encoding_type = parse_len_encoding(bytes)?;
+fn decode_ms(bytes: &mut &[u8]) -> DecodeResult<u64> {
+    Ok(u64::from_le_bytes(take_array(bytes)?))
+}
+
+fn decode_double(bytes: &mut &[u8]) -> DecodeResult<f64> {
+    Ok(f64::from_le_bytes(take_array(bytes)?))
+}
+
+/// Double as written before binary scores: the length of its decimal representation then the
+/// representation itself, lengths past 252 standing for values with none.
+fn decode_string_double(bytes: &mut &[u8]) -> DecodeResult<f64> {
+    match take_u8(bytes)? {
+        253 => Ok(f64::NAN),
+        254 => Ok(f64::INFINITY),
+        255 => Ok(f64::NEG_INFINITY),
+        len => match str::from_utf8(take(bytes, len as usize)?).map(str::parse) {
+            Ok(Ok(value)) => Ok(value),
+            _ => invalid("invalid sorted set score"),
+        },
+    }
+}
+
+/// Stream id as a pair of lengths.
+fn decode_stream_id(bytes: &mut &[u8]) -> DecodeResult<StreamId> {
+    Ok(StreamId {
+        ms: decode_len(bytes)?,
+        seq: decode_len(bytes)?,
+    })
+}
+
+/// Stream id as 16 big endian bytes, like the keys of the stream nodes.
+fn parse_raw_stream_id(raw: &[u8]) -> DecodeResult<StreamId> {
+    let Ok(raw): Result<[u8; 16], _> = raw.try_into() else {
+        return invalid("invalid stream id");
+    };
+    let (ms, seq) = raw.split_at(8);
+
+    Ok(StreamId {
+        ms: u64::from_be_bytes(ms.try_into().expect("ids are split in halves")),
+        seq: u64::from_be_bytes(seq.try_into().expect("ids are split in halves")),
+    })
+}
+
+fn next_int<'a>(items: &mut impl Iterator<Item = ListpackEntry<'a>>) -> DecodeResult<i64> {
+    match items.next().and_then(ListpackEntry::to_int) {
+        Some(value) => Ok(value),
+        None => invalid("invalid stream node"),
+    }
+}
+
+fn next_bytes<'a>(items: &mut impl Iterator<Item = ListpackEntry<'a>>) -> DecodeResult<Bytes> {
+    match items.next() {
+        Some(item) => Ok(item.to_bytes()),
+        None => invalid("invalid stream node"),
+    }
+}
+
+/// Adds the entries of a stream node to the stream. The node starts with a master entry
+/// listing the fields most of its entries share, each entry then being stored relatively
+/// to it: its id as a difference with the master id, and only its values when its fields
+/// are the master ones.
+fn decode_stream_node(stream: &mut Stream, master_id: StreamId, node: &[u8]) -> DecodeResult<()> {
+    let Some(items) = listpack::decode(node) else {
+        return invalid("invalid stream node");
+    };
+    let mut items = items.into_iter();
+
+    let count = next_int(&mut items)? + next_int(&mut items)?;
+    let master_fields = (0..next_int(&mut items)?)
+        .map(|_| next_bytes(&mut items))
+        .collect::<DecodeResult<Vec<_>>>()?;
 
-    match len_encoding_type {
-        LenEncodingType::String(str_len) => {
-            let mut buf = BytesMut::with_capacity(str_len);
-            buf.fill_with(|| 0);
+    if next_int(&mut items)? != 0 {
+        return invalid("invalid stream node");
+    }
+
+    for _ in 0..count {
+        let flags = next_int(&mut items)?;
+        let id = StreamId {
+            ms: master_id.ms.wrapping_add(next_int(&mut items)? as u64),
+            seq: master_id.seq.wrapping_add(next_int(&mut items)? as u64),
+        };
+
+        let entry = if flags & STREAM_ITEM_FLAG_SAME_FIELDS != 0 {
+            master_fields
+                .iter()
+                .map(|field| Ok((field.clone(), next_bytes(&mut items)?)))
+                .collect::<DecodeResult<StreamEntry>>()?
+        } else {
+            (0..next_int(&mut items)?)
+                .map(|_| Ok((next_bytes(&mut items)?, next_bytes(&mut items)?)))
+                .collect::<DecodeResult<StreamEntry>>()?
+        };
 
-            bytes.reader().read_exact(buf.as_mut()).ok()?;
+        // number of items of the entry, to walk the node backwards
+        next_int(&mut items)?;
 
-            Some(LenEncodingValue::String(buf))
+        if flags & STREAM_ITEM_FLAG_DELETED == 0 {
+            stream.entries.insert(id, entry);
         }
-        LenEncodingType::StringInteger(int_len) => match int_len {
-            1 => Some(LenEncodingValue::StringInteger(bytes.get_i8().to_string())),
-            2 => Some(LenEncodingValue::StringInteger(
-                bytes.get_i16_le().to_string(),
-            )),
-            4 => Some(LenEncodingValue::StringInteger(
-                bytes.get_i32_le().to_string(),
-            )),
-            _ => None,
+    }
+
+    if items.next().is_some() {
+        return invalid("invalid stream node");
+    }
+
+    Ok(())
+}
+
+fn decode_consumer_group(
+    rdb_type: &RdbType,
+    stream: &Stream,
+    bytes: &mut &[u8],
+) -> DecodeResult<ConsumerGroup> {
+    let last_delivered_id = decode_stream_id(bytes)?;
+    let entries_read = match rdb_type {
+        // groups did not count the entries they read yet
+        RdbType::StreamListpacks => stream.entries_read_until(&last_delivered_id),
+        _ => match decode_len(bytes)? {
+            u64::MAX => None,
+            entries_read => Some(entries_read),
         },
+    };
+
+    let mut group = ConsumerGroup::new(last_delivered_id, entries_read);
+
+    // the pending entries of the group come first, then those of each consumer tell who
+    // they were delivered to
+    let mut deliveries = BTreeMap::new();
+
+    for _ in 0..decode_len(bytes)? {
+        let id = parse_raw_stream_id(take(bytes, 16)?)?;
+        let delivery_time = decode_ms(bytes)?;
+        let delivery_count = decode_len(bytes)?;
+
+        deliveries.insert(id, (delivery_time, delivery_count));
+    }
+
+    for _ in 0..decode_len(bytes)? {
+        let name = decode_string(bytes)?;
+        let seen_time = decode_ms(bytes)?;
+        let active_time = match rdb_type {
+            RdbType::StreamListpacks3 => match decode_ms(bytes)? as i64 {
+                -1 => None,
+                active_time => Some(active_time as u64),
+            },
+            _ => Some(seen_time),
+        };
+        let mut pending = BTreeSet::new();
+
+        for _ in 0..decode_len(bytes)? {
+            let id = parse_raw_stream_id(take(bytes, 16)?)?;
+
+            let Some((delivery_time, delivery_count)) = deliveries.remove(&id) else {
+                return invalid("pending entry of a consumer is missing from its group");
+            };
+
+            group.pending.insert(
+                id,
+                PendingEntry {
+                    consumer: name.clone(),
+                    delivery_time,
+                    delivery_count,
+                },
+            );
+            pending.insert(id);
+        }
+
+        group.consumers.insert(
+            name,
+            Consumer {
+                seen_time,
+                active_time,
+                pending,
+            },
+        );
     }
+
+    Ok(group)
 }
 
-impl<'a> Decoder for RdbCodec<'a> {
-    type Item = ();
-    type Error = io::Error;
+fn decode_stream(rdb_type: &RdbType, bytes: &mut &[u8]) -> DecodeResult<Stream> {
+    let mut stream = Stream::default();
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        if src.len() < HEADER_STR.len() {
-            return Ok(None);
+    for _ in 0..decode_len(bytes)? {
+        let master_id = parse_raw_stream_id(&decode_string(bytes)?)?;
+        let node = decode_string(bytes)?;
+
+        decode_stream_node(&mut stream, master_id, &node)?;
+    }
+
+    // the length and the first id follow from the entries
+    let len = decode_len(bytes)?;
+    stream.last_id = decode_stream_id(bytes)?;
+
+    if let RdbType::StreamListpacks = rdb_type {
+        stream.entries_added = len;
+    } else {
+        decode_stream_id(bytes)?;
+        stream.max_deleted_id = decode_stream_id(bytes)?;
+        stream.entries_added = decode_len(bytes)?;
+    }
+
+    for _ in 0..decode_len(bytes)? {
+        let name = decode_string(bytes)?;
+        let group = decode_consumer_group(rdb_type, &stream, bytes)?;
+
+        stream.groups.insert(name, group);
+    }
+
+    Ok(stream)
+}
+
+/// Elements of a ziplist or a listpack, which is stored as a string.
+fn decode_compact<'a>(
+    encoded: &'a [u8],
+    decode: fn(&'a [u8]) -> Option<Vec<ListpackEntry<'a>>>,
+    name: &str,
+) -> DecodeResult<Vec<ListpackEntry<'a>>> {
+    match decode(encoded) {
+        Some(entries) => Ok(entries),
+        None => invalid(&format!("invalid {name}")),
+    }
+}
+
+fn insert_score(zset: &mut SortedSet, member: Bytes, score: f64) -> DecodeResult<()> {
+    if score.is_nan() {
+        return invalid("sorted set score is not a number");
+    }
+
+    zset.insert(member, score);
+
+    Ok(())
+}
+
+/// Hash out of its fields each followed by its value.
+fn hash_from_pairs(entries: &[ListpackEntry]) -> DecodeResult<Hash> {
+    if !entries.len().is_multiple_of(2) {
+        return invalid("hash field without a value");
+    }
+
+    let mut hash = Hash::default();
+
+    for pair in entries.chunks_exact(2) {
+        hash.insert(pair[0].to_bytes(), pair[1].to_bytes());
+    }
+
+    Ok(hash)
+}
+
+/// Hash out of its fields each followed by its value and its deadline, 0 being none.
+fn hash_from_triples(entries: &[ListpackEntry]) -> DecodeResult<Hash> {
+    if !entries.len().is_multiple_of(3) {
+        return invalid("hash field without a value or a deadline");
+    }
+
+    let mut hash = Hash::default();
+
+    for triple in entries.chunks_exact(3) {
+        let field = triple[0].to_bytes();
+
+        hash.insert(field.clone(), triple[1].to_bytes());
+
+        match triple[2].to_int() {
+            Some(0) => {}
+            Some(at @ 1..) => hash.set_expire_at(&field, at as u64),
+            _ => return invalid("invalid hash field deadline"),
         }
+    }
 
-        if !self.is_header_read && !src.starts_with(HEADER_STR.as_ref()) {
-            return Err(io::Error::other("unexpected header value"));
-        } else if !self.is_header_read {
-            src.advance(HEADER_STR.len());
-            self.is_header_read = true;
+    Ok(hash)
+}
+
+/// Sorted set out of its members each followed by its score.
+fn zset_from_pairs(entries: &[ListpackEntry]) -> DecodeResult<SortedSet> {
+    if !entries.len().is_multiple_of(2) {
+        return invalid("sorted set member without a score");
+    }
+
+    let mut zset = SortedSet::default();
+
+    for pair in entries.chunks_exact(2) {
+        let score = match pair[1] {
+            ListpackEntry::Int(score) => score as f64,
+            ListpackEntry::String(score) => match str::from_utf8(score).map(str::parse) {
+                Ok(Ok(score)) => score,
+                _ => return invalid("invalid sorted set score"),
+            },
         };
 
+        insert_score(&mut zset, pair[0].to_bytes(), score)?;
+    }
+
+    Ok(zset)
+}
+
+fn decode_object(rdb_type: &RdbType, bytes: &mut &[u8]) -> DecodeResult<Object> {
+    match rdb_type {
+        RdbType::String => Ok(Object::String(decode_string(bytes)?)),
+        RdbType::List => {
+            let mut list = VecDeque::new();
+
+            for _ in 0..decode_len(bytes)? {
+                list.push_back(decode_string(bytes)?);
+            }
+
+            Ok(Object::List(list))
+        }
+        RdbType::ListZiplist => {
+            let ziplist = decode_string(bytes)?;
+            let entries = decode_compact(&ziplist, ziplist::decode, "ziplist")?;
+
+            Ok(Object::List(
+                entries.into_iter().map(ListpackEntry::to_bytes).collect(),
+            ))
+        }
+        RdbType::ListQuicklist | RdbType::ListQuicklist2 => {
+            let mut list = VecDeque::new();
+
+            for _ in 0..decode_len(bytes)? {
+                let container = match rdb_type {
+                    RdbType::ListQuicklist2 => decode_len(bytes)?,
+                    _ => QUICKLIST_NODE_PACKED,
+                };
+                let node = decode_string(bytes)?;
+
+                let entries = match (rdb_type, container) {
+                    (RdbType::ListQuicklist, _) => {
+                        decode_compact(&node, ziplist::decode, "ziplist")?
+                    }
+                    (_, QUICKLIST_NODE_PLAIN) => {
+                        list.push_back(node);
+                        continue;
+                    }
+                    (_, QUICKLIST_NODE_PACKED) => {
+                        decode_compact(&node, listpack::decode, "listpack")?
+                    }
+                    _ => return invalid("unexpected quicklist node container"),
+                };
+
+                list.extend(entries.into_iter().map(ListpackEntry::to_bytes));
+            }
+
+            Ok(Object::List(list))
+        }
+        RdbType::Set => {
+            let mut set = IndexSet::new();
+
+            for _ in 0..decode_len(bytes)? {
+                set.insert(decode_string(bytes)?);
+            }
+
+            Ok(Object::Set(set))
+        }
+        RdbType::SetIntset => {
+            let Some(integers) = intset::decode(&decode_string(bytes)?) else {
+                return invalid("invalid intset");
+            };
+
+            Ok(Object::Set(
+                integers
+                    .into_iter()
+                    .map(|integer| Bytes::from(integer.to_string()))
+                    .collect(),
+            ))
+        }
+        RdbType::SetListpack => {
+            let listpack = decode_string(bytes)?;
+            let entries = decode_compact(&listpack, listpack::decode, "listpack")?;
+
+            Ok(Object::Set(
+                entries.into_iter().map(ListpackEntry::to_bytes).collect(),
+            ))
+        }
+        RdbType::Hash => {
+            let mut hash = Hash::default();
+
+            for _ in 0..decode_len(bytes)? {
+                hash.insert(decode_string(bytes)?, decode_string(bytes)?);
+            }
+
+            Ok(Object::Hash(hash))
+        }
+        RdbType::HashZipmap => {
+            let zipmap = decode_string(bytes)?;
+            let Some(pairs) = zipmap::decode(&zipmap) else {
+                return invalid("invalid zipmap");
+            };
+
+            let mut hash = Hash::default();
+
+            for (field, value) in pairs {
+                hash.insert(Bytes::copy_from_slice(field), Bytes::copy_from_slice(value));
+            }
+
+            Ok(Object::Hash(hash))
+        }
+        RdbType::HashZiplist => {
+            let ziplist = decode_string(bytes)?;
+
+            Ok(Object::Hash(hash_from_pairs(&decode_compact(
+                &ziplist,
+                ziplist::decode,
+                "ziplist",
+            )?)?))
+        }
+        RdbType::HashListpack => {
+            let listpack = decode_string(bytes)?;
+
+            Ok(Object::Hash(hash_from_pairs(&decode_compact(
+                &listpack,
+                listpack::decode,
+                "listpack",
+            )?)?))
+        }
+        RdbType::HashMetadata | RdbType::HashMetadataPreGa => {
+            let mut hash = Hash::default();
+
+            // deadlines are stored relatively to the earliest one, 0 being none, except by
+            // release candidates which stored them as they are
+            let min_expire = match rdb_type {
+                RdbType::HashMetadata => decode_ms(bytes)?,
+                _ => 1,
+            };
+
+            for _ in 0..decode_len(bytes)? {
+                let ttl = decode_len(bytes)?;
+                let (field, value) = (decode_string(bytes)?, decode_string(bytes)?);
+
+                hash.insert(field.clone(), value);
+
+                if ttl > 0 {
+                    let Some(at) = (ttl - 1).checked_add(min_expire) else {
+                        return invalid("invalid hash field deadline");
+                    };
+
+                    hash.set_expire_at(&field, at);
+                }
+            }
+
+            Ok(Object::Hash(hash))
+        }
+        RdbType::HashListpackEx | RdbType::HashListpackExPreGa => {
+            // the earliest deadline, which the fields' own ones already tell
+            if let RdbType::HashListpackEx = rdb_type {
+                decode_ms(bytes)?;
+            }
+
+            let listpack = decode_string(bytes)?;
+
+            Ok(Object::Hash(hash_from_triples(&decode_compact(
+                &listpack,
+                listpack::decode,
+                "listpack",
+            )?)?))
+        }
+        RdbType::ZSet | RdbType::ZSet2 => {
+            let mut zset = SortedSet::default();
+
+            for _ in 0..decode_len(bytes)? {
+                let member = decode_string(bytes)?;
+                let score = match rdb_type {
+                    RdbType::ZSet2 => decode_double(bytes)?,
+                    _ => decode_string_double(bytes)?,
+                };
+
+                insert_score(&mut zset, member, score)?;
+            }
+
+            Ok(Object::ZSet(zset))
+        }
+        RdbType::ZSetZiplist => {
+            let ziplist = decode_string(bytes)?;
+
+            Ok(Object::ZSet(zset_from_pairs(&decode_compact(
+                &ziplist,
+                ziplist::decode,
+                "ziplist",
+            )?)?))
+        }
+        RdbType::ZSetListpack => {
+            let listpack = decode_string(bytes)?;
+
+            Ok(Object::ZSet(zset_from_pairs(&decode_compact(
+                &listpack,
+                listpack::decode,
+                "listpack",
+            )?)?))
+        }
+        RdbType::StreamListpacks | RdbType::StreamListpacks2 | RdbType::StreamListpacks3 => {
+            Ok(Object::Stream(decode_stream(rdb_type, bytes)?))
+        }
+    }
+}
+
+fn decode_key(rdb_type: u8, bytes: &mut &[u8], expires_at: Option<u64>) -> DecodeResult<RdbEntry> {
+    let rdb_type = RdbType::try_from(rdb_type)?;
+    let key = decode_string(bytes)?;
+    let data = decode_object(&rdb_type, bytes)?;
+
+    Ok(RdbEntry::Key {
+        key,
+        data,
+        expires_at,
+    })
+}
+
+impl RdbCodec {
+    fn decode_entry(&self, bytes: &mut &[u8]) -> DecodeResult<RdbEntry> {
+        let mut expires_at = None;
+
+        // the deadline and the eviction information of a key come before it, in any order
         loop {
-            match src.first() {
-                Some(byte) if OpCode::is_valid_opcode(byte) => {
-                    match OpCode::try_from(*byte) {
-                        Ok(opcode) => match opcode {
-                            OpCode::Aux => {
-                                src.advance(1);
-
-                                if let (Some(key), Some(value)) =
-                                    (decode_single_value(src), decode_single_value(src))
-                                {
-                                    println!("OpCode::Aux: key {key:?} - value {value:?}");
-                                } else {
-                                    return Err(io::Error::other(format!(
-                                        "unable to parse {opcode}"
-                                    )));
-                                }
-                            }
-                            OpCode::SelectDb => {
-                                src.advance(1);
-                                parse_len_encoding(src);
-                            }
-                            OpCode::ResizeDb => {
-                                src.advance(1);
-                                parse_len_encoding(src);
-                                parse_len_encoding(src);
-                            }
-                            op @ OpCode::ExpireTime | op @ OpCode::ExpireTimeMs => {
-                                src.advance(1);
-
-                                let is_ms = op == OpCode::ExpireTimeMs;
-
-                                let expire_time_ms: u64 = if is_ms {
-                                    src.get_u64_le()
-                                } else {
-                                    src.get_u32_le() as u64 * 1000
-                                };
-                                let value_type =
-                                    src.first().and_then(|b| RdbType::try_from(*b).ok());
-
-                                match value_type {
-                                    Some(RdbType::String) => {
-                                        src.advance(1);
-
-                                        if let (Some(key), Some(value)) =
-                                            (decode_single_value(src), decode_single_value(src))
-                                        {
-                                            let now = SystemTime::now()
-                                                .duration_since(UNIX_EPOCH)
-                                                .map_err(|e| io::Error::other(e.to_string()))?;
-
-                                            if Duration::from_millis(expire_time_ms).gt(&now) {
-                                                self.map.insert(
-                                                    key.into(),
-                                                    Value::new(
-                                                        value.into(),
-                                                        Some(
-                                                            Duration::from_millis(expire_time_ms)
-                                                                - now,
-                                                        ),
-                                                    ),
-                                                );
-                                            }
-                                        } else {
-                                            return Err(io::Error::other(format!(
-                                                "unable to parse {op}"
-                                            )));
-                                        }
-                                    }
-                                    _ => {
-                                        return Err(io::Error::other(format!(
-                                            "cannot read value type for {op}"
-                                        )));
-                                    }
-                                }
-                            }
-                            OpCode::Eof => {
-                                return Ok(Some(()));
-                            }
-                        },
-                        Err(e) => return Err(e.into()),
+            let byte = take_u8(bytes)?;
+
+            if !OpCode::is_valid_opcode(&byte) {
+                return decode_key(byte, bytes, expires_at);
+            }
+
+            match OpCode::try_from(byte)? {
+                OpCode::Aux => {
+                    return Ok(RdbEntry::Aux(decode_string(bytes)?, decode_string(bytes)?));
+                }
+                OpCode::SelectDb => {
+                    return match decode_len(bytes)? as usize {
+                        db if db < self.databases => Ok(RdbEntry::SelectDb(db)),
+                        _ => Err(DecodeError::Invalid(format!(
+                            "data file was created with a server configured to handle more \
+                             than {} databases",
+                            self.databases
+                        ))),
                     };
                 }
-                Some(byte) if RdbType::is_valid_type(byte) => match RdbType::try_from(*byte) {
-                    Ok(rdb_type) => match rdb_type {
-                        RdbType::String => {
-                            src.advance(1);
-
-                            if let (Some(key), Some(value)) =
-                                (decode_single_value(src), decode_single_value(src))
-                            {
-                                self.map.insert(key.into(), Value::new(value.into(), None));
-                            } else {
-                                return Err(io::Error::other(format!(
-                                    "unable to parse {rdb_type}"
-                                )));
-                            }
-                        }
-                    },
-                    Err(e) => return Err(e.into()),
-                },
-                None => {
-                    return Ok(None);
+                OpCode::ResizeDb => {
+                    return Ok(RdbEntry::ResizeDb {
+                        size: decode_len(bytes)? as usize,
+                        expires: decode_len(bytes)? as usize,
+                    });
+                }
+                OpCode::ExpireTime => {
+                    expires_at = Some(u32::from_le_bytes(take_array(bytes)?) as u64 * 1000);
+                }
+                OpCode::ExpireTimeMs => expires_at = Some(decode_ms(bytes)?),
+                // loaded keys start with a fresh access time and frequency
+                OpCode::Idle => {
+                    decode_len(bytes)?;
+                }
+                OpCode::Freq => {
+                    take_u8(bytes)?;
+                }
+                // slot, number of keys and of keys with a deadline, which only matter to a cluster
+                OpCode::SlotInfo => {
+                    for _ in 0..3 {
+                        decode_len(bytes)?;
+                    }
+                }
+                OpCode::Function2 => return Ok(RdbEntry::Function(decode_string(bytes)?)),
+                OpCode::FunctionPreGa => {
+                    return invalid("functions of release candidates are not supported");
+                }
+                OpCode::ModuleAux => return invalid("module values are not supported"),
+                OpCode::Eof => return Ok(RdbEntry::Eof),
+            }
+        }
+    }
+}
+
+impl Decoder for RdbCodec {
+    type Item = RdbEntry;
+    type Error = io::Error;
+
+    /// Decodes the next entry once all of its bytes are read, leaving them untouched until then.
+    /// An incomplete entry is only decoded again once the bytes it misses are read and the
+    /// buffered ones have doubled, so that entries of any size are decoded in linear time.
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if !self.is_header_read {
+            if src.len() < HEADER_STR.len() {
+                return Ok(None);
+            }
+
+            let (magic, version) = src[..HEADER_STR.len()].split_at(HEADER_MAGIC.len());
+
+            if magic != HEADER_MAGIC.as_bytes() {
+                return Err(io::Error::other("unexpected header value"));
+            }
+
+            match str::from_utf8(version).map(str::parse::<u32>) {
+                Ok(Ok(version @ 1..=MAX_RDB_VERSION)) => self.version = version,
+                Ok(Ok(version)) => {
+                    return Err(io::Error::other(format!(
+                        "can't handle rdb format version {version}"
+                    )));
                 }
-                _ => {
-                    src.advance(1);
+                _ => return Err(io::Error::other("unexpected header value")),
+            }
+
+            self.checksum = crc64(self.checksum, &src[..HEADER_STR.len()]);
+            src.advance(HEADER_STR.len());
+            self.is_header_read = true;
+        }
+
+        if src.len() < self.pending_len {
+            return Ok(None);
+        }
+
+        let mut bytes = &src[..];
+
+        match self.decode_entry(&mut bytes) {
+            Ok(entry) => {
+                let checksum = crc64(self.checksum, &src[..src.len() - bytes.len()]);
+
+                if let RdbEntry::Eof = entry
+                    && self.version >= CHECKSUM_RDB_VERSION
+                {
+                    match take_array(&mut bytes).map(u64::from_le_bytes) {
+                        Ok(0) => eprintln!(
+                            "rdb file was saved with checksum disabled: no check performed"
+                        ),
+                        Ok(stored) if stored != checksum => {
+                            return Err(io::Error::other(format!(
+                                "wrong rdb checksum expected: ({checksum:x}) got: ({stored:x})"
+                            )));
+                        }
+                        Ok(_) => {}
+                        Err(_) => return Ok(None),
+                    }
                 }
+
+                self.checksum = checksum;
+                self.pending_len = 0;
+                src.advance(src.len() - bytes.len());
+
+                Ok(Some(entry))
+            }
+            Err(DecodeError::Incomplete(missing)) => {
+                self.pending_len = src.len().saturating_add(missing).max(src.len() * 2);
+
+                Ok(None)
+            }
+            Err(DecodeError::Invalid(reason)) => Err(io::Error::other(reason)),
+        }
+    }
+
+    /// Decodes what is left once the whole file is read, which no more bytes can complete.
+    fn decode_eof(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        self.pending_len = 0;
+
+        match self.decode(src)? {
+            Some(entry) => Ok(Some(entry)),
+            None if src.is_empty() => Ok(None),
+            None => Err(io::Error::other("bytes remaining on stream")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio_util::codec::Encoder;
+
+    use super::*;
+
+    #[test]
+    fn decode_entries_read_in_chunks() {
+        let mut file = BytesMut::new();
+        let mut codec = RdbCodec::new(1);
+        let list: VecDeque<_> = (0..10_000).map(|i| Bytes::from(i.to_string())).collect();
+
+        for entry in [
+            RdbEntry::Key {
+                key: Bytes::from("list"),
+                data: Object::List(list.clone()),
+                expires_at: None,
+            },
+            RdbEntry::Eof,
+        ] {
+            codec.encode(entry, &mut file).unwrap();
+        }
+
+        let mut codec = RdbCodec::new(1);
+        let mut src = BytesMut::new();
+        let mut entries = Vec::new();
+
+        for chunk in file.chunks(100) {
+            src.extend_from_slice(chunk);
+
+            while let Some(entry) = codec.decode(&mut src).unwrap() {
+                entries.push(entry);
             }
         }
+        while let Some(entry) = codec.decode_eof(&mut src).unwrap() {
+            entries.push(entry);
+        }
+
+        assert!(matches!(
+            &entries[..],
+            [RdbEntry::Key { data: Object::List(decoded), .. }, RdbEntry::Eof] if *decoded == list
+        ));
+
+        // a truncated file is an error however many bytes were awaited
+        let mut codec = RdbCodec::new(1);
+        let mut src = BytesMut::from(&file[..file.len() / 2]);
+
+        assert!(codec.decode(&mut src).unwrap().is_none());
+        assert!(codec.decode_eof(&mut src).is_err());
     }
 }
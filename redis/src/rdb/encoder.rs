@@ -0,0 +1,315 @@
+use std::io;
+
+use bytes::{BufMut, BytesMut};
+use tokio_util::codec::Encoder;
+
+use crate::commands::hash_map::{
+    hash::Hash,
+    object::Object,
+    stream::{ConsumerGroup, Stream, StreamEntry, StreamId},
+};
+use crate::rdb::{
+    constants::{
+        COMPRESSION_MIN_LEN, HEADER_STR, STREAM_ITEM_FLAG_SAME_FIELDS, STREAM_NODE_MAX_ENTRIES,
+    },
+    crc64::crc64,
+    listpack::{self, ListpackEntry},
+    lzf,
+    opcodes::OpCode,
+    parser::{RdbCodec, RdbEntry},
+    types::RdbType,
+};
+
+/// Lengths take 6, 14, 32 or 64 bits depending on how large they are.
+fn encode_len(len: u64, dst: &mut BytesMut) {
+    match len {
+        0..0x40 => dst.put_u8(len as u8),
+        0x40..0x4000 => dst.put_u16(0x4000 | len as u16),
+        _ if len <= u32::MAX as u64 => {
+            dst.put_u8(0x80);
+            dst.put_u32(len as u32);
+        }
+        _ => {
+            dst.put_u8(0x81);
+            dst.put_u64(len);
+        }
+    }
+}
+
+/// Strings holding a small integer, as written by redis, are stored as that integer, and longer
+/// ones are LZF compressed when that makes them shorter.
+fn encode_string(value: &[u8], dst: &mut BytesMut) {
+    let integer = str::from_utf8(value)
+        .ok()
+        .filter(|value| value.len() <= 11)
+        .and_then(|value| value.parse::<i32>().ok())
+        // only when the string comes back the same, without a sign or leading zeros
+        .filter(|integer| integer.to_string().as_bytes() == value);
+
+    match integer {
+        Some(integer) if i8::try_from(integer).is_ok() => {
+            dst.put_u8(0xC0);
+            dst.put_i8(integer as i8);
+        }
+        Some(integer) if i16::try_from(integer).is_ok() => {
+            dst.put_u8(0xC1);
+            dst.put_i16_le(integer as i16);
+        }
+        Some(integer) => {
+            dst.put_u8(0xC2);
+            dst.put_i32_le(integer);
+        }
+        None => match (value.len() > COMPRESSION_MIN_LEN)
+            .then(|| lzf::compress(value))
+            .flatten()
+        {
+            Some(compressed) => {
+                dst.put_u8(0xC3);
+                encode_len(compressed.len() as u64, dst);
+                encode_len(value.len() as u64, dst);
+                dst.put_slice(&compressed);
+            }
+            None => {
+                encode_len(value.len() as u64, dst);
+                dst.put_slice(value);
+            }
+        },
+    }
+}
+
+fn encode_stream_id(id: &StreamId, dst: &mut BytesMut) {
+    encode_len(id.ms, dst);
+    encode_len(id.seq, dst);
+}
+
+fn encode_raw_stream_id(id: &StreamId, dst: &mut BytesMut) {
+    dst.put_u64(id.ms);
+    dst.put_u64(id.seq);
+}
+
+/// Hash whose fields have deadlines: the earliest deadline, then every field preceded by its
+/// deadline relative to the earliest one plus one, 0 standing for none.
+fn encode_hash_metadata(hash: &Hash, dst: &mut BytesMut) {
+    let fields: Vec<_> = hash.iter().collect();
+    let min_expire = fields
+        .iter()
+        .filter_map(|(field, _)| hash.expire_at(field))
+        .min()
+        .unwrap_or_default();
+
+    dst.put_u64_le(min_expire);
+    encode_len(fields.len() as u64, dst);
+
+    for (field, value) in fields {
+        let ttl = hash.expire_at(field).map_or(0, |at| at - min_expire + 1);
+
+        encode_len(ttl, dst);
+        encode_string(field, dst);
+        encode_string(value, dst);
+    }
+}
+
+/// Listpack of a stream node: a master entry with the fields of the first entry, then every
+/// entry relative to it.
+fn encode_stream_node(entries: &[(&StreamId, &StreamEntry)]) -> Vec<u8> {
+    let (master_id, master_entry) = entries[0];
+    let master_fields: Vec<&[u8]> = master_entry.iter().map(|(field, _)| &field[..]).collect();
+
+    let mut items = vec![
+        ListpackEntry::Int(entries.len() as i64),
+        ListpackEntry::Int(0),
+        ListpackEntry::Int(master_fields.len() as i64),
+    ];
+
+    items.extend(
+        master_fields
+            .iter()
+            .map(|field| ListpackEntry::String(field)),
+    );
+    items.push(ListpackEntry::Int(0));
+
+    for (id, entry) in entries {
+        let has_same_fields = entry.len() == master_fields.len()
+            && entry
+                .iter()
+                .zip(&master_fields)
+                .all(|((field, _), master_field)| field == master_field);
+
+        items.push(ListpackEntry::Int(if has_same_fields {
+            STREAM_ITEM_FLAG_SAME_FIELDS
+        } else {
+            0
+        }));
+        items.push(ListpackEntry::Int(id.ms.wrapping_sub(master_id.ms) as i64));
+        items.push(ListpackEntry::Int(id.seq.wrapping_sub(master_id.seq) as i64));
+
+        // besides the values, the entry holds its flags, id and, unless they are the
+        // master ones, its fields and their number
+        let count = if has_same_fields {
+            items.extend(entry.iter().map(|(_, value)| ListpackEntry::String(value)));
+
+            entry.len() + 3
+        } else {
+            items.push(ListpackEntry::Int(entry.len() as i64));
+            items.extend(entry.iter().flat_map(|(field, value)| {
+                [ListpackEntry::String(field), ListpackEntry::String(value)]
+            }));
+
+            entry.len() * 2 + 4
+        };
+
+        items.push(ListpackEntry::Int(count as i64));
+    }
+
+    listpack::encode(items)
+}
+
+fn encode_consumer_group(group: &ConsumerGroup, dst: &mut BytesMut) {
+    encode_stream_id(&group.last_delivered_id, dst);
+    encode_len(group.entries_read.unwrap_or(u64::MAX), dst);
+
+    encode_len(group.pending.len() as u64, dst);
+
+    for (id, pending) in &group.pending {
+        encode_raw_stream_id(id, dst);
+        dst.put_u64_le(pending.delivery_time);
+        encode_len(pending.delivery_count, dst);
+    }
+
+    encode_len(group.consumers.len() as u64, dst);
+
+    for (name, consumer) in &group.consumers {
+        encode_string(name, dst);
+        dst.put_u64_le(consumer.seen_time);
+        dst.put_i64_le(consumer.active_time.map_or(-1, |at| at as i64));
+        encode_len(consumer.pending.len() as u64, dst);
+
+        for id in &consumer.pending {
+            encode_raw_stream_id(id, dst);
+        }
+    }
+}
+
+fn encode_stream(stream: &Stream, dst: &mut BytesMut) {
+    let entries: Vec<_> = stream.entries.iter().collect();
+    let nodes = entries.chunks(STREAM_NODE_MAX_ENTRIES);
+
+    encode_len(nodes.len() as u64, dst);
+
+    for node in nodes {
+        let mut master_id = BytesMut::with_capacity(16);
+
+        encode_raw_stream_id(node[0].0, &mut master_id);
+        encode_string(&master_id, dst);
+        encode_string(&encode_stream_node(node), dst);
+    }
+
+    encode_len(stream.len() as u64, dst);
+    encode_stream_id(&stream.last_id, dst);
+    encode_stream_id(&stream.first_id().unwrap_or_default(), dst);
+    encode_stream_id(&stream.max_deleted_id, dst);
+    encode_len(stream.entries_added, dst);
+
+    encode_len(stream.groups.len() as u64, dst);
+
+    for (name, group) in &stream.groups {
+        encode_string(name, dst);
+        encode_consumer_group(group, dst);
+    }
+}
+
+fn encode_object(data: &Object, dst: &mut BytesMut) {
+    match data {
+        Object::String(value) => encode_string(value, dst),
+        Object::List(list) => {
+            encode_len(list.len() as u64, dst);
+
+            for value in list {
+                encode_string(value, dst);
+            }
+        }
+        Object::Set(set) => {
+            encode_len(set.len() as u64, dst);
+
+            for member in set {
+                encode_string(member, dst);
+            }
+        }
+        Object::Hash(hash) if hash.has_volatile_fields() => encode_hash_metadata(hash, dst),
+        Object::Hash(hash) => {
+            encode_len(hash.len() as u64, dst);
+
+            for (field, value) in hash.iter() {
+                encode_string(field, dst);
+                encode_string(value, dst);
+            }
+        }
+        Object::ZSet(zset) => {
+            encode_len(zset.len() as u64, dst);
+
+            for (member, score) in zset.iter_from_rank(0, false) {
+                encode_string(member, dst);
+                dst.put_f64_le(score);
+            }
+        }
+        Object::Stream(stream) => encode_stream(stream, dst),
+    }
+}
+
+impl Encoder<RdbEntry> for RdbCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: RdbEntry, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let start = dst.len();
+
+        if !self.is_header_written {
+            dst.put_slice(HEADER_STR.as_bytes());
+            self.is_header_written = true;
+        }
+
+        match &item {
+            RdbEntry::Aux(key, value) => {
+                dst.put_u8(OpCode::Aux.into());
+                encode_string(key, dst);
+                encode_string(value, dst);
+            }
+            RdbEntry::SelectDb(db) => {
+                dst.put_u8(OpCode::SelectDb.into());
+                encode_len(*db as u64, dst);
+            }
+            RdbEntry::ResizeDb { size, expires } => {
+                dst.put_u8(OpCode::ResizeDb.into());
+                encode_len(*size as u64, dst);
+                encode_len(*expires as u64, dst);
+            }
+            RdbEntry::Function(library) => {
+                dst.put_u8(OpCode::Function2.into());
+                encode_string(library, dst);
+            }
+            RdbEntry::Key {
+                key,
+                data,
+                expires_at,
+            } => {
+                if let Some(at) = expires_at {
+                    dst.put_u8(OpCode::ExpireTimeMs.into());
+                    dst.put_u64_le(*at);
+                }
+
+                dst.put_u8(RdbType::from(data).into());
+                encode_string(key, dst);
+                encode_object(data, dst);
+            }
+            RdbEntry::Eof => dst.put_u8(OpCode::Eof.into()),
+        }
+
+        self.checksum = crc64(self.checksum, &dst[start..]);
+
+        // the checksum covers everything up to and including the end of file opcode
+        if let RdbEntry::Eof = item {
+            dst.put_u64_le(self.checksum);
+        }
+
+        Ok(())
+    }
+}
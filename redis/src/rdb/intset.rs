@@ -0,0 +1,24 @@
+//! Intsets, the sorted arrays redis stores sets of small integers as: the width of the
+//! integers and their number, then the integers themselves, all little endian.
+
+/// The integers of an intset, `None` if it is malformed.
+pub fn decode(intset: &[u8]) -> Option<Vec<i64>> {
+    let (header, integers) = intset.split_at_checked(8)?;
+    let width = u32::from_le_bytes(header[..4].try_into().ok()?) as usize;
+    let len = u32::from_le_bytes(header[4..].try_into().ok()?) as usize;
+
+    if !matches!(width, 2 | 4 | 8) || integers.len() != width * len {
+        return None;
+    }
+
+    Some(
+        integers
+            .chunks_exact(width)
+            .map(|integer| match width {
+                2 => i16::from_le_bytes([integer[0], integer[1]]) as i64,
+                4 => i32::from_le_bytes(integer.try_into().expect("4 bytes integer")) as i64,
+                _ => i64::from_le_bytes(integer.try_into().expect("8 bytes integer")),
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,244 @@
+//! Listpacks, the compact serialization redis uses for small collections and stream nodes:
+//! a header with the total size and the number of elements, the elements themselves each
+//! followed by its own length so the list can be walked backwards, and a terminator byte.
+
+use bytes::{BufMut, Bytes};
+
+const HEADER_LEN: usize = 6;
+const END: u8 = 0xFF;
+/// Number of elements in the header once there are too many to count.
+const UNKNOWN_LEN: u16 = u16::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListpackEntry<'a> {
+    Int(i64),
+    String(&'a [u8]),
+}
+
+impl ListpackEntry<'_> {
+    /// The entry as stored in a value, integers being formatted in decimal.
+    pub fn to_bytes(self) -> Bytes {
+        match self {
+            ListpackEntry::Int(value) => Bytes::from(value.to_string()),
+            ListpackEntry::String(value) => Bytes::copy_from_slice(value),
+        }
+    }
+
+    /// The entry as an integer, which it might also be stored as a string of.
+    pub fn to_int(self) -> Option<i64> {
+        match self {
+            ListpackEntry::Int(value) => Some(value),
+            ListpackEntry::String(value) => str::from_utf8(value).ok()?.parse().ok(),
+        }
+    }
+}
+
+fn encode_entry(entry: ListpackEntry, dst: &mut Vec<u8>) {
+    let start = dst.len();
+
+    match entry {
+        ListpackEntry::Int(value @ 0..=127) => dst.put_u8(value as u8),
+        ListpackEntry::Int(value @ -4096..=4095) => {
+            let value = (value as u16) & 0x1fff;
+
+            dst.put_u8(0xC0 | (value >> 8) as u8);
+            dst.put_u8(value as u8);
+        }
+        ListpackEntry::Int(value) if i16::try_from(value).is_ok() => {
+            dst.put_u8(0xF1);
+            dst.put_i16_le(value as i16);
+        }
+        ListpackEntry::Int(value @ -0x80_0000..=0x7f_ffff) => {
+            dst.put_u8(0xF2);
+            dst.put_slice(&value.to_le_bytes()[..3]);
+        }
+        ListpackEntry::Int(value) if i32::try_from(value).is_ok() => {
+            dst.put_u8(0xF3);
+            dst.put_i32_le(value as i32);
+        }
+        ListpackEntry::Int(value) => {
+            dst.put_u8(0xF4);
+            dst.put_i64_le(value);
+        }
+        ListpackEntry::String(value) if value.len() < 64 => {
+            dst.put_u8(0x80 | value.len() as u8);
+            dst.put_slice(value);
+        }
+        ListpackEntry::String(value) if value.len() < 4096 => {
+            dst.put_u8(0xE0 | (value.len() >> 8) as u8);
+            dst.put_u8(value.len() as u8);
+            dst.put_slice(value);
+        }
+        ListpackEntry::String(value) => {
+            dst.put_u8(0xF0);
+            dst.put_u32_le(value.len() as u32);
+            dst.put_slice(value);
+        }
+    }
+
+    encode_back_len(dst.len() - start, dst);
+}
+
+/// The length of the entry, most significant bits first and 7 bits per byte, the high bit
+/// of every byte but the first telling there are more when reading backwards.
+fn encode_back_len(len: usize, dst: &mut Vec<u8>) {
+    let groups = back_len_size(len);
+
+    for group in (0..groups).rev() {
+        let bits = ((len >> (7 * group)) & 0x7f) as u8;
+
+        dst.put_u8(if group == groups - 1 {
+            bits
+        } else {
+            bits | 0x80
+        });
+    }
+}
+
+fn back_len_size(len: usize) -> usize {
+    match len {
+        0..=127 => 1,
+        128..16383 => 2,
+        16383..2097151 => 3,
+        2097151..268435455 => 4,
+        _ => 5,
+    }
+}
+
+pub fn encode<'a>(entries: impl IntoIterator<Item = ListpackEntry<'a>>) -> Vec<u8> {
+    let mut listpack = vec![0; HEADER_LEN];
+    let mut len = 0;
+
+    for entry in entries {
+        encode_entry(entry, &mut listpack);
+        len += 1;
+    }
+
+    listpack.put_u8(END);
+
+    let total = (listpack.len() as u32).to_le_bytes();
+    let len = u16::try_from(len).unwrap_or(UNKNOWN_LEN).to_le_bytes();
+
+    listpack[..4].copy_from_slice(&total);
+    listpack[4..HEADER_LEN].copy_from_slice(&len);
+
+    listpack
+}
+
+/// Splits off the first `len` bytes, if there are that many.
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    let (taken, rest) = bytes.split_at_checked(len)?;
+
+    *bytes = rest;
+
+    Some(taken)
+}
+
+fn decode_entry<'a>(bytes: &mut &'a [u8]) -> Option<ListpackEntry<'a>> {
+    let start = *bytes;
+    let first = take(bytes, 1)?[0];
+
+    let sign_extend = |value: u64, bits: u32| ((value << (64 - bits)) as i64) >> (64 - bits);
+    let take_le = |bytes: &mut &[u8], len: usize| {
+        take(bytes, len).map(|taken| {
+            taken
+                .iter()
+                .rev()
+                .fold(0u64, |value, byte| (value << 8) | *byte as u64)
+        })
+    };
+
+    let entry = match first {
+        0x00..=0x7F => ListpackEntry::Int(first as i64),
+        0x80..=0xBF => ListpackEntry::String(take(bytes, (first & 0x3f) as usize)?),
+        0xC0..=0xDF => {
+            let value = ((first as u64 & 0x1f) << 8) | take(bytes, 1)?[0] as u64;
+
+            ListpackEntry::Int(sign_extend(value, 13))
+        }
+        0xE0..=0xEF => {
+            let len = ((first as usize & 0x0f) << 8) | take(bytes, 1)?[0] as usize;
+
+            ListpackEntry::String(take(bytes, len)?)
+        }
+        0xF0 => {
+            let len = take_le(bytes, 4)? as usize;
+
+            ListpackEntry::String(take(bytes, len)?)
+        }
+        0xF1 => ListpackEntry::Int(sign_extend(take_le(bytes, 2)?, 16)),
+        0xF2 => ListpackEntry::Int(sign_extend(take_le(bytes, 3)?, 24)),
+        0xF3 => ListpackEntry::Int(sign_extend(take_le(bytes, 4)?, 32)),
+        0xF4 => ListpackEntry::Int(take_le(bytes, 8)? as i64),
+        _ => return None,
+    };
+
+    let len = start.len() - bytes.len();
+
+    take(bytes, back_len_size(len))?;
+
+    Some(entry)
+}
+
+/// The elements of a listpack, `None` if it is malformed.
+pub fn decode(listpack: &[u8]) -> Option<Vec<ListpackEntry<'_>>> {
+    let mut bytes = listpack;
+    let header = take(&mut bytes, HEADER_LEN)?;
+
+    if u32::from_le_bytes(header[..4].try_into().ok()?) as usize != listpack.len() {
+        return None;
+    }
+
+    let mut entries = vec![];
+
+    loop {
+        match bytes.first()? {
+            &END if bytes.len() == 1 => return Some(entries),
+            _ => entries.push(decode_entry(&mut bytes)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn listpack_roundtrip() {
+        let long = vec![b'x'; 5000];
+        let entries = [
+            ListpackEntry::Int(0),
+            ListpackEntry::Int(127),
+            ListpackEntry::Int(-1),
+            ListpackEntry::Int(-4096),
+            ListpackEntry::Int(4095),
+            ListpackEntry::Int(-32768),
+            ListpackEntry::Int(8_000_000),
+            ListpackEntry::Int(-8_388_608),
+            ListpackEntry::Int(i32::MIN as i64),
+            ListpackEntry::Int(i64::MAX),
+            ListpackEntry::String(b""),
+            ListpackEntry::String(&[b'y'; 200]),
+            ListpackEntry::String(&long),
+        ];
+
+        let listpack = encode(entries);
+
+        assert_eq!(decode(&listpack).as_deref(), Some(&entries[..]));
+    }
+
+    #[test]
+    fn listpack_like_redis() {
+        // "a" then 1024, the layout of the listpack spec
+        let listpack = b"\x0d\x00\x00\x00\x02\x00\x81a\x02\xc4\x00\x02\xff";
+
+        assert_eq!(
+            decode(listpack),
+            Some(vec![ListpackEntry::String(b"a"), ListpackEntry::Int(1024)])
+        );
+        assert_eq!(
+            encode([ListpackEntry::String(b"a"), ListpackEntry::Int(1024)]),
+            listpack
+        );
+    }
+}
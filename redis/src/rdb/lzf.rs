@@ -0,0 +1,156 @@
+//! LZF, the compression redis applies to the longer strings of rdb files: runs of literal
+//! bytes and back references to bytes already output, each preceded by a control byte.
+//! Literal runs have a control byte below 32 holding their length minus one, back references
+//! hold their length minus two in the 3 upper bits, with an extra byte when it is 7 or more,
+//! and their distance minus one in the 5 lower bits and the next byte.
+
+/// Literal bytes a control byte can stand for.
+const MAX_LITERAL_LEN: usize = 32;
+/// Bytes a back reference can stand for, and how far back it can point.
+const MAX_REF_LEN: usize = 264;
+const MAX_REF_OFFSET: usize = 1 << 13;
+/// Back references are looked for among the latest positions of every 3 bytes sequence, by
+/// their hash.
+const HASH_LOG: u32 = 14;
+
+fn hash(bytes: &[u8]) -> usize {
+    let value = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]);
+
+    (value.wrapping_mul(2654435761) >> (32 - HASH_LOG)) as usize
+}
+
+/// Decompresses bytes which decompress to `len` bytes, `None` if they do not.
+pub fn decompress(compressed: &[u8], len: usize) -> Option<Vec<u8>> {
+    // every compressed byte stands for a bounded number of bytes, whatever `len` claims
+    let mut output = Vec::with_capacity(len.min(compressed.len() * MAX_REF_LEN));
+    let mut input = compressed.iter().copied();
+
+    while let Some(control) = input.next() {
+        let control = control as usize;
+
+        if control < MAX_LITERAL_LEN {
+            for _ in 0..=control {
+                output.push(input.next()?);
+            }
+        } else {
+            let ref_len = match control >> 5 {
+                7 => 7 + input.next()? as usize,
+                ref_len => ref_len,
+            } + 2;
+            let offset = ((control & 0x1f) << 8 | input.next()? as usize) + 1;
+            let start = output.len().checked_sub(offset)?;
+
+            // the reference may overlap the bytes it outputs, repeating them
+            for i in start..start + ref_len {
+                output.push(output[i]);
+            }
+        }
+
+        if output.len() > len {
+            return None;
+        }
+    }
+
+    (output.len() == len).then_some(output)
+}
+
+/// Compresses bytes, `None` unless it saves at least 4 bytes like redis requires.
+pub fn compress(input: &[u8]) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut table = vec![usize::MAX; 1 << HASH_LOG];
+    // position of the control byte of the literal run being output, and its length
+    let mut literal_start = 0;
+    let mut literal_len = 0;
+    let mut i = 0;
+
+    output.push(0);
+
+    while i < input.len() {
+        let mut reference = None;
+
+        if i + 2 < input.len() {
+            let hash = hash(&input[i..]);
+            let candidate = std::mem::replace(&mut table[hash], i);
+
+            if candidate < i
+                && i - candidate <= MAX_REF_OFFSET
+                && input[candidate..candidate + 3] == input[i..i + 3]
+            {
+                let max_len = MAX_REF_LEN.min(input.len() - i);
+                let ref_len = (3..max_len)
+                    .find(|&len| input[candidate + len] != input[i + len])
+                    .unwrap_or(max_len);
+
+                reference = Some((i - candidate - 1, ref_len));
+            }
+        }
+
+        match reference {
+            Some((offset, ref_len)) => {
+                if literal_len == 0 {
+                    output.pop();
+                } else {
+                    output[literal_start] = (literal_len - 1) as u8;
+                }
+
+                match ref_len - 2 {
+                    len @ ..7 => output.push(((len << 5) | (offset >> 8)) as u8),
+                    len => {
+                        output.push(((7 << 5) | (offset >> 8)) as u8);
+                        output.push((len - 7) as u8);
+                    }
+                }
+
+                output.push(offset as u8);
+                i += ref_len;
+            }
+            None => {
+                output.push(input[i]);
+                literal_len += 1;
+                i += 1;
+
+                if literal_len < MAX_LITERAL_LEN {
+                    continue;
+                }
+
+                output[literal_start] = (literal_len - 1) as u8;
+            }
+        }
+
+        literal_start = output.len();
+        literal_len = 0;
+        output.push(0);
+    }
+
+    if literal_len == 0 {
+        output.pop();
+    } else {
+        output[literal_start] = (literal_len - 1) as u8;
+    }
+
+    (output.len() + 4 <= input.len()).then_some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lzf_roundtrip() {
+        let input = b"hello hello hello hello, a long enough string to compress, hello".repeat(20);
+        let compressed = compress(&input).unwrap();
+
+        assert!(compressed.len() < input.len() / 4);
+        assert_eq!(decompress(&compressed, input.len()), Some(input));
+        assert_eq!(compress(b"not compressible"), None);
+    }
+
+    #[test]
+    fn lzf_overlapping_reference() {
+        // a literal, a back reference repeating it 37 times and two more literals
+        let compressed = b"\x00a\xe0\x1c\x00\x01aa";
+
+        assert_eq!(decompress(compressed, 40), Some(vec![b'a'; 40]));
+        assert_eq!(decompress(compressed, 39), None);
+    }
+}
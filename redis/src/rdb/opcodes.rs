@@ -5,6 +5,18 @@ use thiserror::Error;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum OpCode {
+    /// Cluster slot of the keys that follow and its number of keys.
+    SlotInfo,
+    /// Library of functions.
+    Function2,
+    /// Library of functions, as written by redis 7.0 release candidates.
+    FunctionPreGa,
+    /// Data of a module that is not attached to any key.
+    ModuleAux,
+    /// Idle time of the key that follows, when evicting least recently used keys.
+    Idle,
+    /// Access frequency of the key that follows, when evicting least frequently used keys.
+    Freq,
     Eof,
     SelectDb,
     ExpireTime,
@@ -15,7 +27,7 @@ pub enum OpCode {
 
 impl OpCode {
     pub fn is_valid_opcode(value: &u8) -> bool {
-        matches!(*value, 0xFA..=0xFF)
+        matches!(*value, 0xF4..=0xFF)
     }
 }
 
@@ -30,6 +42,12 @@ impl TryFrom<u8> for OpCode {
             0xFC => Ok(OpCode::ExpireTimeMs),
             0xFB => Ok(OpCode::ResizeDb),
             0xFA => Ok(OpCode::Aux),
+            0xF9 => Ok(OpCode::Freq),
+            0xF8 => Ok(OpCode::Idle),
+            0xF7 => Ok(OpCode::ModuleAux),
+            0xF6 => Ok(OpCode::FunctionPreGa),
+            0xF5 => Ok(OpCode::Function2),
+            0xF4 => Ok(OpCode::SlotInfo),
             _ => Err(OpCodeParseError::UnexpectedOpCode(value)),
         }
     }
@@ -38,6 +56,12 @@ impl TryFrom<u8> for OpCode {
 impl From<OpCode> for u8 {
     fn from(value: OpCode) -> Self {
         match value {
+            OpCode::SlotInfo => 0xF4,
+            OpCode::Function2 => 0xF5,
+            OpCode::FunctionPreGa => 0xF6,
+            OpCode::ModuleAux => 0xF7,
+            OpCode::Idle => 0xF8,
+            OpCode::Freq => 0xF9,
             OpCode::Aux => 0xFA,
             OpCode::ResizeDb => 0xFB,
             OpCode::ExpireTimeMs => 0xFC,
@@ -51,6 +75,12 @@ impl From<OpCode> for u8 {
 impl Display for OpCode {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            OpCode::SlotInfo => write!(f, "OpCode::SlotInfo"),
+            OpCode::Function2 => write!(f, "OpCode::Function2"),
+            OpCode::FunctionPreGa => write!(f, "OpCode::FunctionPreGa"),
+            OpCode::ModuleAux => write!(f, "OpCode::ModuleAux"),
+            OpCode::Idle => write!(f, "OpCode::Idle"),
+            OpCode::Freq => write!(f, "OpCode::Freq"),
             OpCode::Aux => write!(f, "OpCode::Aux"),
             OpCode::ResizeDb => write!(f, "OpCode::ResizeDb"),
             OpCode::ExpireTimeMs => write!(f, "OpCode::ExpireTimeMs"),
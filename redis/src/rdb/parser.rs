@@ -1,19 +1,54 @@
-use crate::commands::hash_map::{Key, Value};
-use std::collections::HashMap;
-use tokio::sync::RwLockWriteGuard;
+use bytes::Bytes;
 
-type Map<'a> = RwLockWriteGuard<'a, HashMap<Key, Value>>;
+use crate::commands::hash_map::object::Object;
 
-pub struct RdbCodec<'a> {
+/// What an rdb file is made of, in the order it comes in: as decoded when loading a file and
+/// as encoded when writing one.
+#[derive(Debug)]
+pub enum RdbEntry {
+    /// Metadata about the server that wrote the file.
+    Aux(Bytes, Bytes),
+    /// Database the keys that follow belong to.
+    SelectDb(usize),
+    /// Number of keys in the current database and how many of them have a deadline.
+    ResizeDb {
+        size: usize,
+        expires: usize,
+    },
+    /// Code of a library of functions.
+    Function(Bytes),
+    Key {
+        key: Bytes,
+        data: Object,
+        /// Deadline in unix milliseconds.
+        expires_at: Option<u64>,
+    },
+    Eof,
+}
+
+pub struct RdbCodec {
     pub is_header_read: bool,
-    pub map: Map<'a>,
+    pub is_header_written: bool,
+    /// Format version of the file being read, which tells whether it ends with a checksum.
+    pub version: u32,
+    /// Number of databases, which a file being read must not select past.
+    pub databases: usize,
+    /// CRC64 of everything written or read so far, which trails the file.
+    pub checksum: u64,
+    /// Bytes to buffer before decoding again the entry being read, which went on past those
+    /// buffered when it was last tried.
+    pub pending_len: usize,
 }
 
-impl<'a> RdbCodec<'a> {
-    pub fn new(hash_map: Map<'a>) -> Self {
+impl RdbCodec {
+    pub fn new(databases: usize) -> Self {
         Self {
             is_header_read: false,
-            map: hash_map,
+            is_header_written: false,
+            version: 0,
+            databases,
+            checksum: 0,
+            pending_len: 0,
         }
     }
 }
@@ -0,0 +1,291 @@
+use std::{
+    io,
+    path::Path,
+    sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use tokio::{
+    fs::{self, File},
+    task::JoinHandle,
+    time::{MissedTickBehavior, interval},
+};
+use tokio_util::codec::{FramedRead, FramedWrite};
+
+use crate::{
+    commands::{
+        hash_map::{Db, HASH_MAP, Value},
+        utils::unix_time_ms,
+    },
+    config::{CONFIG, SERVER_VERSION},
+    rdb::parser::{RdbCodec, RdbEntry},
+};
+
+/// How often the save rules are checked.
+const SAVE_RULES_PERIOD: Duration = Duration::from_millis(100);
+/// Seconds to wait after a failed save before the rules may trigger another, like redis.
+const SAVE_RETRY_DELAY: u64 = 5;
+
+/// Changes to the dataset and outcome of the saves, reported by `LASTSAVE` and
+/// `INFO persistence`.
+pub(crate) struct SaveState {
+    /// Changes made to the dataset since the server started.
+    pub dirty: AtomicU64,
+    /// Value of `dirty` when the dataset on disk was copied.
+    pub saved_dirty: AtomicU64,
+    /// Unix time in seconds of the last successful save, or of the start of the server.
+    pub last_save: AtomicU64,
+    /// Unix time in seconds of the last save started, successful or not.
+    pub last_attempt: AtomicU64,
+    pub is_last_save_ok: AtomicBool,
+    /// Duration in seconds of the last save, -1 before the first one.
+    pub last_duration: AtomicI64,
+    /// Whether a save is being written, only one can be at a time.
+    pub is_in_progress: AtomicBool,
+}
+
+impl SaveState {
+    pub fn changes_since_last_save(&self) -> u64 {
+        self.dirty
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.saved_dirty.load(Ordering::Relaxed))
+    }
+}
+
+pub(crate) static SAVE_STATE: SaveState = SaveState {
+    dirty: AtomicU64::new(0),
+    saved_dirty: AtomicU64::new(0),
+    last_save: AtomicU64::new(0),
+    last_attempt: AtomicU64::new(0),
+    is_last_save_ok: AtomicBool::new(true),
+    last_duration: AtomicI64::new(-1),
+    is_in_progress: AtomicBool::new(false),
+};
+
+/// Why a save was not written.
+#[derive(Debug)]
+pub(crate) enum SaveError {
+    InProgress,
+    /// Writing the file failed, which the task writing it logs.
+    Failed,
+}
+
+/// Entries of the rdb file of the databases, at the moment it is called. Values are cloned
+/// so they can be written without holding the keyspace lock, which is cheap for most as they
+/// share their bytes with the original.
+fn snapshot(dbs: &[Db]) -> Vec<RdbEntry> {
+    let now = (unix_time_ms() / 1000).to_string();
+
+    let mut entries = vec![
+        RdbEntry::Aux(Bytes::from("redis-ver"), Bytes::from(SERVER_VERSION)),
+        RdbEntry::Aux(Bytes::from("redis-bits"), Bytes::from("64")),
+        RdbEntry::Aux(Bytes::from("ctime"), Bytes::from(now)),
+        RdbEntry::Aux(Bytes::from("aof-base"), Bytes::from("0")),
+    ];
+
+    for db in dbs.iter().filter(|db| db.len() > 0) {
+        let keys: Vec<_> = db.iter().collect();
+
+        entries.push(RdbEntry::SelectDb(db.index()));
+        entries.push(RdbEntry::ResizeDb {
+            size: keys.len(),
+            expires: keys
+                .iter()
+                .filter(|(_, value)| value.expires_at().is_some())
+                .count(),
+        });
+
+        entries.extend(keys.into_iter().map(|(key, value)| RdbEntry::Key {
+            key: key.clone(),
+            data: value.data().clone(),
+            expires_at: value.expires_at(),
+        }));
+    }
+
+    entries.push(RdbEntry::Eof);
+
+    entries
+}
+
+/// Writes the entries to a temporary file renamed over the rdb file once complete, so the
+/// rdb file is never left half written.
+async fn write(path: &Path, entries: Vec<RdbEntry>) -> io::Result<()> {
+    let temp_path = path.with_file_name(format!("temp-{}.rdb", std::process::id()));
+
+    let result = async {
+        let mut framed = FramedWrite::new(File::create(&temp_path).await?, RdbCodec::new(0));
+
+        for entry in entries {
+            framed.feed(entry).await?;
+        }
+
+        framed.flush().await?;
+        framed.into_inner().sync_all().await?;
+
+        fs::rename(&temp_path, path).await
+    }
+    .await;
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path).await;
+    }
+
+    result
+}
+
+fn finish_save(result: &io::Result<()>, dirty: u64, started_at: u64) {
+    let now = unix_time_ms() / 1000;
+
+    match result {
+        Ok(()) => {
+            println!("DB saved on disk");
+
+            SAVE_STATE.saved_dirty.store(dirty, Ordering::Relaxed);
+            SAVE_STATE.last_save.store(now, Ordering::Relaxed);
+        }
+        Err(e) => eprintln!("cannot save rdb file: {e}"),
+    }
+
+    SAVE_STATE
+        .is_last_save_ok
+        .store(result.is_ok(), Ordering::Relaxed);
+    SAVE_STATE
+        .last_duration
+        .store(now.saturating_sub(started_at) as i64, Ordering::Relaxed);
+    SAVE_STATE.is_in_progress.store(false, Ordering::Release);
+}
+
+/// Takes a copy of the dataset and writes it on a task of its own, which completes even if
+/// the client that asked for the save goes away meanwhile.
+async fn spawn_save() -> Result<JoinHandle<io::Result<()>>, SaveError> {
+    // checked again once the copy is taken, this only avoids taking it for nothing
+    if SAVE_STATE.is_in_progress.load(Ordering::Acquire) {
+        return Err(SaveError::InProgress);
+    }
+
+    let path = CONFIG.read().await.rdb_path();
+    let dbs = HASH_MAP.read_all().await;
+    // nothing changes while the keyspace lock is held, so these are exactly the changes saved
+    let dirty = SAVE_STATE.dirty.load(Ordering::Relaxed);
+    let entries = snapshot(&dbs);
+
+    drop(dbs);
+
+    SAVE_STATE
+        .is_in_progress
+        .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+        .map_err(|_| SaveError::InProgress)?;
+
+    let started_at = unix_time_ms() / 1000;
+
+    SAVE_STATE.last_attempt.store(started_at, Ordering::Relaxed);
+
+    Ok(tokio::spawn(async move {
+        let result = write(&path, entries).await;
+
+        finish_save(&result, dirty, started_at);
+
+        result
+    }))
+}
+
+/// Saves the dataset, returning once it is written.
+pub(crate) async fn save() -> Result<(), SaveError> {
+    match spawn_save().await?.await {
+        Ok(Ok(())) => Ok(()),
+        _ => Err(SaveError::Failed),
+    }
+}
+
+/// Saves the dataset as it is now in the background, returning once the copy to write is
+/// taken.
+pub(crate) async fn bgsave() -> Result<(), SaveError> {
+    spawn_save().await.map(|_| ())
+}
+
+/// Periodically saves the dataset in the background once any of the `save` rules is met,
+/// i.e. once there were at least that many changes since the last save and that many
+/// seconds passed.
+pub(crate) async fn run_save_rules() {
+    let mut ticker = interval(SAVE_RULES_PERIOD);
+
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        ticker.tick().await;
+
+        if SAVE_STATE.is_in_progress.load(Ordering::Acquire) {
+            continue;
+        }
+
+        let now = unix_time_ms() / 1000;
+        let changes = SAVE_STATE.changes_since_last_save();
+        let elapsed = now.saturating_sub(SAVE_STATE.last_save.load(Ordering::Relaxed));
+
+        // after a failure, saving is only tried again once in a while
+        let may_retry = SAVE_STATE.is_last_save_ok.load(Ordering::Relaxed)
+            || now.saturating_sub(SAVE_STATE.last_attempt.load(Ordering::Relaxed))
+                > SAVE_RETRY_DELAY;
+
+        let rule = CONFIG
+            .read()
+            .await
+            .save
+            .iter()
+            .find(|rule| changes >= rule.changes && elapsed > rule.seconds)
+            .copied();
+
+        if let Some(rule) = rule
+            && may_retry
+        {
+            println!(
+                "{} changes in {} seconds. Saving...",
+                rule.changes, rule.seconds
+            );
+
+            // a save started meanwhile by a client does just as well
+            let _ = bgsave().await;
+        }
+    }
+}
+
+/// Loads the rdb file into the databases, skipping the keys that expired meanwhile.
+pub(crate) async fn load(path: &Path) -> io::Result<()> {
+    let mut dbs = HASH_MAP.write_all().await;
+    let mut framed = FramedRead::new(File::open(path).await?, RdbCodec::new(dbs.len()));
+    let mut db = 0;
+
+    while let Some(entry) = framed.next().await {
+        match entry? {
+            RdbEntry::SelectDb(index) => db = index,
+            RdbEntry::Key {
+                key,
+                data,
+                expires_at,
+            } => {
+                let value = Value::new(data, expires_at);
+
+                if !value.is_expired() {
+                    dbs[db].insert(key, value);
+                }
+            }
+            RdbEntry::Eof => {
+                // the loaded dataset is the one on disk
+                SAVE_STATE
+                    .saved_dirty
+                    .store(SAVE_STATE.dirty.load(Ordering::Relaxed), Ordering::Relaxed);
+
+                return Ok(());
+            }
+            RdbEntry::Function(_) => eprintln!("functions are not supported, skipping library"),
+            RdbEntry::Aux(..) | RdbEntry::ResizeDb { .. } => {}
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "rdb file ends before its end of file marker",
+    ))
+}
@@ -3,15 +3,55 @@ use std::io;
 
 use thiserror::Error;
 
+use crate::commands::hash_map::object::Object;
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RdbType {
     String,
-}
-
-impl RdbType {
-    pub fn is_valid_type(value: &u8) -> bool {
-        matches!(*value, 0)
-    }
+    /// List of strings, as written before quicklists.
+    List,
+    Set,
+    /// Sorted set with scores written as strings.
+    ZSet,
+    Hash,
+    /// Sorted set with binary scores.
+    ZSet2,
+    /// Hash stored as a zipmap.
+    HashZipmap,
+    /// List stored as a ziplist.
+    ListZiplist,
+    /// Set of integers stored as an intset.
+    SetIntset,
+    /// Sorted set stored as a ziplist of members and scores.
+    ZSetZiplist,
+    /// Hash stored as a ziplist of fields and values.
+    HashZiplist,
+    /// List stored as a sequence of ziplists.
+    ListQuicklist,
+    /// Stream stored as listpack nodes, as written before redis 7.
+    StreamListpacks,
+    /// Hash stored as a listpack of fields and values.
+    HashListpack,
+    /// Sorted set stored as a listpack of members and scores.
+    ZSetListpack,
+    /// List stored as a sequence of listpacks and of large elements on their own.
+    ListQuicklist2,
+    /// Stream stored as listpack nodes, along with its consumer groups' read counters.
+    StreamListpacks2,
+    /// Set stored as a listpack.
+    SetListpack,
+    /// Stream stored as listpack nodes, along with its consumer groups and their consumers'
+    /// active time.
+    StreamListpacks3,
+    /// Hash with deadlines on some fields, as written by redis 7.4 release candidates.
+    HashMetadataPreGa,
+    /// Hash stored as a listpack of fields, values and deadlines, as written by redis 7.4
+    /// release candidates.
+    HashListpackExPreGa,
+    /// Hash with deadlines on some fields, as written since redis 7.4.
+    HashMetadata,
+    /// Hash stored as a listpack of fields, values and deadlines, as written since redis 7.4.
+    HashListpackEx,
 }
 
 impl TryFrom<u8> for RdbType {
@@ -20,6 +60,29 @@ impl TryFrom<u8> for RdbType {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0 => Ok(RdbType::String),
+            1 => Ok(RdbType::List),
+            2 => Ok(RdbType::Set),
+            3 => Ok(RdbType::ZSet),
+            4 => Ok(RdbType::Hash),
+            5 => Ok(RdbType::ZSet2),
+            9 => Ok(RdbType::HashZipmap),
+            10 => Ok(RdbType::ListZiplist),
+            11 => Ok(RdbType::SetIntset),
+            12 => Ok(RdbType::ZSetZiplist),
+            13 => Ok(RdbType::HashZiplist),
+            14 => Ok(RdbType::ListQuicklist),
+            15 => Ok(RdbType::StreamListpacks),
+            16 => Ok(RdbType::HashListpack),
+            17 => Ok(RdbType::ZSetListpack),
+            18 => Ok(RdbType::ListQuicklist2),
+            19 => Ok(RdbType::StreamListpacks2),
+            20 => Ok(RdbType::SetListpack),
+            21 => Ok(RdbType::StreamListpacks3),
+            22 => Ok(RdbType::HashMetadataPreGa),
+            23 => Ok(RdbType::HashListpackExPreGa),
+            24 => Ok(RdbType::HashMetadata),
+            25 => Ok(RdbType::HashListpackEx),
+            6 | 7 => Err(RdbTypeParseError::ModuleType),
             _ => Err(RdbTypeParseError::UnexpectedRdbType(value)),
         }
     }
@@ -29,6 +92,43 @@ impl From<RdbType> for u8 {
     fn from(value: RdbType) -> Self {
         match value {
             RdbType::String => 0,
+            RdbType::List => 1,
+            RdbType::Set => 2,
+            RdbType::ZSet => 3,
+            RdbType::Hash => 4,
+            RdbType::ZSet2 => 5,
+            RdbType::HashZipmap => 9,
+            RdbType::ListZiplist => 10,
+            RdbType::SetIntset => 11,
+            RdbType::ZSetZiplist => 12,
+            RdbType::HashZiplist => 13,
+            RdbType::ListQuicklist => 14,
+            RdbType::StreamListpacks => 15,
+            RdbType::HashListpack => 16,
+            RdbType::ZSetListpack => 17,
+            RdbType::ListQuicklist2 => 18,
+            RdbType::StreamListpacks2 => 19,
+            RdbType::SetListpack => 20,
+            RdbType::StreamListpacks3 => 21,
+            RdbType::HashMetadataPreGa => 22,
+            RdbType::HashListpackExPreGa => 23,
+            RdbType::HashMetadata => 24,
+            RdbType::HashListpackEx => 25,
+        }
+    }
+}
+
+impl From<&Object> for RdbType {
+    /// The type a value is written as.
+    fn from(value: &Object) -> Self {
+        match value {
+            Object::String(_) => RdbType::String,
+            Object::List(_) => RdbType::List,
+            Object::Set(_) => RdbType::Set,
+            Object::Hash(hash) if hash.has_volatile_fields() => RdbType::HashMetadata,
+            Object::Hash(_) => RdbType::Hash,
+            Object::ZSet(_) => RdbType::ZSet2,
+            Object::Stream(_) => RdbType::StreamListpacks3,
         }
     }
 }
@@ -37,6 +137,28 @@ impl Display for RdbType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RdbType::String => write!(f, "RdbType::String"),
+            RdbType::List => write!(f, "RdbType::List"),
+            RdbType::Set => write!(f, "RdbType::Set"),
+            RdbType::ZSet => write!(f, "RdbType::ZSet"),
+            RdbType::Hash => write!(f, "RdbType::Hash"),
+            RdbType::ZSet2 => write!(f, "RdbType::ZSet2"),
+            RdbType::HashZipmap => write!(f, "RdbType::HashZipmap"),
+            RdbType::ListZiplist => write!(f, "RdbType::ListZiplist"),
+            RdbType::SetIntset => write!(f, "RdbType::SetIntset"),
+            RdbType::ZSetZiplist => write!(f, "RdbType::ZSetZiplist"),
+            RdbType::HashZiplist => write!(f, "RdbType::HashZiplist"),
+            RdbType::ListQuicklist => write!(f, "RdbType::ListQuicklist"),
+            RdbType::StreamListpacks => write!(f, "RdbType::StreamListpacks"),
+            RdbType::HashListpack => write!(f, "RdbType::HashListpack"),
+            RdbType::ZSetListpack => write!(f, "RdbType::ZSetListpack"),
+            RdbType::ListQuicklist2 => write!(f, "RdbType::ListQuicklist2"),
+            RdbType::StreamListpacks2 => write!(f, "RdbType::StreamListpacks2"),
+            RdbType::SetListpack => write!(f, "RdbType::SetListpack"),
+            RdbType::StreamListpacks3 => write!(f, "RdbType::StreamListpacks3"),
+            RdbType::HashMetadataPreGa => write!(f, "RdbType::HashMetadataPreGa"),
+            RdbType::HashListpackExPreGa => write!(f, "RdbType::HashListpackExPreGa"),
+            RdbType::HashMetadata => write!(f, "RdbType::HashMetadata"),
+            RdbType::HashListpackEx => write!(f, "RdbType::HashListpackEx"),
         }
     }
 }
@@ -45,6 +167,8 @@ impl Display for RdbType {
 pub enum RdbTypeParseError {
     #[error("unexpected type: {0}")]
     UnexpectedRdbType(u8),
+    #[error("module values are not supported")]
+    ModuleType,
 }
 
 impl From<RdbTypeParseError> for io::Error {
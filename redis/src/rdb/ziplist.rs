@@ -0,0 +1,111 @@
+//! Ziplists, the compact serialization of small collections that listpacks replaced in redis 7:
+//! a header with the total size, the offset of the last element and the number of elements,
+//! the elements themselves each preceded by the length of the previous one, and a terminator
+//! byte.
+
+use crate::rdb::listpack::ListpackEntry;
+
+const HEADER_LEN: usize = 10;
+const END: u8 = 0xFF;
+/// First byte of a previous element length that takes 4 more bytes.
+const BIG_PREV_LEN: u8 = 0xFE;
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    let (taken, rest) = bytes.split_at_checked(len)?;
+
+    *bytes = rest;
+
+    Some(taken)
+}
+
+fn take_int<const N: usize>(bytes: &mut &[u8]) -> Option<i64> {
+    let taken = take(bytes, N)?;
+    let value = taken
+        .iter()
+        .rev()
+        .fold(0u64, |value, byte| (value << 8) | *byte as u64);
+
+    // sign extended from the width of the integer
+    Some(((value << (64 - 8 * N)) as i64) >> (64 - 8 * N))
+}
+
+fn decode_entry<'a>(bytes: &mut &'a [u8]) -> Option<ListpackEntry<'a>> {
+    if take(bytes, 1)?[0] == BIG_PREV_LEN {
+        take(bytes, 4)?;
+    }
+
+    let encoding = take(bytes, 1)?[0];
+
+    let entry = match encoding >> 6 {
+        0b00 => ListpackEntry::String(take(bytes, (encoding & 0x3f) as usize)?),
+        0b01 => {
+            let len = ((encoding as usize & 0x3f) << 8) | take(bytes, 1)?[0] as usize;
+
+            ListpackEntry::String(take(bytes, len)?)
+        }
+        0b10 => {
+            let len = u32::from_be_bytes(take(bytes, 4)?.try_into().ok()?) as usize;
+
+            ListpackEntry::String(take(bytes, len)?)
+        }
+        _ => match encoding {
+            0xC0 => ListpackEntry::Int(take_int::<2>(bytes)?),
+            0xD0 => ListpackEntry::Int(take_int::<4>(bytes)?),
+            0xE0 => ListpackEntry::Int(take_int::<8>(bytes)?),
+            0xF0 => ListpackEntry::Int(take_int::<3>(bytes)?),
+            0xFE => ListpackEntry::Int(take_int::<1>(bytes)?),
+            // small integers are stored in the encoding itself, off by one
+            0xF1..=0xFD => ListpackEntry::Int((encoding & 0x0f) as i64 - 1),
+            _ => return None,
+        },
+    };
+
+    Some(entry)
+}
+
+/// The elements of a ziplist, `None` if it is malformed.
+pub fn decode(ziplist: &[u8]) -> Option<Vec<ListpackEntry<'_>>> {
+    let mut bytes = ziplist;
+    let header = take(&mut bytes, HEADER_LEN)?;
+
+    if u32::from_le_bytes(header[..4].try_into().ok()?) as usize != ziplist.len() {
+        return None;
+    }
+
+    let mut entries = vec![];
+
+    loop {
+        match bytes.first()? {
+            &END if bytes.len() == 1 => return Some(entries),
+            _ => entries.push(decode_entry(&mut bytes)?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ziplist_entries() {
+        // "a", 12, -2, 1000 and 100000, along the layout of the ziplist spec
+        let ziplist = b"\x1c\x00\x00\x00\x16\x00\x00\x00\x05\x00\
+            \x00\x01a\
+            \x03\xfd\
+            \x02\xfe\xfe\
+            \x03\xc0\xe8\x03\
+            \x04\xf0\xa0\x86\x01\
+            \xff";
+
+        assert_eq!(
+            decode(ziplist),
+            Some(vec![
+                ListpackEntry::String(b"a"),
+                ListpackEntry::Int(12),
+                ListpackEntry::Int(-2),
+                ListpackEntry::Int(1000),
+                ListpackEntry::Int(100000),
+            ])
+        );
+    }
+}
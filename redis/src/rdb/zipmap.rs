@@ -0,0 +1,47 @@
+//! Zipmaps, the compact serialization of small hashes before redis 2.6: the number of
+//! fields, then every field and its value each preceded by its length, values also being
+//! followed by some unused bytes, and a terminator byte.
+
+const END: u8 = 0xFF;
+/// First byte of a length that takes 4 more bytes.
+const BIG_LEN: u8 = 0xFE;
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    let (taken, rest) = bytes.split_at_checked(len)?;
+
+    *bytes = rest;
+
+    Some(taken)
+}
+
+fn take_len(bytes: &mut &[u8]) -> Option<usize> {
+    match take(bytes, 1)?[0] {
+        BIG_LEN => Some(u32::from_le_bytes(take(bytes, 4)?.try_into().ok()?) as usize),
+        END => None,
+        len => Some(len as usize),
+    }
+}
+
+/// The fields and values of a zipmap, `None` if it is malformed.
+pub fn decode(zipmap: &[u8]) -> Option<Vec<(&[u8], &[u8])>> {
+    let mut bytes = zipmap;
+    let mut pairs = vec![];
+
+    // the number of fields is only a hint once there are 254 or more
+    take(&mut bytes, 1)?;
+
+    loop {
+        if bytes.first()? == &END {
+            return bytes.len().eq(&1).then_some(pairs);
+        }
+
+        let field_len = take_len(&mut bytes)?;
+        let field = take(&mut bytes, field_len)?;
+        let value_len = take_len(&mut bytes)?;
+        let free = take(&mut bytes, 1)?[0] as usize;
+        let value = take(&mut bytes, value_len)?;
+
+        take(&mut bytes, free)?;
+        pairs.push((field, value));
+    }
+}
@@ -3,19 +3,29 @@ use std::io;
 use bytes::BytesMut;
 use tokio_util::codec::Decoder;
 
-use crate::resp::{parser::RespCodec, parser::rules::parse_rule_factory, types::RespType};
+use crate::resp::{
+    parser::RespCodec,
+    parser::rules::{inline::skip_empty_lines, request_parse_rule_factory},
+    types::RespType,
+};
 
 impl Decoder for RespCodec {
     type Item = RespType;
     type Error = io::Error;
 
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // the first byte of a request tells its type, so the empty lines before it are dropped
+        // rather than taken for an inline request
+        if self.rule.is_none() {
+            skip_empty_lines(src);
+        }
+
         if src.len() < 2 {
             return Ok(None);
         }
 
         if self.rule.is_none() {
-            self.rule = Some(parse_rule_factory(src[0])?)
+            self.rule = Some(request_parse_rule_factory(src[0])?)
         }
 
         let Some(rule) = self.rule.as_mut() else {
@@ -32,3 +42,99 @@ impl Decoder for RespCodec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resp::types::RespDouble;
+
+    fn decode_all(input: &[u8]) -> RespType {
+        let mut codec = RespCodec::new();
+        let mut src = BytesMut::from(input);
+
+        let value = codec.decode(&mut src).unwrap().unwrap();
+
+        assert!(src.is_empty());
+
+        value
+    }
+
+    #[test]
+    fn resp3_scalars() {
+        assert_eq!(decode_all(b"_\r\n"), RespType::Null);
+        assert_eq!(decode_all(b"#t\r\n"), RespType::Boolean(Some(true)));
+        assert_eq!(
+            decode_all(b",-1.5\r\n"),
+            RespType::Double(Some(RespDouble(-1.5)))
+        );
+        assert_eq!(
+            decode_all(b"(3492890328409238509324850943850943825024385\r\n"),
+            RespType::BigNumber(Some("3492890328409238509324850943850943825024385".into()))
+        );
+        assert_eq!(
+            decode_all(b"=15\r\ntxt:Some string\r\n"),
+            RespType::VerbatimString(Some(("txt".into(), "Some string".into())))
+        );
+        assert_eq!(
+            decode_all(b"!10\r\nSYNTAX err\r\n"),
+            RespType::BlobError(Some("SYNTAX err".into()))
+        );
+    }
+
+    #[test]
+    fn resp3_aggregates() {
+        assert_eq!(
+            decode_all(b"%2\r\n+first\r\n:1\r\n+second\r\n~1\r\n$0\r\n\r\n"),
+            RespType::Map(Some(vec![
+                (
+                    RespType::SimpleString(Some("first".into())),
+                    RespType::Integer(Some(1))
+                ),
+                (
+                    RespType::SimpleString(Some("second".into())),
+                    RespType::Set(Some(vec![RespType::BulkString(Some("".into()))]))
+                ),
+            ]))
+        );
+        assert_eq!(decode_all(b"*-1\r\n"), RespType::Array(None));
+        assert_eq!(decode_all(b"$-1\r\n"), RespType::BulkString(None));
+    }
+
+    #[test]
+    fn empty_line_then_array() {
+        let ping = Some(RespType::Array(Some(vec![RespType::BulkString(Some(
+            "PING".into(),
+        ))])));
+        let mut codec = RespCodec::new();
+        let mut src = BytesMut::from(&b"\r\n*1\r\n$4\r\nPING\r\n"[..]);
+
+        assert_eq!(codec.decode(&mut src).unwrap(), ping);
+        assert!(src.is_empty());
+
+        src.extend_from_slice(b" \r\n");
+
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(b"*1\r\n$4\r\nPING\r\n");
+
+        assert_eq!(codec.decode(&mut src).unwrap(), ping);
+    }
+
+    #[test]
+    fn partial_frames() {
+        let mut codec = RespCodec::new();
+        let mut src = BytesMut::from(&b"*2\r\n$3\r\nGET\r\n$5\r\nhel"[..]);
+
+        assert_eq!(codec.decode(&mut src).unwrap(), None);
+
+        src.extend_from_slice(b"lo\r\n");
+
+        assert_eq!(
+            codec.decode(&mut src).unwrap(),
+            Some(RespType::Array(Some(vec![
+                RespType::BulkString(Some("GET".into())),
+                RespType::BulkString(Some("hello".into())),
+            ])))
+        );
+    }
+}
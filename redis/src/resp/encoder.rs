@@ -5,10 +5,15 @@ use tokio_util::codec::Encoder;
 
 use crate::resp::{constants::END_SEQ, parser::RespCodec, types::RespType};
 
-impl Encoder<RespType> for RespCodec {
-    type Error = io::Error;
+impl RespCodec {
+    fn encode_blob(dst: &mut BytesMut, blob: &[u8]) {
+        dst.extend_from_slice(blob.len().to_string().as_ref());
+        dst.extend_from_slice(&END_SEQ);
+        dst.extend_from_slice(blob);
+        dst.extend_from_slice(&END_SEQ);
+    }
 
-    fn encode(&mut self, item: RespType, dst: &mut BytesMut) -> Result<(), Self::Error> {
+    fn encode_nested(&mut self, item: RespType, dst: &mut BytesMut) -> Result<(), io::Error> {
         let resp_byte_type = u8::try_from(&item).map_err(|e| {
             io::Error::new(
                 ErrorKind::InvalidInput,
@@ -24,34 +29,58 @@ impl Encoder<RespType> for RespCodec {
                 dst.extend_from_slice(&END_SEQ);
             }
             RespType::Integer(Some(int)) => {
-                if int < 0 {
-                    dst.put_u8(b'-');
-                }
                 dst.extend_from_slice(int.to_string().as_ref());
                 dst.extend_from_slice(&END_SEQ);
             }
-            RespType::SimpleString(Some(simple_str)) => {
+            RespType::SimpleString(Some(simple_str)) | RespType::BigNumber(Some(simple_str)) => {
                 dst.extend_from_slice(simple_str.as_ref());
                 dst.extend_from_slice(&END_SEQ);
             }
             RespType::BulkString(bulk_str) => match bulk_str {
-                Some(str) => {
-                    dst.extend_from_slice(str.len().to_string().as_ref());
-                    dst.extend_from_slice(&END_SEQ);
-                    dst.extend_from_slice(str.as_ref());
-                    dst.extend_from_slice(&END_SEQ);
-                }
+                Some(str) => Self::encode_blob(dst, str.as_ref()),
                 None => {
                     dst.extend_from_slice(b"-1");
                     dst.extend_from_slice(&END_SEQ);
                 }
             },
-            RespType::Array(Some(arr)) => {
+            RespType::BlobError(Some(err)) => Self::encode_blob(dst, err.as_ref()),
+            RespType::VerbatimString(Some((format, str))) => {
+                let mut verbatim = BytesMut::with_capacity(format.len() + 1 + str.len());
+
+                verbatim.extend_from_slice(format.as_ref());
+                verbatim.put_u8(b':');
+                verbatim.extend_from_slice(str.as_ref());
+
+                Self::encode_blob(dst, verbatim.as_ref());
+            }
+            RespType::Null => dst.extend_from_slice(&END_SEQ),
+            RespType::Boolean(Some(b)) => {
+                dst.put_u8(if b { b't' } else { b'f' });
+                dst.extend_from_slice(&END_SEQ);
+            }
+            RespType::Double(Some(double)) => {
+                dst.extend_from_slice(double.to_string().as_ref());
+                dst.extend_from_slice(&END_SEQ);
+            }
+            RespType::Array(None) => {
+                dst.extend_from_slice(b"-1");
+                dst.extend_from_slice(&END_SEQ);
+            }
+            RespType::Array(Some(arr)) | RespType::Set(Some(arr)) | RespType::Push(Some(arr)) => {
                 dst.extend_from_slice(arr.len().to_string().as_ref());
                 dst.extend_from_slice(&END_SEQ);
 
                 for nested_resp in arr {
-                    self.encode(nested_resp, dst)?;
+                    self.encode_nested(nested_resp, dst)?;
+                }
+            }
+            RespType::Map(Some(pairs)) | RespType::Attribute(Some(pairs)) => {
+                dst.extend_from_slice(pairs.len().to_string().as_ref());
+                dst.extend_from_slice(&END_SEQ);
+
+                for (key, value) in pairs {
+                    self.encode_nested(key, dst)?;
+                    self.encode_nested(value, dst)?;
                 }
             }
             _ => {
@@ -65,3 +94,67 @@ impl Encoder<RespType> for RespCodec {
         Ok(())
     }
 }
+
+impl Encoder<RespType> for RespCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, item: RespType, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match item.into_protocol(self.protocol) {
+            Some(item) => self.encode_nested(item, dst),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resp::types::{ProtocolVersion, RespDouble};
+
+    fn encode(protocol: ProtocolVersion, item: RespType) -> BytesMut {
+        let mut codec = RespCodec::new();
+        let mut dst = BytesMut::new();
+
+        codec.protocol = protocol;
+        codec.encode(item, &mut dst).unwrap();
+
+        dst
+    }
+
+    #[test]
+    fn map_is_flattened_for_resp2() {
+        let map = RespType::Map(Some(vec![(
+            RespType::BulkString(Some("score".into())),
+            RespType::Double(Some(RespDouble(1.5))),
+        )]));
+
+        assert_eq!(
+            encode(ProtocolVersion::Resp2, map.clone()).as_ref(),
+            b"*2\r\n$5\r\nscore\r\n$3\r\n1.5\r\n"
+        );
+        assert_eq!(
+            encode(ProtocolVersion::Resp3, map).as_ref(),
+            b"%1\r\n$5\r\nscore\r\n,1.5\r\n"
+        );
+    }
+
+    #[test]
+    fn nulls_follow_protocol() {
+        assert_eq!(
+            encode(ProtocolVersion::Resp2, RespType::Null).as_ref(),
+            b"$-1\r\n"
+        );
+        assert_eq!(
+            encode(ProtocolVersion::Resp2, RespType::Array(None)).as_ref(),
+            b"*-1\r\n"
+        );
+        assert_eq!(
+            encode(ProtocolVersion::Resp3, RespType::BulkString(None)).as_ref(),
+            b"_\r\n"
+        );
+        assert_eq!(
+            encode(ProtocolVersion::Resp2, RespType::Integer(Some(-7))).as_ref(),
+            b":-7\r\n"
+        );
+    }
+}
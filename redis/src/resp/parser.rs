@@ -1,13 +1,17 @@
-use crate::resp::parser::rules::types::BoxedRespParseRule;
+use crate::resp::{parser::rules::types::BoxedRespParseRule, types::ProtocolVersion};
 
 pub(crate) mod rules;
 
 pub struct RespCodec {
     pub rule: Option<BoxedRespParseRule>,
+    pub protocol: ProtocolVersion,
 }
 
 impl RespCodec {
     pub fn new() -> Self {
-        Self { rule: None }
+        Self {
+            rule: None,
+            protocol: ProtocolVersion::default(),
+        }
     }
 }
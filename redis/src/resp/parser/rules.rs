@@ -2,16 +2,29 @@ use std::io;
 
 use crate::resp::{
     parser::rules::{
-        array::ArraysParseRule, bulk_string::BulkStringsParseRule, integer::IntegersParseRule,
-        simple_string::SimpleStringsParseRule, types::BoxedRespParseRule,
+        aggregate::{AggregateKind, AggregatesParseRule},
+        big_number::BigNumbersParseRule,
+        blob::{BlobKind, BlobsParseRule},
+        boolean::BooleansParseRule,
+        double::DoublesParseRule,
+        inline::InlineCommandsParseRule,
+        integer::IntegersParseRule,
+        null::NullsParseRule,
+        simple_string::SimpleStringsParseRule,
+        types::BoxedRespParseRule,
     },
     types::{RespType, RespTypeError},
 };
 use thiserror::Error;
 
-pub(crate) mod array;
-pub(crate) mod bulk_string;
+pub(crate) mod aggregate;
+pub(crate) mod big_number;
+pub(crate) mod blob;
+pub(crate) mod boolean;
+pub(crate) mod double;
+pub(crate) mod inline;
 pub(crate) mod integer;
+pub(crate) mod null;
 pub(crate) mod simple_string;
 pub(crate) mod types;
 mod utils;
@@ -19,15 +32,36 @@ mod utils;
 pub(crate) fn parse_rule_factory(byte: u8) -> Result<BoxedRespParseRule, ParseRuleFactoryError> {
     match RespType::try_from(byte)? {
         RespType::SimpleString(_) => Ok(Box::new(SimpleStringsParseRule::new())),
-        RespType::BulkString(_) => Ok(Box::new(BulkStringsParseRule::new())),
-        RespType::Array(_) => Ok(Box::new(ArraysParseRule::new())),
+        RespType::BulkString(_) => Ok(Box::new(BlobsParseRule::new(BlobKind::BulkString))),
+        RespType::BlobError(_) => Ok(Box::new(BlobsParseRule::new(BlobKind::BlobError))),
+        RespType::VerbatimString(_) => Ok(Box::new(BlobsParseRule::new(BlobKind::VerbatimString))),
+        RespType::Array(_) => Ok(Box::new(AggregatesParseRule::new(AggregateKind::Array))),
+        RespType::Set(_) => Ok(Box::new(AggregatesParseRule::new(AggregateKind::Set))),
+        RespType::Push(_) => Ok(Box::new(AggregatesParseRule::new(AggregateKind::Push))),
+        RespType::Map(_) => Ok(Box::new(AggregatesParseRule::new(AggregateKind::Map))),
+        RespType::Attribute(_) => Ok(Box::new(AggregatesParseRule::new(AggregateKind::Attribute))),
         RespType::Integer(_) => Ok(Box::new(IntegersParseRule::new())),
+        RespType::Null => Ok(Box::new(NullsParseRule::new())),
+        RespType::Boolean(_) => Ok(Box::new(BooleansParseRule::new())),
+        RespType::Double(_) => Ok(Box::new(DoublesParseRule::new())),
+        RespType::BigNumber(_) => Ok(Box::new(BigNumbersParseRule::new())),
         _ => Err(ParseRuleFactoryError::UnexpectedRespType(format!(
             "{byte} resp type is not supported"
         ))),
     }
 }
 
+/// Same as [`parse_rule_factory`], but anything that does not start with a resp type byte is
+/// treated as an inline command. Only valid for top level requests, never for nested values.
+pub(crate) fn request_parse_rule_factory(
+    byte: u8,
+) -> Result<BoxedRespParseRule, ParseRuleFactoryError> {
+    match RespType::try_from(byte) {
+        Ok(_) => parse_rule_factory(byte),
+        Err(_) => Ok(Box::new(InlineCommandsParseRule::new())),
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum ParseRuleFactoryError {
     #[error("unable to get parse rule for provided type: {0}")]
@@ -0,0 +1,144 @@
+use bytes::BytesMut;
+
+use crate::resp::{
+    parser::rules::{
+        parse_rule_factory,
+        types::{BoxedRespParseRule, ParseRule, RespParseRule, RespRuleParseError},
+        utils::take_line,
+    },
+    types::RespType,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum AggregateKind {
+    Array,
+    Set,
+    Push,
+    Map,
+    Attribute,
+}
+
+impl AggregateKind {
+    fn is_keyed(&self) -> bool {
+        matches!(self, AggregateKind::Map | AggregateKind::Attribute)
+    }
+}
+
+/// Count-prefixed nested types: `*` arrays, `~` sets, `>` pushes and
+/// `%` maps / `|` attributes, the latter two holding a key and a value per entry.
+#[derive(Debug)]
+pub(crate) struct AggregatesParseRule {
+    kind: AggregateKind,
+    values: Vec<RespType>,
+    size: Option<usize>,
+    current_parse_rule: Option<BoxedRespParseRule>,
+}
+
+impl AggregatesParseRule {
+    pub(crate) fn new(kind: AggregateKind) -> Self {
+        Self {
+            kind,
+            values: vec![],
+            size: None,
+            current_parse_rule: None,
+        }
+    }
+
+    fn get_size(&self) -> usize {
+        self.size.unwrap_or(0)
+    }
+
+    fn parse_next_rule(
+        &mut self,
+        bytes: &mut BytesMut,
+    ) -> Result<Option<RespType>, RespRuleParseError> {
+        if self.get_size() < 1 {
+            return Ok(None);
+        }
+
+        if self.current_parse_rule.is_none() {
+            match bytes.first() {
+                Some(b) => self.current_parse_rule = Some(parse_rule_factory(*b)?),
+                None => return Ok(None),
+            }
+        }
+
+        let rule_parse_result: Option<RespType> = self
+            .current_parse_rule
+            .as_mut()
+            .ok_or(RespRuleParseError::UnexpectedSubruleParseError(
+                "unexpected empty subrule".to_string(),
+            ))?
+            .next(bytes)?;
+
+        let Some(rule_parse_result) = rule_parse_result else {
+            return Ok(None);
+        };
+
+        self.size = Some(self.get_size() - 1);
+        self.current_parse_rule = None;
+
+        Ok(Some(rule_parse_result))
+    }
+
+    fn build(&mut self) -> RespType {
+        let values = std::mem::take(&mut self.values);
+
+        if !self.kind.is_keyed() {
+            return match self.kind {
+                AggregateKind::Set => RespType::Set(Some(values)),
+                AggregateKind::Push => RespType::Push(Some(values)),
+                _ => RespType::Array(Some(values)),
+            };
+        }
+
+        let mut pairs = Vec::with_capacity(values.len() / 2);
+        let mut values = values.into_iter();
+
+        while let (Some(key), Some(value)) = (values.next(), values.next()) {
+            pairs.push((key, value));
+        }
+
+        match self.kind {
+            AggregateKind::Attribute => RespType::Attribute(Some(pairs)),
+            _ => RespType::Map(Some(pairs)),
+        }
+    }
+}
+
+impl ParseRule for AggregatesParseRule {
+    type Output = RespType;
+
+    fn next(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Output>, RespRuleParseError> {
+        if self.size.is_none() {
+            let Some(line) = take_line(bytes) else {
+                return Ok(None);
+            };
+
+            let size = str::parse::<i64>(str::from_utf8(&line)?)?;
+
+            match (self.kind, size) {
+                (AggregateKind::Array, -1) => return Ok(Some(RespType::Array(None))),
+                (_, size) if size < 0 => {
+                    return Err(RespRuleParseError::InvalidValue(format!(
+                        "unexpected aggregate size: {size}"
+                    )));
+                }
+                (kind, size) if kind.is_keyed() => self.size = Some(size as usize * 2),
+                (_, size) => self.size = Some(size as usize),
+            }
+        }
+
+        while let Some(parse_result) = self.parse_next_rule(bytes)? {
+            self.values.push(parse_result);
+        }
+
+        if self.get_size() == 0 {
+            return Ok(Some(self.build()));
+        }
+
+        Ok(None)
+    }
+}
+
+impl RespParseRule for AggregatesParseRule {}
@@ -0,0 +1,41 @@
+use bytes::BytesMut;
+
+use crate::resp::{
+    parser::rules::{
+        types::{ParseRule, RespParseRule, RespRuleParseError},
+        utils::take_line,
+    },
+    types::RespType,
+};
+
+#[derive(Debug)]
+pub(crate) struct BigNumbersParseRule {}
+
+impl BigNumbersParseRule {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ParseRule for BigNumbersParseRule {
+    type Output = RespType;
+
+    fn next(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Output>, RespRuleParseError> {
+        let Some(line) = take_line(bytes) else {
+            return Ok(None);
+        };
+
+        let digits = line.strip_prefix(b"-").unwrap_or(&line);
+
+        if digits.is_empty() || !digits.iter().all(u8::is_ascii_digit) {
+            return Err(RespRuleParseError::InvalidValue(format!(
+                "big number must consist of digits only, got: {}",
+                String::from_utf8_lossy(&line)
+            )));
+        }
+
+        Ok(Some(RespType::BigNumber(Some(line))))
+    }
+}
+
+impl RespParseRule for BigNumbersParseRule {}
@@ -0,0 +1,94 @@
+use bytes::{Buf, BytesMut};
+
+use crate::resp::{
+    parser::rules::{
+        types::{ParseRule, RespParseRule, RespRuleParseError},
+        utils::{get_end_seq_len, is_end_seq, take_line},
+    },
+    types::RespType,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum BlobKind {
+    BulkString,
+    BlobError,
+    VerbatimString,
+}
+
+/// Length-prefixed, binary safe types: `$` bulk strings, `!` blob errors and `=` verbatim strings.
+#[derive(Debug)]
+pub(crate) struct BlobsParseRule {
+    kind: BlobKind,
+    size: Option<i64>,
+}
+
+impl BlobsParseRule {
+    pub(crate) fn new(kind: BlobKind) -> Self {
+        Self { kind, size: None }
+    }
+
+    fn build(&self, blob: BytesMut) -> Result<RespType, RespRuleParseError> {
+        match self.kind {
+            BlobKind::BulkString => Ok(RespType::BulkString(Some(blob))),
+            BlobKind::BlobError => Ok(RespType::BlobError(Some(blob))),
+            BlobKind::VerbatimString => {
+                if blob.len() < 4 || blob[3] != b':' {
+                    return Err(RespRuleParseError::InvalidValue(
+                        "verbatim string must start with a three bytes format and ':'".to_string(),
+                    ));
+                }
+
+                let mut blob = blob;
+                let format = blob.split_to(3);
+
+                blob.advance(1);
+
+                Ok(RespType::VerbatimString(Some((format, blob))))
+            }
+        }
+    }
+}
+
+impl ParseRule for BlobsParseRule {
+    type Output = RespType;
+
+    fn next(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Output>, RespRuleParseError> {
+        if self.size.is_none() {
+            let Some(line) = take_line(bytes) else {
+                return Ok(None);
+            };
+
+            let size = str::parse::<i64>(str::from_utf8(&line)?)?;
+
+            match (self.kind, size) {
+                (BlobKind::BulkString, -1) => return Ok(Some(RespType::BulkString(None))),
+                (_, size) if size < 0 => {
+                    return Err(RespRuleParseError::InvalidValue(format!(
+                        "unexpected blob size: {size}"
+                    )));
+                }
+                _ => self.size = Some(size),
+            }
+        }
+
+        let size = self.size.unwrap_or(0) as usize;
+
+        if bytes.len() < size + get_end_seq_len() {
+            return Ok(None);
+        }
+
+        if !is_end_seq(&bytes[size..size + get_end_seq_len()]) {
+            return Err(RespRuleParseError::InvalidValue(
+                "blob is not terminated right after its declared size".to_string(),
+            ));
+        }
+
+        let blob = bytes.split_to(size);
+
+        bytes.advance(get_end_seq_len());
+
+        Ok(Some(self.build(blob)?))
+    }
+}
+
+impl RespParseRule for BlobsParseRule {}
@@ -0,0 +1,39 @@
+use bytes::BytesMut;
+
+use crate::resp::{
+    parser::rules::{
+        types::{ParseRule, RespParseRule, RespRuleParseError},
+        utils::take_line,
+    },
+    types::RespType,
+};
+
+#[derive(Debug)]
+pub(crate) struct BooleansParseRule {}
+
+impl BooleansParseRule {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ParseRule for BooleansParseRule {
+    type Output = RespType;
+
+    fn next(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Output>, RespRuleParseError> {
+        let Some(line) = take_line(bytes) else {
+            return Ok(None);
+        };
+
+        match line.as_ref() {
+            b"t" => Ok(Some(RespType::Boolean(Some(true)))),
+            b"f" => Ok(Some(RespType::Boolean(Some(false)))),
+            _ => Err(RespRuleParseError::InvalidValue(format!(
+                "boolean must be either 't' or 'f', got: {}",
+                String::from_utf8_lossy(&line)
+            ))),
+        }
+    }
+}
+
+impl RespParseRule for BooleansParseRule {}
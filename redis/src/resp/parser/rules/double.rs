@@ -0,0 +1,34 @@
+use bytes::BytesMut;
+
+use crate::resp::{
+    parser::rules::{
+        types::{ParseRule, RespParseRule, RespRuleParseError},
+        utils::take_line,
+    },
+    types::{RespDouble, RespType},
+};
+
+#[derive(Debug)]
+pub(crate) struct DoublesParseRule {}
+
+impl DoublesParseRule {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ParseRule for DoublesParseRule {
+    type Output = RespType;
+
+    fn next(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Output>, RespRuleParseError> {
+        let Some(line) = take_line(bytes) else {
+            return Ok(None);
+        };
+
+        Ok(Some(RespType::Double(Some(RespDouble(str::parse::<f64>(
+            str::from_utf8(&line)?,
+        )?)))))
+    }
+}
+
+impl RespParseRule for DoublesParseRule {}
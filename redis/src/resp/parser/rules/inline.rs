@@ -0,0 +1,240 @@
+use bytes::{Buf, BytesMut};
+
+use crate::resp::{
+    parser::rules::types::{ParseRule, RespParseRule, RespRuleParseError},
+    types::RespType,
+};
+
+const MAX_INLINE_LEN: usize = 64 * 1024;
+
+/// Plain text commands as typed into `nc` or telnet, e.g. `SET foo "bar baz"\r\n`,
+/// turned into the same array of bulk strings a RESP client would send.
+#[derive(Debug)]
+pub(crate) struct InlineCommandsParseRule {}
+
+impl InlineCommandsParseRule {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+fn is_space(byte: u8) -> bool {
+    matches!(byte, b' ' | b'\t' | b'\n' | b'\r' | 0x0b | 0x0c)
+}
+
+/// Drops the empty lines the bytes start with, which redis skips between requests, e.g. a
+/// bare enter in telnet.
+pub(crate) fn skip_empty_lines(bytes: &mut BytesMut) {
+    let blank_len = bytes
+        .iter()
+        .position(|byte| !is_space(*byte))
+        .unwrap_or(bytes.len());
+
+    if let Some(new_line_idx) = bytes[..blank_len].iter().rposition(|byte| *byte == b'\n') {
+        bytes.advance(new_line_idx + 1);
+    }
+}
+
+fn hex_digit(byte: u8) -> Option<u8> {
+    (byte as char).to_digit(16).map(|d| d as u8)
+}
+
+/// Splits a line into arguments following the quoting rules of `redis-cli`:
+/// double quoted arguments support `\n`, `\r`, `\t`, `\b`, `\a` and `\xHH` escapes,
+/// single quoted ones only `\'`, and a closing quote must be followed by a space.
+fn split_args(line: &[u8]) -> Result<Vec<BytesMut>, RespRuleParseError> {
+    let mut args = vec![];
+    let mut idx = 0;
+
+    loop {
+        while idx < line.len() && is_space(line[idx]) {
+            idx += 1;
+        }
+
+        if idx == line.len() {
+            return Ok(args);
+        }
+
+        let mut arg = BytesMut::new();
+        let mut in_double_quotes = false;
+        let mut in_single_quotes = false;
+
+        loop {
+            let Some(&byte) = line.get(idx) else {
+                if in_double_quotes || in_single_quotes {
+                    return Err(RespRuleParseError::InvalidValue(
+                        "unbalanced quotes in request".to_string(),
+                    ));
+                }
+
+                break;
+            };
+
+            if in_double_quotes {
+                match (byte, line.get(idx + 1).copied()) {
+                    (b'\\', Some(b'x')) => {
+                        match (
+                            line.get(idx + 2).and_then(|b| hex_digit(*b)),
+                            line.get(idx + 3).and_then(|b| hex_digit(*b)),
+                        ) {
+                            (Some(high), Some(low)) => {
+                                arg.extend_from_slice(&[high << 4 | low]);
+                                idx += 3;
+                            }
+                            _ => {
+                                arg.extend_from_slice(b"x");
+                                idx += 1;
+                            }
+                        }
+                    }
+                    (b'\\', Some(escaped)) => {
+                        arg.extend_from_slice(&[match escaped {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => 0x08,
+                            b'a' => 0x07,
+                            other => other,
+                        }]);
+                        idx += 1;
+                    }
+                    (b'"', next) => {
+                        if next.is_some_and(|b| !is_space(b)) {
+                            return Err(RespRuleParseError::InvalidValue(
+                                "closing quote must be followed by a space".to_string(),
+                            ));
+                        }
+
+                        idx += 1;
+
+                        break;
+                    }
+                    (byte, _) => arg.extend_from_slice(&[byte]),
+                }
+            } else if in_single_quotes {
+                match (byte, line.get(idx + 1).copied()) {
+                    (b'\\', Some(b'\'')) => {
+                        arg.extend_from_slice(b"'");
+                        idx += 1;
+                    }
+                    (b'\'', next) => {
+                        if next.is_some_and(|b| !is_space(b)) {
+                            return Err(RespRuleParseError::InvalidValue(
+                                "closing quote must be followed by a space".to_string(),
+                            ));
+                        }
+
+                        idx += 1;
+
+                        break;
+                    }
+                    (byte, _) => arg.extend_from_slice(&[byte]),
+                }
+            } else {
+                match byte {
+                    byte if is_space(byte) => break,
+                    b'"' => in_double_quotes = true,
+                    b'\'' => in_single_quotes = true,
+                    byte => arg.extend_from_slice(&[byte]),
+                }
+            }
+
+            idx += 1;
+        }
+
+        args.push(arg);
+    }
+}
+
+impl ParseRule for InlineCommandsParseRule {
+    type Output = RespType;
+
+    fn next(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Output>, RespRuleParseError> {
+        loop {
+            let Some(new_line_idx) = bytes.iter().position(|b| *b == b'\n') else {
+                if bytes.len() > MAX_INLINE_LEN {
+                    return Err(RespRuleParseError::InvalidValue(
+                        "too big inline request".to_string(),
+                    ));
+                }
+
+                return Ok(None);
+            };
+
+            let line = bytes.split_to(new_line_idx);
+
+            bytes.advance(1);
+
+            let args = split_args(line.strip_suffix(b"\r").unwrap_or(&line))?;
+
+            // empty lines are skipped just like redis does, e.g. a bare enter in telnet
+            if args.is_empty() {
+                continue;
+            }
+
+            return Ok(Some(RespType::Array(Some(
+                args.into_iter()
+                    .map(|arg| RespType::BulkString(Some(arg)))
+                    .collect(),
+            ))));
+        }
+    }
+}
+
+impl RespParseRule for InlineCommandsParseRule {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &[u8]) -> Option<RespType> {
+        InlineCommandsParseRule::new()
+            .next(&mut BytesMut::from(input))
+            .unwrap()
+    }
+
+    fn command(args: &[&[u8]]) -> Option<RespType> {
+        Some(RespType::Array(Some(
+            args.iter()
+                .map(|arg| RespType::BulkString(Some(BytesMut::from(*arg))))
+                .collect(),
+        )))
+    }
+
+    #[test]
+    fn plain_arguments() {
+        assert_eq!(parse(b"PING\r\n"), command(&[b"PING"]));
+        assert_eq!(
+            parse(b"  SET  foo\tbar\n"),
+            command(&[b"SET", b"foo", b"bar"])
+        );
+        assert_eq!(parse(b"SET foo"), None);
+    }
+
+    #[test]
+    fn quoted_arguments() {
+        assert_eq!(
+            parse(b"SET \"foo bar\" 'it\\'s' \"\\x41\\n\" \"\"\r\n"),
+            command(&[b"SET", b"foo bar", b"it's", b"A\n", b""])
+        );
+    }
+
+    #[test]
+    fn empty_lines_are_skipped() {
+        assert_eq!(parse(b"\r\n\nECHO hi\r\n"), command(&[b"ECHO", b"hi"]));
+    }
+
+    #[test]
+    fn unbalanced_quotes() {
+        let mut rule = InlineCommandsParseRule::new();
+
+        assert!(
+            rule.next(&mut BytesMut::from(&b"SET \"foo\r\n"[..]))
+                .is_err()
+        );
+        assert!(
+            rule.next(&mut BytesMut::from(&b"SET \"foo\"bar\r\n"[..]))
+                .is_err()
+        );
+    }
+}
@@ -1,9 +1,9 @@
-use bytes::{Buf, BytesMut};
+use bytes::BytesMut;
 
 use crate::resp::{
     parser::rules::{
         types::{ParseRule, RespParseRule, RespRuleParseError},
-        utils::{get_end_seq_len, is_end_seq},
+        utils::take_line,
     },
     types::RespType,
 };
@@ -21,25 +21,13 @@ impl ParseRule for IntegersParseRule {
     type Output = RespType;
 
     fn next(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Output>, RespRuleParseError> {
-        if bytes.len() < 4 {
+        let Some(line) = take_line(bytes) else {
             return Ok(None);
-        }
+        };
 
-        for idx in 1..bytes.len() - 1 {
-            if !is_end_seq(&bytes[idx..idx + get_end_seq_len()]) {
-                continue;
-            }
-
-            let integer = Some(RespType::Integer(Some(str::parse::<i64>(str::from_utf8(
-                &bytes[1..idx],
-            )?)?)));
-
-            bytes.advance(idx + get_end_seq_len());
-
-            return Ok(integer);
-        }
-
-        Ok(None)
+        Ok(Some(RespType::Integer(Some(str::parse::<i64>(
+            str::from_utf8(&line)?,
+        )?))))
     }
 }
 
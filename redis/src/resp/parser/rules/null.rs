@@ -0,0 +1,34 @@
+use bytes::BytesMut;
+
+use crate::resp::{
+    parser::rules::{
+        types::{ParseRule, RespParseRule, RespRuleParseError},
+        utils::take_line,
+    },
+    types::RespType,
+};
+
+#[derive(Debug)]
+pub(crate) struct NullsParseRule {}
+
+impl NullsParseRule {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+impl ParseRule for NullsParseRule {
+    type Output = RespType;
+
+    fn next(&mut self, bytes: &mut BytesMut) -> Result<Option<Self::Output>, RespRuleParseError> {
+        match take_line(bytes) {
+            Some(line) if line.is_empty() => Ok(Some(RespType::Null)),
+            Some(_) => Err(RespRuleParseError::InvalidValue(
+                "null cannot carry a payload".to_string(),
+            )),
+            None => Ok(None),
+        }
+    }
+}
+
+impl RespParseRule for NullsParseRule {}
@@ -1,5 +1,10 @@
 use bytes::BytesMut;
-use std::{fmt::Debug, io, num::ParseIntError, str::Utf8Error};
+use std::{
+    fmt::Debug,
+    io,
+    num::{ParseFloatError, ParseIntError},
+    str::Utf8Error,
+};
 use thiserror::Error;
 
 use crate::resp::{parser::rules::ParseRuleFactoryError, types::RespType};
@@ -22,6 +27,8 @@ pub enum RespRuleParseError {
     Utf8ParseError(String),
     #[error("unable to parse with subrule: {0}")]
     UnexpectedSubruleParseError(String),
+    #[error("unexpected value: {0}")]
+    InvalidValue(String),
 }
 
 impl From<ParseIntError> for RespRuleParseError {
@@ -30,6 +37,12 @@ impl From<ParseIntError> for RespRuleParseError {
     }
 }
 
+impl From<ParseFloatError> for RespRuleParseError {
+    fn from(error: ParseFloatError) -> Self {
+        Self::NumericParseError(error.to_string())
+    }
+}
+
 impl From<Utf8Error> for RespRuleParseError {
     fn from(error: Utf8Error) -> Self {
         Self::Utf8ParseError(error.to_string())
@@ -1,3 +1,5 @@
+use bytes::{Buf, BytesMut};
+
 use crate::resp::constants::END_SEQ;
 
 pub(crate) fn is_end_seq(bytes: &[u8]) -> bool {
@@ -7,3 +9,24 @@ pub(crate) fn is_end_seq(bytes: &[u8]) -> bool {
 pub(crate) fn get_end_seq_len() -> usize {
     END_SEQ.len()
 }
+
+pub(crate) fn find_end_seq(bytes: &[u8], from: usize) -> Option<usize> {
+    if bytes.len() < from + get_end_seq_len() {
+        return None;
+    }
+
+    (from..bytes.len() - 1).find(|idx| is_end_seq(&bytes[*idx..*idx + get_end_seq_len()]))
+}
+
+/// Consumes a `<type byte><payload>\r\n` line and returns its payload,
+/// or `None` while the line is not fully buffered yet.
+pub(crate) fn take_line(bytes: &mut BytesMut) -> Option<BytesMut> {
+    let idx = find_end_seq(bytes, 1)?;
+
+    let mut line = bytes.split_to(idx);
+
+    line.advance(1);
+    bytes.advance(get_end_seq_len());
+
+    Some(line)
+}
@@ -1,4 +1,10 @@
-use std::{io, num::ParseIntError};
+use std::{
+    cmp::Ordering,
+    fmt::Display,
+    hash::{Hash, Hasher},
+    io,
+    num::ParseIntError,
+};
 
 use bytes::BytesMut;
 use thiserror::Error;
@@ -10,6 +16,89 @@ pub enum RespType {
     Array(Option<Vec<RespType>>),
     Integer(Option<i64>),
     RError(String),
+    Null,
+    Boolean(Option<bool>),
+    Double(Option<RespDouble>),
+    BigNumber(Option<BytesMut>),
+    Map(Option<Vec<(RespType, RespType)>>),
+    Set(Option<Vec<RespType>>),
+    VerbatimString(Option<(BytesMut, BytesMut)>),
+    Push(Option<Vec<RespType>>),
+    Attribute(Option<Vec<(RespType, RespType)>>),
+    BlobError(Option<BytesMut>),
+}
+
+impl RespType {
+    pub fn bulk_string(value: impl AsRef<[u8]>) -> Self {
+        RespType::BulkString(Some(BytesMut::from(value.as_ref())))
+    }
+
+    pub fn simple_string(value: &str) -> Self {
+        RespType::SimpleString(Some(value.into()))
+    }
+
+    /// Rewrites the value into the shape expected by a client speaking the given protocol:
+    /// RESP2 has no native maps, sets, doubles etc., so those are flattened into the
+    /// closest RESP2 types, while RESP3 replaces the RESP2 null bulk string and null array
+    /// with its dedicated null type. Attributes cannot be expressed in RESP2 and are dropped.
+    pub fn into_protocol(self, protocol: ProtocolVersion) -> Option<RespType> {
+        match protocol {
+            ProtocolVersion::Resp2 => self.into_resp2(),
+            ProtocolVersion::Resp3 => Some(self.into_resp3()),
+        }
+    }
+
+    fn into_resp2(self) -> Option<RespType> {
+        let flatten = |values: Vec<RespType>| -> Vec<RespType> {
+            values.into_iter().filter_map(|v| v.into_resp2()).collect()
+        };
+        let flatten_pairs = |pairs: Vec<(RespType, RespType)>| -> Vec<RespType> {
+            pairs
+                .into_iter()
+                .flat_map(|(k, v)| [k, v])
+                .filter_map(|v| v.into_resp2())
+                .collect()
+        };
+
+        Some(match self {
+            RespType::Null => RespType::BulkString(None),
+            RespType::Boolean(Some(b)) => RespType::Integer(Some(b as i64)),
+            RespType::Double(Some(d)) => RespType::BulkString(Some(d.to_string().as_str().into())),
+            RespType::BigNumber(Some(n)) => RespType::BulkString(Some(n)),
+            RespType::VerbatimString(Some((_, s))) => RespType::BulkString(Some(s)),
+            RespType::BlobError(Some(e)) => {
+                RespType::RError(String::from_utf8_lossy(e.as_ref()).to_string())
+            }
+            RespType::Array(Some(values))
+            | RespType::Set(Some(values))
+            | RespType::Push(Some(values)) => RespType::Array(Some(flatten(values))),
+            RespType::Map(Some(pairs)) => RespType::Array(Some(flatten_pairs(pairs))),
+            RespType::Attribute(_) => return None,
+            other => other,
+        })
+    }
+
+    fn into_resp3(self) -> RespType {
+        let convert = |values: Vec<RespType>| -> Vec<RespType> {
+            values.into_iter().map(|v| v.into_resp3()).collect()
+        };
+        let convert_pairs = |pairs: Vec<(RespType, RespType)>| -> Vec<(RespType, RespType)> {
+            pairs
+                .into_iter()
+                .map(|(k, v)| (k.into_resp3(), v.into_resp3()))
+                .collect()
+        };
+
+        match self {
+            RespType::BulkString(None) | RespType::Array(None) => RespType::Null,
+            RespType::Array(Some(values)) => RespType::Array(Some(convert(values))),
+            RespType::Set(Some(values)) => RespType::Set(Some(convert(values))),
+            RespType::Push(Some(values)) => RespType::Push(Some(convert(values))),
+            RespType::Map(Some(pairs)) => RespType::Map(Some(convert_pairs(pairs))),
+            RespType::Attribute(Some(pairs)) => RespType::Attribute(Some(convert_pairs(pairs))),
+            other => other,
+        }
+    }
 }
 
 impl TryFrom<u8> for RespType {
@@ -21,6 +110,16 @@ impl TryFrom<u8> for RespType {
             b'$' => Ok(Self::BulkString(None)),
             b'*' => Ok(Self::Array(None)),
             b':' => Ok(Self::Integer(None)),
+            b'_' => Ok(Self::Null),
+            b'#' => Ok(Self::Boolean(None)),
+            b',' => Ok(Self::Double(None)),
+            b'(' => Ok(Self::BigNumber(None)),
+            b'%' => Ok(Self::Map(None)),
+            b'~' => Ok(Self::Set(None)),
+            b'=' => Ok(Self::VerbatimString(None)),
+            b'>' => Ok(Self::Push(None)),
+            b'|' => Ok(Self::Attribute(None)),
+            b'!' => Ok(Self::BlobError(None)),
             _ => Err(RespTypeError::UnsupportedType(byte as char)),
         }
     }
@@ -36,6 +135,16 @@ impl TryFrom<&RespType> for u8 {
             RespType::Array(_) => b'*',
             RespType::Integer(_) => b':',
             RespType::RError(_) => b'-',
+            RespType::Null => b'_',
+            RespType::Boolean(_) => b'#',
+            RespType::Double(_) => b',',
+            RespType::BigNumber(_) => b'(',
+            RespType::Map(_) => b'%',
+            RespType::Set(_) => b'~',
+            RespType::VerbatimString(_) => b'=',
+            RespType::Push(_) => b'>',
+            RespType::Attribute(_) => b'|',
+            RespType::BlobError(_) => b'!',
         })
     }
 }
@@ -46,6 +155,65 @@ impl From<io::Error> for RespType {
     }
 }
 
+/// RESP3 double, compared and hashed by its bit pattern so that `RespType` keeps
+/// its total `Eq`/`Ord`/`Hash` implementations.
+#[derive(Debug, Clone, Copy)]
+pub struct RespDouble(pub f64);
+
+impl PartialEq for RespDouble {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for RespDouble {}
+
+impl PartialOrd for RespDouble {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RespDouble {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for RespDouble {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+
+impl Display for RespDouble {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_nan() {
+            write!(f, "nan")
+        } else if self.0.is_infinite() {
+            write!(f, "{}", if self.0 > 0.0 { "inf" } else { "-inf" })
+        } else {
+            write!(f, "{}", self.0)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProtocolVersion {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl From<ProtocolVersion> for i64 {
+    fn from(value: ProtocolVersion) -> Self {
+        match value {
+            ProtocolVersion::Resp2 => 2,
+            ProtocolVersion::Resp3 => 3,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum RespTypeError {
     #[error("unsupported resp type provided: {0}")]